@@ -0,0 +1,87 @@
+//! Generates the server side of the part of the node's gRPC API v2 that is
+//! served by the fake node used in the tests. The messages are those generated
+//! in the SDK, so only the service is described here.
+use tonic_build::manual::{Builder, Method, Service};
+
+/// The methods of the `Queries` service that the fake node serves, given by
+/// their name, input type, output type and whether they stream the response.
+const METHODS: &[(&str, &str, &str, bool)] = &[
+    ("GetAccountList", "BlockHashInput", "AccountAddress", true),
+    (
+        "GetNextAccountSequenceNumber",
+        "AccountAddress",
+        "NextAccountSequenceNumber",
+        false,
+    ),
+    ("SendBlockItem", "SendBlockItemRequest", "TransactionHash", false),
+    ("GetInstanceInfo", "InstanceInfoRequest", "InstanceInfo", false),
+    (
+        "InvokeInstance",
+        "InvokeInstanceRequest",
+        "InvokeInstanceResponse",
+        false,
+    ),
+    (
+        "GetInstanceState",
+        "InstanceInfoRequest",
+        "InstanceStateKvPair",
+        true,
+    ),
+    (
+        "GetCryptographicParameters",
+        "BlockHashInput",
+        "CryptographicParameters",
+        false,
+    ),
+    ("GetAccountInfo", "AccountInfoRequest", "AccountInfo", false),
+    (
+        "GetBlockChainParameters",
+        "BlockHashInput",
+        "ChainParameters",
+        false,
+    ),
+    ("GetConsensusInfo", "Empty", "ConsensusInfo", false),
+    ("GetBakerList", "BlockHashInput", "BakerId", true),
+    ("GetPoolInfo", "PoolInfoRequest", "PoolInfoResponse", false),
+    ("GetIdentityProviders", "BlockHashInput", "IpInfo", true),
+    ("GetAnonymityRevokers", "BlockHashInput", "ArInfo", true),
+    (
+        "GetNextUpdateSequenceNumbers",
+        "BlockHashInput",
+        "NextUpdateSequenceNumbers",
+        false,
+    ),
+];
+
+/// Convert a method name such as `GetAccountList` to `get_account_list`.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && !snake.is_empty() {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+fn main() {
+    let mut service = Service::builder().name("Queries").package("concordium.v2");
+    for &(name, input, output, streaming) in METHODS {
+        let method = Method::builder()
+            .name(snake_case(name))
+            .route_name(name)
+            .input_type(format!("super::{input}"))
+            .output_type(format!("super::{output}"))
+            .codec_path("tonic::codec::ProstCodec");
+        let method = if streaming {
+            method.server_streaming()
+        } else {
+            method
+        };
+        service = service.method(method.build());
+    }
+    Builder::new()
+        .build_client(false)
+        .compile(&[service.build()]);
+}
@@ -0,0 +1,963 @@
+//! A fake node used by the tests. It serves the part of the gRPC API v2 that is
+//! needed to instantiate the generators against existing contract instances,
+//! and to submit transactions, using fixed answers.
+//!
+//! The messages are the ones generated in the SDK, and the service is the
+//! `Queries` server generated by the build script. Block identifiers in the
+//! requests are ignored, so every query is answered from the same [`State`].
+//! Deploying and initializing contracts is not supported, since it requires
+//! waiting for finalization.
+use concordium_rust_sdk::{
+    base::contracts_common::Duration,
+    common::{to_bytes, types::Ratio},
+    id::{
+        constants::{ArCurve, IpPairing},
+        types::{AccountAddress, ArInfo, GlobalContext, IpInfo, VerifyKey},
+    },
+    types::{
+        hashes::TransactionHash, queries::NextUpdateSequenceNumbers, smart_contracts::InstanceInfo,
+        AccessStructure, AccountInfo, AccountStakingInfo, AmountFraction, AuthorizationsV0,
+        AuthorizationsV1, BakerPoolInfo, BakerPoolStatus, CommissionRates, ContractAddress,
+        CredentialRegistrationID, DelegationTarget, ExchangeRate, HigherLevelAccessStructure,
+        Nonce, OpenStatus, PartsPerHundredThousands, PoolPendingChange, StakePendingChange,
+        UpdatePublicKey, UpdateSequenceNumber,
+    },
+    v2,
+};
+use futures::StreamExt;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+use tonic::{Request, Response, Status};
+
+/// The messages of the API as generated in the SDK, together with the server
+/// side of the `Queries` service.
+#[allow(clippy::all, dead_code, missing_docs)]
+mod proto {
+    include!("../../deps/concordium-rust-sdk/src/v2/generated/concordium.v2.rs");
+    include!(concat!(env!("OUT_DIR"), "/concordium.v2.Queries.rs"));
+}
+
+use proto::queries_server::{Queries, QueriesServer};
+
+/// The answer to invoking an entrypoint of a contract instance.
+pub enum Invoked {
+    Success {
+        return_value: Vec<u8>,
+        used_energy:  u64,
+    },
+    Rejected {
+        reason: i32,
+    },
+}
+
+/// Answers `InvokeInstance` given the receive name and the parameter.
+pub type InvokeHandler = Box<dyn FnMut(&str, &[u8]) -> Invoked + Send>;
+
+/// The state of the fake node.
+#[derive(Default)]
+pub struct State {
+    /// The accounts returned by `GetAccountList`.
+    pub accounts:                 Vec<AccountAddress>,
+    /// The next nonces returned by `GetNextAccountSequenceNumber`. Accounts
+    /// that are not listed have nonce 1.
+    pub nonces:                   BTreeMap<AccountAddress, Nonce>,
+    /// Number of block items received by `SendBlockItem`.
+    pub submitted:                usize,
+    /// How long `SendBlockItem` takes to respond.
+    pub send_delay:               std::time::Duration,
+    /// Number of `SendBlockItem` requests currently being answered.
+    pub sending:                  usize,
+    /// The largest number of `SendBlockItem` requests that were answered at
+    /// the same time.
+    pub max_concurrent_sends:     usize,
+    /// The instances returned by `GetInstanceInfo`. Only `V1` instances are
+    /// supported.
+    pub instances:                BTreeMap<ContractAddress, InstanceInfo>,
+    /// The key-value pairs returned by `GetInstanceState`.
+    pub instance_states:          BTreeMap<ContractAddress, Vec<KeyValuePair>>,
+    /// Answers `InvokeInstance`. Invoking fails if it is not set.
+    pub invoke:                   Option<InvokeHandler>,
+    /// Number of `InvokeInstance` requests answered.
+    pub invocations:              usize,
+    /// The accounts returned by `GetAccountInfo` when they are given by
+    /// address or index. The credentials of the accounts are not returned.
+    pub account_infos:            BTreeMap<AccountAddress, AccountInfo>,
+    /// The accounts returned by `GetAccountInfo` when they are given by
+    /// credential registration id. Only the address of these accounts is
+    /// looked up in `account_infos`.
+    pub credentials:              Vec<(CredentialRegistrationID, AccountAddress)>,
+    /// The parameters returned by `GetCryptographicParameters`.
+    pub cryptographic_parameters: Option<GlobalContext<ArCurve>>,
+    /// The chain parameters returned by `GetBlockChainParameters`. Only
+    /// version 2 is supported.
+    pub chain_parameters:         Option<v2::ChainParametersV2>,
+    /// The epoch duration in the consensus info. The rest of the consensus
+    /// info is fixed, for protocol version 6.
+    pub epoch_duration:           chrono::Duration,
+    /// The pools returned by `GetBakerList` and `GetPoolInfo`.
+    pub pools:                    Vec<BakerPoolStatus>,
+    /// The identity providers returned by `GetIdentityProviders`.
+    pub identity_providers:       Vec<IpInfo<IpPairing>>,
+    /// The anonymity revokers returned by `GetAnonymityRevokers`.
+    pub anonymity_revokers:       Vec<ArInfo<ArCurve>>,
+    /// The sequence numbers returned by `GetNextUpdateSequenceNumbers`.
+    pub update_sequence_numbers:  Option<NextUpdateSequenceNumbers>,
+}
+
+/// A fake node serving the queries of a [`State`].
+#[derive(Clone)]
+pub struct FakeNode {
+    state: Arc<Mutex<State>>,
+}
+
+impl FakeNode {
+    /// Start serving the given state on a local port, and return a client
+    /// connected to the node together with the state.
+    pub async fn start(state: State) -> anyhow::Result<(v2::Client, Arc<Mutex<State>>)> {
+        let state = Arc::new(Mutex::new(state));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let incoming = futures::stream::unfold(listener, |listener| async move {
+            let stream = listener.accept().await.map(|(stream, _)| stream);
+            Some((stream, listener))
+        });
+        let server = tonic::transport::Server::builder()
+            .add_service(QueriesServer::new(FakeNode {
+                state: state.clone(),
+            }))
+            .serve_with_incoming(incoming.boxed());
+        tokio::spawn(server);
+        let client = v2::Client::new(format!("http://{address}")).await?;
+        Ok((client, state))
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("Lock is not poisoned.")
+    }
+}
+
+/// A key and its value in the state of a contract instance.
+pub type KeyValuePair = (Vec<u8>, Vec<u8>);
+
+/// The responses of a streaming query.
+type Stream<T> = futures::stream::Iter<std::vec::IntoIter<Result<T, Status>>>;
+
+/// Queries about a block return the hash of the block in the metadata.
+fn block_response<T>(message: T) -> Response<T> {
+    let mut response = Response::new(message);
+    response.metadata_mut().insert(
+        "blockhash",
+        "00".repeat(32)
+            .parse()
+            .expect("The block hash is valid metadata."),
+    );
+    response
+}
+
+/// The response to a streaming query about a block.
+fn block_stream<T>(messages: impl IntoIterator<Item = T>) -> Response<Stream<T>> {
+    let messages: Vec<_> = messages.into_iter().map(Ok).collect();
+    block_response(futures::stream::iter(messages))
+}
+
+#[tonic::async_trait]
+impl Queries for FakeNode {
+    type GetAccountListStream = Stream<proto::AccountAddress>;
+    type GetAnonymityRevokersStream = Stream<proto::ArInfo>;
+    type GetBakerListStream = Stream<proto::BakerId>;
+    type GetIdentityProvidersStream = Stream<proto::IpInfo>;
+    type GetInstanceStateStream = Stream<proto::InstanceStateKvPair>;
+
+    async fn get_account_list(
+        &self,
+        _request: Request<proto::BlockHashInput>,
+    ) -> Result<Response<Self::GetAccountListStream>, Status> {
+        Ok(block_stream(
+            self.state()
+                .accounts
+                .iter()
+                .map(proto::AccountAddress::from),
+        ))
+    }
+
+    async fn get_next_account_sequence_number(
+        &self,
+        request: Request<proto::AccountAddress>,
+    ) -> Result<Response<proto::NextAccountSequenceNumber>, Status> {
+        // Malformed addresses are treated as unknown accounts.
+        let address = AccountAddress(request.into_inner().value.try_into().unwrap_or_default());
+        let nonce = self
+            .state()
+            .nonces
+            .get(&address)
+            .copied()
+            .unwrap_or(Nonce { nonce: 1 });
+        Ok(Response::new(proto::NextAccountSequenceNumber {
+            sequence_number: Some(proto::SequenceNumber { value: nonce.nonce }),
+            all_final:       true,
+        }))
+    }
+
+    async fn send_block_item(
+        &self,
+        _request: Request<proto::SendBlockItemRequest>,
+    ) -> Result<Response<proto::TransactionHash>, Status> {
+        let delay = {
+            let mut state = self.state();
+            state.sending += 1;
+            state.max_concurrent_sends = state.max_concurrent_sends.max(state.sending);
+            state.send_delay
+        };
+        tokio::time::sleep(delay).await;
+        let mut state = self.state();
+        state.sending -= 1;
+        state.submitted += 1;
+        let hash = TransactionHash::new([state.submitted as u8; 32]);
+        Ok(Response::new(proto::TransactionHash {
+            value: hash.as_ref().to_vec(),
+        }))
+    }
+
+    async fn get_instance_info(
+        &self,
+        request: Request<proto::InstanceInfoRequest>,
+    ) -> Result<Response<proto::InstanceInfo>, Status> {
+        let address = request.into_inner().address.unwrap_or_default();
+        let address = ContractAddress::new(address.index, address.subindex);
+        match self.state().instances.get(&address) {
+            Some(InstanceInfo::V1 {
+                owner,
+                amount,
+                methods,
+                name,
+                source_module,
+            }) => Ok(block_response(proto::InstanceInfo {
+                version: Some(proto::instance_info::Version::V1(
+                    proto::instance_info::V1 {
+                        owner:         Some(owner.into()),
+                        amount:        Some(proto::Amount {
+                            value: amount.micro_ccd(),
+                        }),
+                        methods:       methods
+                            .iter()
+                            .map(|method| proto::ReceiveName {
+                                value: method.to_string(),
+                            })
+                            .collect(),
+                        name:          Some(proto::InitName {
+                            value: name.to_string(),
+                        }),
+                        source_module: Some(proto::ModuleRef {
+                            value: source_module.bytes.to_vec(),
+                        }),
+                    },
+                )),
+            })),
+            _ => Err(Status::not_found("No such V1 instance.")),
+        }
+    }
+
+    async fn invoke_instance(
+        &self,
+        request: Request<proto::InvokeInstanceRequest>,
+    ) -> Result<Response<proto::InvokeInstanceResponse>, Status> {
+        let request = request.into_inner();
+        let entrypoint = request.entrypoint.unwrap_or_default().value;
+        let parameter = request.parameter.unwrap_or_default().value;
+        let mut state = self.state();
+        state.invocations += 1;
+        let invoke = state
+            .invoke
+            .as_mut()
+            .ok_or_else(|| Status::unimplemented("Invoking is not supported."))?;
+        let result = match invoke(&entrypoint, &parameter) {
+            Invoked::Success {
+                return_value,
+                used_energy,
+            } => proto::invoke_instance_response::Result::Success(
+                proto::invoke_instance_response::Success {
+                    return_value: Some(return_value),
+                    used_energy:  Some(proto::Energy { value: used_energy }),
+                    effects:      Vec::new(),
+                },
+            ),
+            Invoked::Rejected { reason } => proto::invoke_instance_response::Result::Failure(
+                proto::invoke_instance_response::Failure {
+                    return_value: Some(Vec::new()),
+                    used_energy:  Some(proto::Energy { value: 0 }),
+                    reason:       Some(proto::RejectReason {
+                        reason: Some(proto::reject_reason::Reason::RejectedReceive(
+                            proto::reject_reason::RejectedReceive {
+                                reject_reason:    reason,
+                                contract_address: request.instance,
+                                receive_name:     Some(proto::ReceiveName { value: entrypoint }),
+                                parameter:        Some(proto::Parameter { value: parameter }),
+                            },
+                        )),
+                    }),
+                },
+            ),
+        };
+        Ok(block_response(proto::InvokeInstanceResponse {
+            result: Some(result),
+        }))
+    }
+
+    async fn get_instance_state(
+        &self,
+        request: Request<proto::InstanceInfoRequest>,
+    ) -> Result<Response<Self::GetInstanceStateStream>, Status> {
+        let address = request.into_inner().address.unwrap_or_default();
+        let address = ContractAddress::new(address.index, address.subindex);
+        let state = self.state();
+        let pairs = state
+            .instance_states
+            .get(&address)
+            .ok_or_else(|| Status::not_found("No such instance."))?;
+        Ok(block_stream(pairs.iter().map(|(key, value)| {
+            proto::InstanceStateKvPair {
+                key:   key.clone(),
+                value: value.clone(),
+            }
+        })))
+    }
+
+    async fn get_cryptographic_parameters(
+        &self,
+        _request: Request<proto::BlockHashInput>,
+    ) -> Result<Response<proto::CryptographicParameters>, Status> {
+        let state = self.state();
+        let global = state
+            .cryptographic_parameters
+            .as_ref()
+            .ok_or_else(|| Status::unimplemented("No cryptographic parameters."))?;
+        Ok(block_response(proto::CryptographicParameters {
+            genesis_string:          global.genesis_string.clone(),
+            bulletproof_generators:  to_bytes(&global.bulletproof_generators),
+            on_chain_commitment_key: to_bytes(&global.on_chain_commitment_key),
+        }))
+    }
+
+    async fn get_account_info(
+        &self,
+        request: Request<proto::AccountInfoRequest>,
+    ) -> Result<Response<proto::AccountInfo>, Status> {
+        use proto::account_identifier_input::AccountIdentifierInput;
+        let identifier = request
+            .into_inner()
+            .account_identifier
+            .and_then(|identifier| identifier.account_identifier_input)
+            .ok_or_else(|| Status::invalid_argument("Missing account identifier."))?;
+        let state = self.state();
+        let info = match identifier {
+            AccountIdentifierInput::Address(address) => {
+                let address = AccountAddress(address.value.try_into().unwrap_or_default());
+                state.account_infos.get(&address)
+            }
+            AccountIdentifierInput::CredId(cred_id) => state
+                .credentials
+                .iter()
+                .find(|(registered, _)| to_bytes(registered) == cred_id.value)
+                .and_then(|(_, address)| state.account_infos.get(address)),
+            AccountIdentifierInput::AccountIndex(index) => state
+                .account_infos
+                .values()
+                .find(|info| info.account_index.index == index.value),
+        };
+        match info {
+            Some(info) => Ok(block_response(info.into())),
+            None => Err(Status::not_found("No such account.")),
+        }
+    }
+
+    async fn get_block_chain_parameters(
+        &self,
+        _request: Request<proto::BlockHashInput>,
+    ) -> Result<Response<proto::ChainParameters>, Status> {
+        let state = self.state();
+        let parameters = state
+            .chain_parameters
+            .as_ref()
+            .ok_or_else(|| Status::unimplemented("No chain parameters."))?;
+        Ok(block_response(proto::ChainParameters {
+            parameters: Some(proto::chain_parameters::Parameters::V2(parameters.into())),
+        }))
+    }
+
+    async fn get_consensus_info(
+        &self,
+        _request: Request<proto::Empty>,
+    ) -> Result<Response<proto::ConsensusInfo>, Status> {
+        let block = || Some(proto::BlockHash { value: vec![0; 32] });
+        let time = || Some(proto::Timestamp { value: 0 });
+        let epoch_duration = self.state().epoch_duration.num_milliseconds() as u64;
+        Ok(Response::new(proto::ConsensusInfo {
+            best_block: block(),
+            genesis_block: block(),
+            genesis_time: time(),
+            epoch_duration: Some(proto::Duration {
+                value: epoch_duration,
+            }),
+            last_finalized_block: block(),
+            best_block_height: Some(proto::AbsoluteBlockHeight { value: 0 }),
+            last_finalized_block_height: Some(proto::AbsoluteBlockHeight { value: 0 }),
+            protocol_version: proto::ProtocolVersion::ProtocolVersion6.into(),
+            genesis_index: Some(proto::GenesisIndex { value: 0 }),
+            current_era_genesis_block: block(),
+            current_era_genesis_time: time(),
+            current_timeout_duration: Some(proto::Duration { value: 10_000 }),
+            current_round: Some(proto::Round { value: 0 }),
+            current_epoch: Some(proto::Epoch { value: 0 }),
+            trigger_block_time: time(),
+            ..Default::default()
+        }))
+    }
+
+    async fn get_baker_list(
+        &self,
+        _request: Request<proto::BlockHashInput>,
+    ) -> Result<Response<Self::GetBakerListStream>, Status> {
+        Ok(block_stream(self.state().pools.iter().map(|pool| {
+            proto::BakerId {
+                value: pool.baker_id.id.index,
+            }
+        })))
+    }
+
+    async fn get_pool_info(
+        &self,
+        request: Request<proto::PoolInfoRequest>,
+    ) -> Result<Response<proto::PoolInfoResponse>, Status> {
+        let baker_id = request.into_inner().baker.unwrap_or_default().value;
+        let state = self.state();
+        match state
+            .pools
+            .iter()
+            .find(|pool| pool.baker_id.id.index == baker_id)
+        {
+            Some(pool) => Ok(block_response(pool.into())),
+            None => Err(Status::not_found("No such pool.")),
+        }
+    }
+
+    async fn get_identity_providers(
+        &self,
+        _request: Request<proto::BlockHashInput>,
+    ) -> Result<Response<Self::GetIdentityProvidersStream>, Status> {
+        Ok(block_stream(self.state().identity_providers.iter().map(
+            |ip_info| proto::IpInfo {
+                identity:       Some(proto::IpIdentity {
+                    value: ip_info.ip_identity.0,
+                }),
+                description:    Some(proto::Description {
+                    name:        ip_info.ip_description.name.clone(),
+                    url:         ip_info.ip_description.url.clone(),
+                    description: ip_info.ip_description.description.clone(),
+                }),
+                verify_key:     Some(proto::ip_info::IpVerifyKey {
+                    value: to_bytes(&ip_info.ip_verify_key),
+                }),
+                cdi_verify_key: Some(proto::ip_info::IpCdiVerifyKey {
+                    value: to_bytes(&ip_info.ip_cdi_verify_key),
+                }),
+            },
+        )))
+    }
+
+    async fn get_anonymity_revokers(
+        &self,
+        _request: Request<proto::BlockHashInput>,
+    ) -> Result<Response<Self::GetAnonymityRevokersStream>, Status> {
+        Ok(block_stream(self.state().anonymity_revokers.iter().map(
+            |ar_info| proto::ArInfo {
+                identity:    Some(proto::ar_info::ArIdentity {
+                    value: ar_info.ar_identity.into(),
+                }),
+                description: Some(proto::Description {
+                    name:        ar_info.ar_description.name.clone(),
+                    url:         ar_info.ar_description.url.clone(),
+                    description: ar_info.ar_description.description.clone(),
+                }),
+                public_key:  Some(proto::ar_info::ArPublicKey {
+                    value: to_bytes(&ar_info.ar_public_key),
+                }),
+            },
+        )))
+    }
+
+    async fn get_next_update_sequence_numbers(
+        &self,
+        _request: Request<proto::BlockHashInput>,
+    ) -> Result<Response<proto::NextUpdateSequenceNumbers>, Status> {
+        let numbers = self
+            .state()
+            .update_sequence_numbers
+            .ok_or_else(|| Status::unimplemented("No update sequence numbers."))?;
+        let number = |number: UpdateSequenceNumber| {
+            Some(proto::SequenceNumber {
+                value: number.number,
+            })
+        };
+        Ok(block_response(proto::NextUpdateSequenceNumbers {
+            root_keys: number(numbers.root_keys),
+            level1_keys: number(numbers.level_1_keys),
+            level2_keys: number(numbers.level_2_keys),
+            protocol: number(numbers.protocol),
+            election_difficulty: number(numbers.election_difficulty),
+            euro_per_energy: number(numbers.euro_per_energy),
+            micro_ccd_per_euro: number(numbers.micro_ccd_per_euro),
+            foundation_account: number(numbers.foundation_account),
+            mint_distribution: number(numbers.mint_distribution),
+            transaction_fee_distribution: number(numbers.transaction_fee_distribution),
+            gas_rewards: number(numbers.gas_rewards),
+            pool_parameters: number(numbers.pool_parameters),
+            add_anonymity_revoker: number(numbers.add_anonymity_revoker),
+            add_identity_provider: number(numbers.add_identity_provider),
+            cooldown_parameters: number(numbers.cooldown_parameters),
+            time_parameters: number(numbers.time_parameters),
+            timeout_parameters: number(numbers.timeout_parameters),
+            min_block_time: number(numbers.min_block_time),
+            block_energy_limit: number(numbers.block_energy_limit),
+            finalization_committee_parameters: number(numbers.finalization_committee_parameters),
+        }))
+    }
+}
+
+impl From<&AccountAddress> for proto::AccountAddress {
+    fn from(address: &AccountAddress) -> Self {
+        Self {
+            value: address.0.to_vec(),
+        }
+    }
+}
+
+/// A [`proto::Amount`] for the `Option` fields of the messages.
+fn amount(amount: concordium_rust_sdk::common::types::Amount) -> Option<proto::Amount> {
+    Some(proto::Amount {
+        value: amount.micro_ccd(),
+    })
+}
+
+/// A [`proto::Timestamp`] for the `Option` fields of the messages.
+fn timestamp(time: chrono::DateTime<chrono::Utc>) -> Option<proto::Timestamp> {
+    Some(proto::Timestamp {
+        value: time.timestamp_millis() as u64,
+    })
+}
+
+fn fraction(fraction: AmountFraction) -> Option<proto::AmountFraction> {
+    Some(proto::AmountFraction {
+        parts_per_hundred_thousand: PartsPerHundredThousands::from(fraction).into(),
+    })
+}
+
+fn ratio(ratio: Ratio) -> Option<proto::Ratio> {
+    Some(proto::Ratio {
+        numerator:   ratio.numerator(),
+        denominator: ratio.denominator(),
+    })
+}
+
+fn exchange_rate(rate: ExchangeRate) -> Option<proto::ExchangeRate> {
+    Some(proto::ExchangeRate {
+        value: Some(proto::Ratio {
+            numerator:   rate.numerator(),
+            denominator: rate.denominator(),
+        }),
+    })
+}
+
+fn duration(duration: Duration) -> Option<proto::Duration> {
+    Some(proto::Duration {
+        value: duration.millis(),
+    })
+}
+
+fn commission_rates(rates: &CommissionRates) -> Option<proto::CommissionRates> {
+    Some(proto::CommissionRates {
+        finalization: fraction(rates.finalization),
+        baking:       fraction(rates.baking),
+        transaction:  fraction(rates.transaction),
+    })
+}
+
+fn pool_info(info: &BakerPoolInfo) -> Option<proto::BakerPoolInfo> {
+    let open_status = match info.open_status {
+        OpenStatus::OpenForAll => proto::OpenStatus::OpenForAll,
+        OpenStatus::ClosedForNew => proto::OpenStatus::ClosedForNew,
+        OpenStatus::ClosedForAll => proto::OpenStatus::ClosedForAll,
+    };
+    Some(proto::BakerPoolInfo {
+        open_status:      open_status.into(),
+        url:              info.metadata_url.clone().into(),
+        commission_rates: commission_rates(&info.commission_rates),
+    })
+}
+
+fn stake_pending_change(change: &StakePendingChange) -> proto::StakePendingChange {
+    let change = match *change {
+        StakePendingChange::ReduceStake {
+            new_stake,
+            effective_time,
+        } => proto::stake_pending_change::Change::Reduce(proto::stake_pending_change::Reduce {
+            new_stake:      amount(new_stake),
+            effective_time: timestamp(effective_time),
+        }),
+        StakePendingChange::RemoveStake { effective_time } => {
+            proto::stake_pending_change::Change::Remove(proto::Timestamp {
+                value: effective_time.timestamp_millis() as u64,
+            })
+        }
+    };
+    proto::StakePendingChange {
+        change: Some(change),
+    }
+}
+
+impl From<&AccountStakingInfo> for proto::AccountStakingInfo {
+    fn from(stake: &AccountStakingInfo) -> Self {
+        use proto::account_staking_info::{Baker, Delegator, StakingInfo};
+        let staking_info = match stake {
+            AccountStakingInfo::Baker {
+                staked_amount,
+                restake_earnings,
+                baker_info,
+                pending_change,
+                pool_info: info,
+            } => StakingInfo::Baker(Baker {
+                staked_amount:    amount(*staked_amount),
+                restake_earnings: *restake_earnings,
+                baker_info:       Some(proto::BakerInfo {
+                    baker_id:        Some(proto::BakerId {
+                        value: baker_info.baker_id.id.index,
+                    }),
+                    election_key:    Some(proto::BakerElectionVerifyKey {
+                        value: to_bytes(&baker_info.baker_election_verify_key),
+                    }),
+                    signature_key:   Some(proto::BakerSignatureVerifyKey {
+                        value: to_bytes(&baker_info.baker_signature_verify_key),
+                    }),
+                    aggregation_key: Some(proto::BakerAggregationVerifyKey {
+                        value: to_bytes(&baker_info.baker_aggregation_verify_key),
+                    }),
+                }),
+                pending_change:   pending_change.as_ref().map(stake_pending_change),
+                pool_info:        info.as_ref().and_then(pool_info),
+            }),
+            AccountStakingInfo::Delegated {
+                staked_amount,
+                restake_earnings,
+                delegation_target,
+                pending_change,
+            } => {
+                let target = match delegation_target {
+                    DelegationTarget::Passive => {
+                        proto::delegation_target::Target::Passive(proto::Empty {})
+                    }
+                    DelegationTarget::Baker { baker_id } => {
+                        proto::delegation_target::Target::Baker(proto::BakerId {
+                            value: baker_id.id.index,
+                        })
+                    }
+                };
+                StakingInfo::Delegator(Delegator {
+                    staked_amount:    amount(*staked_amount),
+                    restake_earnings: *restake_earnings,
+                    target:           Some(proto::DelegationTarget {
+                        target: Some(target),
+                    }),
+                    pending_change:   pending_change.as_ref().map(stake_pending_change),
+                })
+            }
+        };
+        Self {
+            staking_info: Some(staking_info),
+        }
+    }
+}
+
+impl From<&AccountInfo> for proto::AccountInfo {
+    fn from(info: &AccountInfo) -> Self {
+        let encrypted = &info.account_encrypted_amount;
+        let encrypted_amount = |amount| proto::EncryptedAmount {
+            value: to_bytes(amount),
+        };
+        Self {
+            sequence_number:   Some(proto::SequenceNumber {
+                value: info.account_nonce.nonce,
+            }),
+            amount:            amount(info.account_amount),
+            schedule:          Some(proto::ReleaseSchedule {
+                total:     amount(info.account_release_schedule.total),
+                schedules: info
+                    .account_release_schedule
+                    .schedule
+                    .iter()
+                    .map(|release| proto::Release {
+                        timestamp:    timestamp(release.timestamp),
+                        amount:       amount(release.amount),
+                        transactions: release
+                            .transactions
+                            .iter()
+                            .map(|hash| proto::TransactionHash {
+                                value: hash.as_ref().to_vec(),
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            }),
+            creds:             Default::default(),
+            threshold:         Some(proto::AccountThreshold {
+                value: u8::from(info.account_threshold).into(),
+            }),
+            encrypted_balance: Some(proto::EncryptedBalance {
+                self_amount:       Some(encrypted_amount(&encrypted.self_amount)),
+                start_index:       encrypted.start_index,
+                aggregated_amount: encrypted
+                    .aggregated_amount
+                    .as_ref()
+                    .map(|(amount, _)| encrypted_amount(amount)),
+                num_aggregated:    encrypted.aggregated_amount.as_ref().map(|(_, num)| *num),
+                incoming_amounts:  encrypted
+                    .incoming_amounts
+                    .iter()
+                    .map(encrypted_amount)
+                    .collect(),
+            }),
+            encryption_key:    Some(proto::EncryptionKey {
+                value: to_bytes(&info.account_encryption_key),
+            }),
+            index:             Some(proto::AccountIndex {
+                value: info.account_index.index,
+            }),
+            stake:             info.account_stake.as_ref().map(Into::into),
+            address:           Some((&info.account_address).into()),
+        }
+    }
+}
+
+impl From<&BakerPoolStatus> for proto::PoolInfoResponse {
+    fn from(pool: &BakerPoolStatus) -> Self {
+        let equity_pending_change = match pool.baker_stake_pending_change {
+            PoolPendingChange::NoChange => None,
+            PoolPendingChange::ReduceBakerCapital {
+                baker_equity_capital,
+                effective_time,
+            } => Some(proto::pool_pending_change::Change::Reduce(
+                proto::pool_pending_change::Reduce {
+                    reduced_equity_capital: amount(baker_equity_capital),
+                    effective_time:         timestamp(effective_time),
+                },
+            )),
+            PoolPendingChange::RemovePool { effective_time } => Some(
+                proto::pool_pending_change::Change::Remove(proto::pool_pending_change::Remove {
+                    effective_time: timestamp(effective_time),
+                }),
+            ),
+        };
+        Self {
+            baker:                  Some(proto::BakerId {
+                value: pool.baker_id.id.index,
+            }),
+            address:                Some((&pool.baker_address).into()),
+            equity_capital:         amount(pool.baker_equity_capital),
+            delegated_capital:      amount(pool.delegated_capital),
+            delegated_capital_cap:  amount(pool.delegated_capital_cap),
+            pool_info:              pool_info(&pool.pool_info),
+            equity_pending_change:  equity_pending_change.map(|change| proto::PoolPendingChange {
+                change: Some(change),
+            }),
+            current_payday_info:    pool.current_payday_status.as_ref().map(|status| {
+                proto::PoolCurrentPaydayInfo {
+                    blocks_baked:            status.blocks_baked,
+                    finalization_live:       status.finalization_live,
+                    transaction_fees_earned: amount(status.transaction_fees_earned),
+                    effective_stake:         amount(status.effective_stake),
+                    lottery_power:           status.lottery_power,
+                    baker_equity_capital:    amount(status.baker_equity_capital),
+                    delegated_capital:       amount(status.delegated_capital),
+                    commission_rates:        commission_rates(&status.commission_rates),
+                }
+            }),
+            all_pool_total_capital: amount(pool.all_pool_total_capital),
+        }
+    }
+}
+
+fn update_public_keys(keys: &[UpdatePublicKey]) -> Vec<proto::UpdatePublicKey> {
+    keys.iter()
+        .map(|key| {
+            // The message holds the key without the tag of its scheme.
+            let VerifyKey::Ed25519VerifyKey(key) = &key.public;
+            proto::UpdatePublicKey {
+                value: to_bytes(key),
+            }
+        })
+        .collect()
+}
+
+fn higher_level_keys<Kind>(
+    keys: &HigherLevelAccessStructure<Kind>,
+) -> Option<proto::HigherLevelKeys> {
+    Some(proto::HigherLevelKeys {
+        keys:      update_public_keys(&keys.keys),
+        threshold: Some(proto::UpdateKeysThreshold {
+            value: u16::from(keys.threshold).into(),
+        }),
+    })
+}
+
+fn access_structure(access: &AccessStructure) -> Option<proto::AccessStructure> {
+    Some(proto::AccessStructure {
+        access_public_keys: access
+            .authorized_keys
+            .iter()
+            .map(|index| proto::UpdateKeysIndex {
+                value: index.index.into(),
+            })
+            .collect(),
+        access_threshold:   Some(proto::UpdateKeysThreshold {
+            value: u16::from(access.threshold).into(),
+        }),
+    })
+}
+
+fn authorizations(authorizations: &AuthorizationsV1) -> Option<proto::AuthorizationsV1> {
+    let AuthorizationsV0 {
+        keys,
+        emergency,
+        protocol,
+        election_difficulty,
+        euro_per_energy,
+        micro_gtu_per_euro,
+        foundation_account,
+        mint_distribution,
+        transaction_fee_distribution,
+        param_gas_rewards,
+        pool_parameters,
+        add_anonymity_revoker,
+        add_identity_provider,
+    } = &authorizations.v0;
+    Some(proto::AuthorizationsV1 {
+        v0:                 Some(proto::AuthorizationsV0 {
+            keys: update_public_keys(keys),
+            emergency: access_structure(emergency),
+            protocol: access_structure(protocol),
+            parameter_consensus: access_structure(election_difficulty),
+            parameter_euro_per_energy: access_structure(euro_per_energy),
+            parameter_micro_ccd_per_euro: access_structure(micro_gtu_per_euro),
+            parameter_foundation_account: access_structure(foundation_account),
+            parameter_mint_distribution: access_structure(mint_distribution),
+            parameter_transaction_fee_distribution: access_structure(transaction_fee_distribution),
+            parameter_gas_rewards: access_structure(param_gas_rewards),
+            pool_parameters: access_structure(pool_parameters),
+            add_anonymity_revoker: access_structure(add_anonymity_revoker),
+            add_identity_provider: access_structure(add_identity_provider),
+        }),
+        parameter_cooldown: access_structure(&authorizations.cooldown_parameters),
+        parameter_time:     access_structure(&authorizations.time_parameters),
+    })
+}
+
+impl From<&v2::ChainParametersV2> for proto::ChainParametersV2 {
+    fn from(parameters: &v2::ChainParametersV2) -> Self {
+        let pool = &parameters.pool_parameters;
+        let bounds = &pool.commission_bounds;
+        let range = |range: &concordium_rust_sdk::types::InclusiveRange<AmountFraction>| {
+            Some(proto::InclusiveRangeAmountFraction {
+                min: fraction(range.min),
+                max: fraction(range.max),
+            })
+        };
+        let seconds = |duration: concordium_rust_sdk::types::DurationSeconds| {
+            Some(proto::DurationSeconds {
+                value: duration.seconds,
+            })
+        };
+        let committee = &parameters.finalization_committee_parameters;
+        Self {
+            consensus_parameters: Some(proto::ConsensusParametersV1 {
+                timeout_parameters: Some(proto::TimeoutParameters {
+                    timeout_base:     duration(parameters.timeout_parameters.base),
+                    timeout_increase: ratio(parameters.timeout_parameters.increase),
+                    timeout_decrease: ratio(parameters.timeout_parameters.decrease),
+                }),
+                min_block_time:     duration(parameters.min_block_time),
+                block_energy_limit: Some(proto::Energy {
+                    value: parameters.block_energy_limit.energy,
+                }),
+            }),
+            euro_per_energy: exchange_rate(parameters.euro_per_energy),
+            micro_ccd_per_euro: exchange_rate(parameters.micro_ccd_per_euro),
+            cooldown_parameters: Some(proto::CooldownParametersCpv1 {
+                pool_owner_cooldown: seconds(parameters.cooldown_parameters.pool_owner_cooldown),
+                delegator_cooldown:  seconds(parameters.cooldown_parameters.delegator_cooldown),
+            }),
+            time_parameters: Some(proto::TimeParametersCpv1 {
+                reward_period_length: Some(proto::RewardPeriodLength {
+                    value: Some(proto::Epoch {
+                        value: concordium_rust_sdk::types::Epoch::from(
+                            parameters.time_parameters.reward_period_length,
+                        )
+                        .epoch,
+                    }),
+                }),
+                mint_per_payday:      Some(proto::MintRate {
+                    mantissa: parameters.time_parameters.mint_per_payday.mantissa,
+                    exponent: parameters.time_parameters.mint_per_payday.exponent.into(),
+                }),
+            }),
+            account_creation_limit: Some(proto::CredentialsPerBlockLimit {
+                value: parameters.account_creation_limit.limit.into(),
+            }),
+            mint_distribution: Some(proto::MintDistributionCpv1 {
+                baking_reward:       fraction(parameters.mint_distribution.baking_reward),
+                finalization_reward: fraction(parameters.mint_distribution.finalization_reward),
+            }),
+            transaction_fee_distribution: Some(proto::TransactionFeeDistribution {
+                baker:       fraction(parameters.transaction_fee_distribution.baker),
+                gas_account: fraction(parameters.transaction_fee_distribution.gas_account),
+            }),
+            gas_rewards: Some(proto::GasRewardsCpv2 {
+                baker:            fraction(parameters.gas_rewards.baker),
+                account_creation: fraction(parameters.gas_rewards.account_creation),
+                chain_update:     fraction(parameters.gas_rewards.chain_update),
+            }),
+            foundation_account: Some((&parameters.foundation_account).into()),
+            pool_parameters: Some(proto::PoolParametersCpv1 {
+                passive_finalization_commission: fraction(pool.passive_finalization_commission),
+                passive_baking_commission:       fraction(pool.passive_baking_commission),
+                passive_transaction_commission:  fraction(pool.passive_transaction_commission),
+                commission_bounds:               Some(proto::CommissionRanges {
+                    finalization: range(&bounds.finalization),
+                    baking:       range(&bounds.baking),
+                    transaction:  range(&bounds.transaction),
+                }),
+                minimum_equity_capital:          amount(pool.minimum_equity_capital),
+                capital_bound:                   Some(proto::CapitalBound {
+                    value: fraction(pool.capital_bound.bound),
+                }),
+                leverage_bound:                  Some(proto::LeverageFactor {
+                    value: Some(proto::Ratio {
+                        numerator:   pool.leverage_bound.numerator,
+                        denominator: pool.leverage_bound.denominator,
+                    }),
+                }),
+            }),
+            root_keys: higher_level_keys(&parameters.keys.root_keys),
+            level1_keys: higher_level_keys(&parameters.keys.level_1_keys),
+            level2_keys: authorizations(&parameters.keys.level_2_keys),
+            finalization_committee_parameters: Some(proto::FinalizationCommitteeParameters {
+                minimum_finalizers:                 committee.min_finalizers,
+                maximum_finalizers:                 committee.max_finalizers,
+                finalizer_relative_stake_threshold: Some(proto::AmountFraction {
+                    parts_per_hundred_thousand: committee
+                        .finalizers_relative_stake_threshold
+                        .into(),
+                }),
+            }),
+        }
+    }
+}
@@ -1,33 +1,77 @@
+use crate::{
+    metrics::Metrics,
+    submitter::{write_signed_transaction, Submitter},
+};
 use anyhow::Context;
 use clap::Args;
 use concordium_rust_sdk::{
-    base::cis4_types::CredentialInfo,
+    base::cis4_types::{CredentialInfo, Reason},
     cis2::{
-        AdditionalData, Cis2Contract, Cis2TransactionMetadata, Receiver, TokenAmount, TokenId,
-        Transfer,
+        AdditionalData, BalanceOfQuery, BalanceOfQueryParams, Cis2Contract, Cis2QueryError,
+        OperatorUpdate, Receiver, TokenAmount, TokenId, TokenMetadataQueryParams, Transfer,
+        TransferParams, UpdateOperator, UpdateOperatorParams,
     },
-    cis4::{Cis4Contract, Cis4TransactionMetadata},
+    cis4::Cis4Contract,
     common::{
-        types::{Amount, KeyPair, TransactionTime},
-        Deserial,
+        types::{Amount, KeyIndex, KeyPair, TransactionTime},
+        Deserial, Versioned, VERSION_0,
+    },
+    contract_client::{ContractClient, MetadataUrl, SchemaRef},
+    encrypted_transfers::{
+        self,
+        types::{AggregatedDecryptedAmount, EncryptedAmountAggIndex},
+    },
+    id::{
+        account_holder::create_credential,
+        constants::{ArCurve, AttributeKind, IpPairing},
+        elgamal,
+        pedersen_commitment::Value as PedersenValue,
+        types::{
+            account_address_from_registration_id, AccCredentialInfo, AccountAddress,
+            AccountCredential, AccountCredentialMessage, AccountKeys, ArIdentity, ArInfo,
+            CredentialData, CredentialHolderInfo, GlobalContext, HasIdentityObjectFields,
+            IdCredentials, IdObjectUseData, IdentityObjectV1, IpContext, IpInfo, Policy,
+        },
+    },
+    smart_contracts::{
+        common as concordium_std,
+        common::{
+            schema::{Type, VersionedModuleSchema},
+            OwnedEntrypointName, SignatureEd25519, SignatureThreshold, Timestamp,
+        },
+        engine::utils::{get_embedded_schema_v0, get_embedded_schema_v1},
     },
-    contract_client::{ContractTransactionMetadata, MetadataUrl, SchemaRef},
-    id::types::AccountAddress,
-    smart_contracts::{common as concordium_std, common::Timestamp},
     types::{
-        smart_contracts::{OwnedContractName, OwnedParameter, WasmModule},
+        hashes::TransactionHash,
+        smart_contracts::{
+            ContractContext, InstanceInfo, InvokeContractResult, OwnedContractName, OwnedParameter,
+            OwnedReceiveName, WasmModule, WasmVersion,
+        },
         transactions::{
-            send, send::GivenEnergy, AccountTransaction, BlockItem, EncodedPayload,
-            InitContractPayload,
+            construct::TRANSACTION_HEADER_SIZE, cost, send, AccountTransaction, BlockItem,
+            ConfigureBakerPayload, ConfigureDelegationPayload, EncodedPayload, InitContractPayload,
+            Payload, PayloadLike, UpdateContractPayload,
         },
-        Address, ContractAddress, Energy, NodeDetails, Nonce, WalletAccount,
+        update, AccessStructure, AccountStakingInfo, AccountTransactionEffects, Address, BakerId,
+        BakerKeyPairs, CapitalBound, ContractAddress, CredentialRegistrationID, DelegationTarget,
+        Energy, Epoch, LeverageFactor, Memo, NodeDetails, Nonce, OpenStatus,
+        PartsPerHundredThousands, PoolParameters, RegisteredData, UpdateKeyPair, UpdateKeysIndex,
+        UpdatePayload, UpdatePublicKey, UpdateSequenceNumber, UrlText, WalletAccount,
     },
-    v2::{self, BlockIdentifier},
-    web3id::CredentialHolderId,
+    v2::{self, dry_run::DryRunTransaction, AccountIdentifier, BlockIdentifier},
+    web3id::{CredentialHolderId, REVOKE_DOMAIN_STRING},
 };
-use futures::TryStreamExt;
+use either::Either;
+use futures::{FutureExt, StreamExt, TryStreamExt};
+use key_derivation::{words_to_seed, ConcordiumHdWallet, CredentialContext, Net};
 use rand::{rngs::StdRng, Rng, SeedableRng};
-use std::{collections, collections::BTreeMap, io::Cursor, path::PathBuf, str::FromStr};
+use std::{
+    collections,
+    collections::BTreeMap,
+    io::{Cursor, Read, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 #[derive(Debug, Args)]
 pub struct CcdArgs {
@@ -46,12 +90,464 @@ pub struct CcdArgs {
                 partitioned based on baker id into the given amount of chunks."
     )]
     mode:      Option<Mode>,
+    #[clap(
+        long = "memo-size",
+        help = "If set, a memo of the given number of bytes (at most 256) is added to each \
+                transaction."
+    )]
+    memo_size: Option<usize>,
+    #[clap(
+        long = "schedule",
+        help = "If set, send scheduled transfers with the given number of release points (at most \
+                255), one minute apart. In this case `--amount` is the amount released at each \
+                point."
+    )]
+    schedule:  Option<u8>,
 }
 
 #[derive(Debug, Args)]
 pub struct TransferCis2Args {
     #[arg(long = "receivers", help = "Path to file containing receivers.")]
-    receivers: Option<PathBuf>,
+    receivers:  Option<PathBuf>,
+    #[command(flatten)]
+    instance:   InstanceArgs,
+    #[arg(
+        long = "variant",
+        help = "The kind of transactions to send. One of `transfer`, `update-operator` or \
+                `operator-transfer`.",
+        default_value = "transfer"
+    )]
+    variant:    Cis2Variant,
+    #[arg(
+        long = "batch-size",
+        help = "Number of transfers or operator updates in each transaction.",
+        default_value = "1"
+    )]
+    batch_size: usize,
+    #[arg(
+        long = "operator",
+        help = "Path to the key file of the account that acts as operator of the sender in the \
+                `operator-transfer` variant."
+    )]
+    operator:   Option<PathBuf>,
+}
+
+/// The kind of transactions sent by the `transfer-cis2` mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cis2Variant {
+    /// Transfers from the sender to the receivers.
+    Transfer,
+    /// Adding and removing the receivers as operators of the sender.
+    UpdateOperator,
+    /// Transfers from the sender to the receivers made by an operator of the
+    /// sender.
+    OperatorTransfer,
+}
+
+impl FromStr for Cis2Variant {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "transfer" => Ok(Self::Transfer),
+            "update-operator" => Ok(Self::UpdateOperator),
+            "operator-transfer" => Ok(Self::OperatorTransfer),
+            _ => anyhow::bail!(
+                "Unknown variant `{s}`. Expected one of `transfer`, `update-operator` or \
+                 `operator-transfer`."
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct InstanceArgs {
+    #[arg(
+        long = "contract",
+        help = "Address of an existing instance of the contract to use, given as \
+                `<index,subindex>`. If not given, a new instance is deployed and initialized.",
+        value_parser = parse_contract_address
+    )]
+    contract: Option<ContractAddress>,
+}
+
+#[derive(Debug, Args)]
+pub struct RegisterCredentialsArgs {
+    #[command(flatten)]
+    instance:  InstanceArgs,
+    #[arg(
+        long = "lifecycle",
+        help = "Register revocable credentials, update their metadata, and revoke them, \
+                alternately as the issuer and as the holder."
+    )]
+    lifecycle: bool,
+}
+
+/// Parse a contract address given as `<index,subindex>`, where the brackets
+/// are optional.
+pub(crate) fn parse_contract_address(s: &str) -> anyhow::Result<ContractAddress> {
+    let s = s.trim();
+    let address = if s.starts_with('<') {
+        s.parse()
+    } else {
+        format!("<{s}>").parse()
+    };
+    address.map_err(|e| anyhow::anyhow!("Invalid contract address: {e:?}"))
+}
+
+#[derive(Debug, Args)]
+pub struct RegisterDataArgs {
+    #[arg(
+        long = "size",
+        help = "Size in bytes of the registered data (at most 256).",
+        default_value = "32"
+    )]
+    size: usize,
+}
+
+#[derive(Debug, Args)]
+pub struct EncryptedTransferArgs {
+    #[arg(long = "receivers", help = "Path to file containing receivers.")]
+    receivers:      Option<PathBuf>,
+    #[arg(
+        long = "encryption-key",
+        help = "Path to a file containing the encryption secret key of the sender in the \
+                `encryptionSecretKey` field, such as the account files produced by the genesis \
+                creator."
+    )]
+    encryption_key: PathBuf,
+    #[clap(
+        long = "amount",
+        help = "CCD amount to transfer in each encrypted transaction.",
+        default_value = "0.000001"
+    )]
+    amount:         Amount,
+}
+
+#[derive(Debug, Args)]
+pub struct StakingArgs {
+    #[arg(
+        long = "accounts",
+        help = "Path to a directory containing the key files of the accounts that configure stake."
+    )]
+    accounts: PathBuf,
+    #[arg(
+        long = "bakers",
+        help = "Number of accounts (in the order of the file names) that act as bakers. The \
+                remaining accounts act as delegators.",
+        default_value = "0"
+    )]
+    bakers:   usize,
+    #[clap(
+        long = "amount",
+        help = "CCD amount by which stake is increased and decreased.",
+        default_value = "1"
+    )]
+    amount:   Amount,
+}
+
+#[derive(Debug, Args)]
+pub struct CreateAccountsArgs {
+    #[arg(
+        long = "identity",
+        help = "Path to the identity object that the credentials are derived from."
+    )]
+    identity:       PathBuf,
+    #[arg(
+        long = "seed-phrase",
+        help = "Path to a file containing the seed phrase that the identity was created with."
+    )]
+    seed_phrase:    PathBuf,
+    #[arg(
+        long = "ip-index",
+        help = "Index of the identity provider that issued the identity.",
+        default_value = "0"
+    )]
+    ip_index:       u32,
+    #[arg(
+        long = "identity-index",
+        help = "Index of the identity among the identities created with the seed phrase.",
+        default_value = "0"
+    )]
+    identity_index: u32,
+    #[arg(
+        long = "mainnet",
+        help = "Derive the keys for mainnet instead of testnet."
+    )]
+    mainnet:        bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ContractArgs {
+    #[arg(long = "module", help = "Path to the `.wasm.v1` module to deploy.")]
+    module:      PathBuf,
+    #[arg(
+        long = "init-name",
+        help = "Name of the init function of the contract, e.g. `init_cis2_nft`."
+    )]
+    init_name:   String,
+    #[arg(
+        long = "init-param",
+        help = "Path to a JSON file containing the parameter to the init function. If not given, \
+                the empty parameter is used."
+    )]
+    init_param:  Option<PathBuf>,
+    #[arg(
+        long = "init-energy",
+        help = "Energy to use for the init transaction. If not given, it is estimated with a dry \
+                run."
+    )]
+    init_energy: Option<Energy>,
+    #[arg(
+        long = "schema",
+        help = "Path to the schema of the module. If not given, the schema embedded in the module \
+                is used."
+    )]
+    schema:      Option<PathBuf>,
+    #[arg(
+        long = "entrypoints",
+        help = "Path to a JSON file containing the list of entrypoints to call. See the README \
+                for the format."
+    )]
+    entrypoints: PathBuf,
+    #[arg(
+        long = "receivers",
+        help = "Path to file containing the accounts used for the `$ACCOUNT` placeholder."
+    )]
+    receivers:   Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct StateGrowthArgs {
+    #[command(flatten)]
+    instance:              InstanceArgs,
+    #[arg(
+        long = "workload",
+        help = "How the state grows. Either `tokens`, minting new tokens of a CIS-2 NFT contract, \
+                or `metadata`, registering credentials with large metadata URLs in a credential \
+                registry.",
+        default_value = "tokens"
+    )]
+    workload:              GrowthWorkload,
+    #[arg(
+        long = "items-per-transaction",
+        help = "Number of tokens minted by each transaction of the `tokens` workload.",
+        default_value = "10"
+    )]
+    items_per_transaction: u8,
+    #[arg(
+        long = "payload-size",
+        help = "Length in bytes of the metadata URL of each credential of the `metadata` workload.",
+        default_value = "1000"
+    )]
+    payload_size:          u16,
+    #[arg(
+        long = "report-interval",
+        help = "Interval in seconds between reports of the size of the state and the energy cost \
+                of an update.",
+        default_value = "60"
+    )]
+    report_interval:       u64,
+}
+
+/// The ways the `state-growth` mode grows the state of a contract.
+#[derive(Debug, Clone, Copy)]
+enum GrowthWorkload {
+    Tokens,
+    Metadata,
+}
+
+impl FromStr for GrowthWorkload {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tokens" => Ok(Self::Tokens),
+            "metadata" => Ok(Self::Metadata),
+            _ => anyhow::bail!("Unknown workload `{s}`. Expected `tokens` or `metadata`."),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ChainUpdatesArgs {
+    #[arg(
+        long = "keys",
+        help = "Directory containing the level 2 update keys, as written to the `updateKeys` \
+                directory by the genesis creator. Keys are read from the files \
+                `level2-key-<index>.json`."
+    )]
+    keys:    PathBuf,
+    #[arg(
+        long = "updates",
+        help = "Comma-separated list of the updates to make in turn. Supported updates are \
+                `micro-ccd-per-euro`, `euro-per-energy`, `gas-rewards` and `pool-parameters`.",
+        value_delimiter = ',',
+        default_value = "micro-ccd-per-euro,euro-per-energy,gas-rewards,pool-parameters"
+    )]
+    updates: Vec<UpdateKind>,
+}
+
+/// The kinds of chain updates made by the `chain-updates` mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UpdateKind {
+    MicroCcdPerEuro,
+    EuroPerEnergy,
+    GasRewards,
+    PoolParameters,
+}
+
+impl FromStr for UpdateKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "micro-ccd-per-euro" => Ok(Self::MicroCcdPerEuro),
+            "euro-per-energy" => Ok(Self::EuroPerEnergy),
+            "gas-rewards" => Ok(Self::GasRewards),
+            "pool-parameters" => Ok(Self::PoolParameters),
+            _ => anyhow::bail!(
+                "Unknown update `{s}`. Expected one of `micro-ccd-per-euro`, `euro-per-energy`, \
+                 `gas-rewards` or `pool-parameters`."
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ReplayArgs {
+    #[arg(
+        long = "file",
+        help = "Path to file containing the pre-signed transactions."
+    )]
+    file:      PathBuf,
+    #[arg(
+        long = "max-speed",
+        help = "Submit the transactions as fast as possible, ignoring `--tps`."
+    )]
+    max_speed: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct InvokeArgs {
+    #[arg(
+        long = "contract",
+        help = "Address of the CIS-2 contract instance to invoke, given as `<index,subindex>`.",
+        value_parser = parse_contract_address
+    )]
+    contract:        ContractAddress,
+    #[arg(
+        long = "entrypoints",
+        help = "Comma-separated list of the entrypoints to invoke in turn. Supported entrypoints \
+                are `balanceOf` and `tokenMetadata`.",
+        value_delimiter = ',',
+        default_value = "balanceOf,tokenMetadata"
+    )]
+    entrypoints:     Vec<ViewEntrypoint>,
+    #[arg(
+        long = "token-id",
+        help = "Type of the token ids of the contract. Either `u8` (as in `transfer-cis2`), `u32` \
+                (as in `mint-nfts`), or `unit` (as in `wccd`).",
+        default_value = "u32"
+    )]
+    token_id:        TokenIdKind,
+    #[arg(
+        long = "max-token-id",
+        help = "The token ids in the queries are chosen at random between 0 and this number.",
+        default_value = "100"
+    )]
+    max_token_id:    u32,
+    #[arg(
+        long = "receivers",
+        help = "Path to file containing the accounts whose balances are queried. If not given, \
+                all accounts on the chain are used."
+    )]
+    receivers:       Option<PathBuf>,
+    #[arg(
+        long = "report-interval",
+        help = "Interval in seconds between reports of the latency of the invocations.",
+        default_value = "10"
+    )]
+    report_interval: u64,
+}
+
+/// The view entrypoints of CIS-2 contracts invoked by the `invoke` command.
+#[derive(Debug, Clone, Copy)]
+enum ViewEntrypoint {
+    BalanceOf,
+    TokenMetadata,
+}
+
+impl FromStr for ViewEntrypoint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "balanceOf" => Ok(Self::BalanceOf),
+            "tokenMetadata" => Ok(Self::TokenMetadata),
+            _ => {
+                anyhow::bail!("Unknown entrypoint `{s}`. Expected `balanceOf` or `tokenMetadata`.")
+            }
+        }
+    }
+}
+
+/// The type of the token ids of a CIS-2 contract.
+#[derive(Debug, Clone, Copy)]
+enum TokenIdKind {
+    U8,
+    U32,
+    Unit,
+}
+
+impl FromStr for TokenIdKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "u8" => Ok(Self::U8),
+            "u32" => Ok(Self::U32),
+            "unit" => Ok(Self::Unit),
+            _ => anyhow::bail!("Unknown token id type `{s}`. Expected `u8`, `u32` or `unit`."),
+        }
+    }
+}
+
+impl TokenIdKind {
+    fn token_id(self, id: u32) -> TokenId {
+        match self {
+            // The maximum id is checked to fit when the arguments are validated.
+            TokenIdKind::U8 => TokenId::new_u8(id as u8),
+            TokenIdKind::U32 => TokenId::new_u32(id),
+            TokenIdKind::Unit => TokenId::new_unchecked(Vec::new()),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct BootstrapArgs {
+    #[command(flatten)]
+    identity:   CreateAccountsArgs,
+    #[arg(long = "count", help = "Number of accounts to create.")]
+    count:      usize,
+    #[arg(
+        long = "out",
+        help = "Directory to write the key files of the accounts and the list of their addresses \
+                to."
+    )]
+    out:        PathBuf,
+    #[arg(
+        long = "fund",
+        help = "If set, transfer the given CCD amount from the sender to each of the created \
+                accounts."
+    )]
+    fund:       Option<Amount>,
+    #[arg(
+        long = "batch-size",
+        help = "Number of transactions to submit before waiting for them to be finalized.",
+        default_value = "100"
+    )]
+    batch_size: usize,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -81,21 +577,50 @@ const WCCD_MODULE: &[u8] = include_bytes!("../resources/cis2_wccd.wasm.v1");
 const REGISTER_CREDENTIALS_MODULE: &[u8] =
     include_bytes!("../resources/credential_registry.wasm.v1");
 
+/// Get the list of receivers, either from the given file, or all accounts on
+/// the chain if no file is given.
+pub(crate) async fn get_receivers(
+    client: &mut v2::Client,
+    receivers: Option<PathBuf>,
+) -> anyhow::Result<Vec<AccountAddress>> {
+    let accounts: Vec<AccountAddress> = match receivers {
+        None => {
+            client
+                .get_account_list(BlockIdentifier::LastFinal)
+                .await
+                .context("Could not obtain a list of accounts.")?
+                .response
+                .try_collect()
+                .await?
+        }
+        Some(receivers) => serde_json::from_str(
+            &std::fs::read_to_string(receivers).context("Could not read the receivers file.")?,
+        )
+        .context("Could not parse the receivers file.")?,
+    };
+    anyhow::ensure!(!accounts.is_empty(), "List of receivers must not be empty.");
+    Ok(accounts)
+}
+
 /// Info needed to deploy and initialize a contract.
-struct ContractDeploymentInfo {
+pub(crate) struct ContractDeploymentInfo<'a> {
     /// The module to deploy.
-    module:      &'static [u8],
+    pub module:      &'a [u8],
     /// The name of the init function, e.g. "init_cis2_nft".
-    name:        &'static str,
-    /// The energy needed to initialize the contract.
-    init_energy: Energy,
+    pub name:        &'a str,
+    /// The energy needed to initialize the contract. If not given, it is
+    /// estimated by dry running the deployment and the init transaction.
+    pub init_energy: Option<Energy>,
+    /// Whether the generated transactions are only allowed for the owner of
+    /// the instance, such as minting tokens or issuing credentials.
+    pub owner_only:  bool,
 }
 
-impl ContractDeploymentInfo {
+impl ContractDeploymentInfo<'_> {
     /// Deploys and initializes a contract based on a [`ContractDeploymentInfo`]
     /// and an [`OwnedParameter`] to the init function. Also uses and increments
     /// a supplied nonce.
-    async fn deploy_and_init_contract(
+    pub async fn deploy_and_init_contract(
         &self,
         client: &mut v2::Client,
         args: &CommonArgs,
@@ -107,7 +632,19 @@ impl ContractDeploymentInfo {
         // Deploy module.
         let expiry: TransactionTime = TransactionTime::seconds_after(args.expiry);
         let module = WasmModule::deserial(&mut Cursor::new(self.module))?;
-        let mod_ref = module.get_module_ref();
+        let payload = InitContractPayload {
+            amount: Amount::zero(),
+            mod_ref: module.get_module_ref(),
+            init_name: OwnedContractName::new(self.name.into())?,
+            param,
+        };
+        let init_energy = match self.init_energy {
+            Some(energy) => energy,
+            None => estimate_init_energy(client, args, module.clone(), &payload)
+                .await
+                .context("Could not estimate the energy of the init transaction.")?,
+        };
+
         let deploy_tx = send::deploy_module(&args.keys, args.keys.address, *nonce, expiry, module);
         nonce.next_mut();
 
@@ -116,19 +653,13 @@ impl ContractDeploymentInfo {
 
         // We don't need to wait for deployment finalization, so we can send the init
         // transaction.
-        let payload = InitContractPayload {
-            amount: Amount::zero(),
-            mod_ref,
-            init_name: OwnedContractName::new(self.name.into())?,
-            param,
-        };
         let init_tx = send::init_contract(
             &args.keys,
             args.keys.address,
             *nonce,
             expiry,
             payload,
-            self.init_energy,
+            init_energy,
         );
         nonce.next_mut();
 
@@ -150,93 +681,888 @@ impl ContractDeploymentInfo {
             .context("Transaction was not a contract init")
             .map(|init| init.address)
     }
+
+    /// Check that the existing instance at the given address is an instance of
+    /// the contract described by the [`ContractDeploymentInfo`], and that it is
+    /// owned by the sender if the transactions require that.
+    async fn check_instance(
+        &self,
+        client: &mut v2::Client,
+        args: &CommonArgs,
+        address: ContractAddress,
+    ) -> anyhow::Result<()> {
+        let info = client
+            .get_instance_info(address, BlockIdentifier::LastFinal)
+            .await
+            .with_context(|| format!("Could not get the contract instance {address}."))?
+            .response;
+        let mod_ref = WasmModule::deserial(&mut Cursor::new(self.module))?.get_module_ref();
+        anyhow::ensure!(
+            info.source_module() == mod_ref,
+            "The contract instance {address} is an instance of module {}, but module {mod_ref} \
+             was expected.",
+            info.source_module()
+        );
+        anyhow::ensure!(
+            info.name().as_contract_name().get_chain_name() == self.name,
+            "The contract instance {address} is an instance of {}, but {} was expected.",
+            info.name(),
+            self.name
+        );
+        if self.owner_only {
+            let (InstanceInfo::V0 { owner, .. } | InstanceInfo::V1 { owner, .. }) = &info;
+            anyhow::ensure!(
+                *owner == args.keys.address,
+                "The contract instance {address} is owned by {owner}, but the transactions \
+                 require the sender {} to be the owner.",
+                args.keys.address
+            );
+        }
+        Ok(())
+    }
+
+    /// Use the existing instance at `existing` if given, after checking it with
+    /// [`check_instance`](Self::check_instance). Otherwise deploy and
+    /// initialize a new instance with
+    /// [`deploy_and_init_contract`](Self::deploy_and_init_contract).
+    async fn existing_or_deploy(
+        &self,
+        client: &mut v2::Client,
+        args: &CommonArgs,
+        existing: Option<ContractAddress>,
+        param: OwnedParameter,
+        nonce: &mut Nonce,
+    ) -> anyhow::Result<ContractAddress> {
+        match existing {
+            Some(address) => {
+                self.check_instance(client, args, address).await?;
+                println!("Using existing contract instance {address}.");
+                Ok(address)
+            }
+            None => self
+                .deploy_and_init_contract(client, args, param, nonce)
+                .await
+                .context("Could not deploy/init the contract."),
+        }
+    }
 }
 
-/// Arguments used by all transaction generators.
-pub struct CommonArgs {
-    pub keys:   WalletAccount,
-    pub expiry: u32,
+/// Estimate the energy needed to execute a contract init transaction,
+/// excluding the base cost of the transaction, by dry running it. The module
+/// is not yet deployed in the last finalized block, so the dry run deploys it
+/// first.
+async fn estimate_init_energy(
+    client: &mut v2::Client,
+    args: &CommonArgs,
+    module: WasmModule,
+    payload: &InitContractPayload,
+) -> anyhow::Result<Energy> {
+    let sender = args.keys.address;
+    let (mut dry_run, _) = client
+        .dry_run(BlockIdentifier::LastFinal)
+        .await
+        .context("Could not start a dry run.")?
+        .inner;
+    let deploy = Payload::DeployModule { module };
+    let deployed = dry_run
+        .run_transaction(DryRunTransaction::new(
+            sender,
+            dry_run.energy_quota(),
+            &deploy,
+        ))
+        .await?;
+    let init = Payload::InitContract {
+        payload: payload.clone(),
+    };
+    let executed = dry_run
+        .run_transaction(DryRunTransaction::new(
+            sender,
+            deployed.quota_remaining,
+            &init,
+        ))
+        .await?
+        .inner;
+    dry_run.close();
+    if let AccountTransactionEffects::None { reject_reason, .. } = executed.details.effects {
+        anyhow::bail!("The init transaction was rejected in the dry run: {reject_reason:?}");
+    }
+    let base_cost = cost::base_cost(
+        TRANSACTION_HEADER_SIZE + u64::from(u32::from(init.encode().size())),
+        1,
+    );
+    let energy = executed
+        .energy_cost
+        .checked_sub(base_cost)
+        .context("The energy cost of the dry run is less than the base cost.")?;
+    Ok(add_margin(energy, args.energy_margin))
 }
 
-/// A transaction generator.
-pub trait Generate {
-    /// Generate a transaction. Will be called in a loop.
-    fn generate(&mut self) -> anyhow::Result<AccountTransaction<EncodedPayload>>;
+/// Add the safety margin, given in percent, to an energy estimate.
+fn add_margin(energy: Energy, margin: u64) -> Energy {
+    Energy::from(energy.energy + energy.energy * margin / 100)
 }
 
-pub async fn generate_transactions(
-    mut client: v2::Client,
-    mut generator: impl Generate + Send + 'static,
-    tps: u16,
-) -> anyhow::Result<()> {
-    // Create a channel between the task signing and the task sending transactions.
-    let (sender, mut rx) = tokio::sync::mpsc::channel(100);
+/// The latest energy estimate for each entrypoint together with the time it
+/// was made.
+type Estimates =
+    std::sync::Arc<std::sync::Mutex<BTreeMap<OwnedReceiveName, (Energy, std::time::Instant)>>>;
 
-    // A task that will generate and sign transactions. Spawn it to run in the
-    // background.
-    tokio::spawn(async move {
-        loop {
-            let tx = generator.generate();
-            sender.send(tx).await.expect("Error in receiver");
+/// Estimates the energy needed by updates of a contract instance by invoking
+/// the instance on the node. The estimates are refreshed periodically in the
+/// background, since the cost of an update can change as the state of the
+/// contract grows.
+pub(crate) struct EnergyEstimator {
+    client:        v2::Client,
+    address:       ContractAddress,
+    contract_name: std::sync::Arc<OwnedContractName>,
+    sender:        AccountAddress,
+    margin:        u64,
+    interval:      std::time::Duration,
+    estimates:     Estimates,
+}
+
+impl EnergyEstimator {
+    pub fn new<Type>(contract: ContractClient<Type>, args: &CommonArgs) -> Self {
+        Self {
+            client:        contract.client,
+            address:       contract.address,
+            contract_name: contract.contract_name,
+            sender:        args.keys.address,
+            margin:        args.energy_margin,
+            interval:      std::time::Duration::from_secs(args.reestimate_interval),
+            estimates:     Default::default(),
         }
-    });
+    }
 
-    let mut interval = tokio::time::interval(tokio::time::Duration::from_micros(
-        1_000_000 / u64::from(tps),
-    ));
-    loop {
-        interval.tick().await;
-        if let Some(tx) = rx.recv().await.transpose()? {
-            let nonce = tx.header.nonce;
-            let energy = tx.header.energy_amount;
-            let item = BlockItem::AccountTransaction(tx);
-            let transaction_hash = client.send_block_item(&item).await?;
-            println!(
-                "{}: Transaction {} submitted (nonce = {nonce}, energy = {energy}).",
-                chrono::Utc::now(),
-                transaction_hash,
+    /// Construct the payload of an update of the given entrypoint.
+    fn payload(
+        &self,
+        entrypoint: &str,
+        amount: Amount,
+        message: OwnedParameter,
+    ) -> anyhow::Result<UpdateContractPayload> {
+        let contract_name = self.contract_name.as_contract_name().contract_name();
+        Ok(UpdateContractPayload {
+            amount,
+            address: self.address,
+            receive_name: OwnedReceiveName::new(format!("{contract_name}.{entrypoint}"))?,
+            message,
+        })
+    }
+
+    /// Estimate the energy needed to execute an update, excluding the base
+    /// cost of the transaction, and store the estimate. Every entrypoint that
+    /// is updated with [`energy`](Self::energy) must be estimated with this
+    /// first.
+    async fn estimate(&mut self, payload: &UpdateContractPayload) -> anyhow::Result<Energy> {
+        let energy =
+            invoke_for_energy(self.client.clone(), self.sender, self.margin, payload).await?;
+        self.estimates
+            .lock()
+            .expect("The estimates are not poisoned.")
+            .insert(
+                payload.receive_name.clone(),
+                (energy, std::time::Instant::now()),
             );
-        } else {
-            break Ok(());
+        Ok(energy)
+    }
+
+    /// Get the energy to use for an update from the latest estimate of its
+    /// entrypoint. If the estimate is older than the re-estimation interval,
+    /// it is refreshed in the background and the current estimate is used
+    /// until the refresh is done. If re-estimation fails, for example because
+    /// the update depends on transactions that are not yet finalized, the
+    /// previous estimate is kept.
+    fn energy(&mut self, payload: &UpdateContractPayload) -> anyhow::Result<Energy> {
+        let mut estimates = self
+            .estimates
+            .lock()
+            .expect("The estimates are not poisoned.");
+        let (energy, time) = estimates.get_mut(&payload.receive_name).with_context(|| {
+            format!(
+                "Could not estimate energy: no estimate for {}.",
+                payload.receive_name
+            )
+        })?;
+        let energy = *energy;
+        if time.elapsed() >= self.interval {
+            // Mark the estimate as fresh so that only one refresh is started.
+            *time = std::time::Instant::now();
+            let estimates = self.estimates.clone();
+            let client = self.client.clone();
+            let (sender, margin, payload) = (self.sender, self.margin, payload.clone());
+            tokio::spawn(async move {
+                match invoke_for_energy(client, sender, margin, &payload).await {
+                    Ok(energy) => {
+                        estimates
+                            .lock()
+                            .expect("The estimates are not poisoned.")
+                            .insert(payload.receive_name, (energy, std::time::Instant::now()));
+                    }
+                    Err(e) => eprintln!(
+                        "Could not re-estimate energy, keeping the previous estimate: {e:#}"
+                    ),
+                }
+            });
         }
+        Ok(energy)
+    }
+
+    /// Make a signed update of the given entrypoint using the estimated
+    /// energy.
+    pub fn make_update(
+        &mut self,
+        args: &CommonArgs,
+        nonce: Nonce,
+        entrypoint: &str,
+        amount: Amount,
+        message: OwnedParameter,
+    ) -> anyhow::Result<AccountTransaction<EncodedPayload>> {
+        let payload = self.payload(entrypoint, amount, message)?;
+        self.make_update_estimated_by(args, nonce, payload.clone(), &payload)
+    }
+
+    /// Make a signed update of the given entrypoint, estimating the energy of
+    /// the update itself first.
+    pub async fn make_estimated_update(
+        &mut self,
+        args: &CommonArgs,
+        nonce: Nonce,
+        entrypoint: &str,
+        amount: Amount,
+        message: OwnedParameter,
+    ) -> anyhow::Result<AccountTransaction<EncodedPayload>> {
+        let payload = self.payload(entrypoint, amount, message)?;
+        self.estimate(&payload).await?;
+        self.make_update_estimated_by(args, nonce, payload.clone(), &payload)
+    }
+
+    /// Make a signed update with the given payload, using the energy estimated
+    /// for the `probe` payload of the same entrypoint. This is used for updates
+    /// that cannot be estimated themselves, because they depend on
+    /// transactions that are not yet finalized.
+    fn make_update_estimated_by(
+        &mut self,
+        args: &CommonArgs,
+        nonce: Nonce,
+        payload: UpdateContractPayload,
+        probe: &UpdateContractPayload,
+    ) -> anyhow::Result<AccountTransaction<EncodedPayload>> {
+        let energy = self.energy(probe)?;
+        Ok(send::update_contract(
+            &args.keys,
+            args.keys.address,
+            nonce,
+            TransactionTime::seconds_after(args.expiry),
+            payload,
+            energy,
+        ))
     }
 }
 
-/// A generator that makes CCD transactions for a list of accounts.
-pub struct CcdGenerator {
-    args:     CommonArgs,
-    amount:   Amount,
-    accounts: Vec<AccountAddress>,
-    random:   bool,
-    rng:      StdRng,
-    count:    usize,
-    nonce:    Nonce,
+/// Invoke a contract update on the node and return the energy it used,
+/// excluding the base cost of the transaction, with the safety margin added.
+async fn invoke_for_energy(
+    mut client: v2::Client,
+    sender: AccountAddress,
+    margin: u64,
+    payload: &UpdateContractPayload,
+) -> anyhow::Result<Energy> {
+    let context = ContractContext::new_from_payload(sender, None, payload.clone());
+    let result = client
+        .invoke_instance(BlockIdentifier::LastFinal, &context)
+        .await?
+        .response;
+    match result {
+        InvokeContractResult::Success { used_energy, .. } => Ok(add_margin(used_energy, margin)),
+        InvokeContractResult::Failure { reason, .. } => {
+            anyhow::bail!("Invoking {} was rejected: {reason:?}", payload.receive_name)
+        }
+    }
 }
 
-impl CcdGenerator {
-    pub async fn instantiate(
-        mut client: v2::Client,
-        args: CommonArgs,
-        ccd_args: CcdArgs,
-    ) -> anyhow::Result<Self> {
-        // Get the list of receivers.
-        let accounts: Vec<AccountAddress> = match ccd_args.receivers {
-            None => {
-                client
-                    .get_account_list(BlockIdentifier::LastFinal)
-                    .await
-                    .context("Could not obtain a list of accounts.")?
-                    .response
-                    .try_collect()
-                    .await?
+/// Arguments used by all transaction generators.
+pub struct CommonArgs {
+    pub keys:                WalletAccount,
+    pub expiry:              u32,
+    /// Safety margin in percent that is added to estimated energy.
+    pub energy_margin:       u64,
+    /// Interval in seconds after which the energy of contract updates is
+    /// estimated again.
+    pub reestimate_interval: u64,
+    /// Seed of the randomness used by the generator, if the run should be
+    /// reproducible.
+    pub seed:                Option<u64>,
+}
+
+impl CommonArgs {
+    /// A random number generator seeded with the `--seed` option, or from
+    /// entropy if no seed is given.
+    fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+}
+
+/// A transaction generator.
+pub trait Generate {
+    /// Generate a transaction. Will be called in a loop.
+    fn generate(&mut self) -> anyhow::Result<BlockItem<EncodedPayload>>;
+}
+
+/// Submit a transaction, and record it in the metrics if they are enabled.
+async fn submit(
+    submitter: &mut Submitter,
+    item: &BlockItem<EncodedPayload>,
+    metrics: Option<&Metrics>,
+) -> anyhow::Result<TransactionHash> {
+    let Some(metrics) = metrics else {
+        return submitter.send(item).await;
+    };
+    let timer = metrics.latency.start_timer();
+    let result = submitter.send(item).await;
+    timer.observe_duration();
+    match result {
+        Ok(transaction_hash) => {
+            metrics.track(item, transaction_hash);
+            Ok(transaction_hash)
+        }
+        Err(e) => {
+            metrics.failed.inc();
+            Err(e)
+        }
+    }
+}
+
+/// Keeps track of the rate transactions are submitted at, and records it in
+/// the metrics once a second.
+struct RateTracker {
+    start: tokio::time::Instant,
+    count: u64,
+}
+
+impl RateTracker {
+    fn new() -> Self {
+        Self {
+            start: tokio::time::Instant::now(),
+            count: 0,
+        }
+    }
+
+    fn tick(&mut self, metrics: &Metrics) {
+        self.count += 1;
+        let elapsed = self.start.elapsed().as_secs_f64();
+        if elapsed >= 1.0 {
+            metrics.achieved_tps.set(self.count as f64 / elapsed);
+            *self = Self::new();
+        }
+    }
+}
+
+/// A transaction waiting to be submitted, together with its share of the
+/// bound on submissions in flight.
+type Queued = (BlockItem<EncodedPayload>, tokio::sync::OwnedSemaphorePermit);
+
+/// Submits transactions concurrently, with at most a given number of
+/// submissions in flight. Transactions of the same sender are submitted in the
+/// order they are dispatched, so that they reach the node in nonce order, but
+/// without waiting for the response to the previous submission. Likewise,
+/// chain updates are submitted in sequence number order.
+struct Pipeline {
+    submitter:     Submitter,
+    metrics:       Option<Metrics>,
+    max_in_flight: usize,
+    /// Permits for the submissions in flight.
+    permits:       std::sync::Arc<tokio::sync::Semaphore>,
+    /// For each sender, the queue of the task submitting its transactions in
+    /// order. Chain updates use the key `None`.
+    queues:        BTreeMap<Option<AccountAddress>, tokio::sync::mpsc::UnboundedSender<Queued>>,
+    /// The tasks submitting the queues, and the credential deployments, which
+    /// have no nonce and are submitted on their own.
+    tasks:         futures::stream::FuturesUnordered<tokio::task::JoinHandle<anyhow::Result<()>>>,
+    rate:          RateTracker,
+}
+
+impl Pipeline {
+    fn new(submitter: Submitter, metrics: Option<Metrics>, max_in_flight: usize) -> Self {
+        let max_in_flight = max_in_flight.max(1);
+        Self {
+            submitter,
+            metrics,
+            max_in_flight,
+            permits: std::sync::Arc::new(tokio::sync::Semaphore::new(max_in_flight)),
+            queues: BTreeMap::new(),
+            tasks: futures::stream::FuturesUnordered::new(),
+            rate: RateTracker::new(),
+        }
+    }
+
+    /// Wait for the next task to complete, and return its error if it failed.
+    async fn complete_one(&mut self) -> anyhow::Result<()> {
+        if let Some(result) = self.tasks.next().await {
+            result.context("Submission task panicked.")??;
+        }
+        Ok(())
+    }
+
+    /// Start submitting the transaction. This waits until there is room for
+    /// another submission in flight, and returns the error of any submission
+    /// that failed in the meantime.
+    async fn dispatch(&mut self, item: BlockItem<EncodedPayload>) -> anyhow::Result<()> {
+        while let Some(Some(result)) = self.tasks.next().now_or_never() {
+            result.context("Submission task panicked.")??;
+        }
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("The semaphore is never closed.");
+        if let Some(metrics) = &self.metrics {
+            self.rate.tick(metrics);
+        }
+
+        let key = match &item {
+            BlockItem::AccountTransaction(tx) => Some(tx.header.sender),
+            BlockItem::UpdateInstruction(_) => None,
+            BlockItem::CredentialDeployment(_) => {
+                let submitter = self.submitter.clone();
+                let metrics = self.metrics.clone();
+                self.tasks.push(tokio::spawn(submit_and_report(
+                    submitter,
+                    metrics,
+                    (item, permit),
+                )));
+                return Ok(());
+            }
+        };
+        let queue = self.queues.entry(key).or_insert_with(|| {
+            let (queue, mut receiver) = tokio::sync::mpsc::unbounded_channel::<Queued>();
+            let submitter = self.submitter.clone();
+            let metrics = self.metrics.clone();
+            // The submissions are started in the order they are queued, and up
+            // to `max_in_flight` of them are awaited concurrently.
+            let submissions = futures::stream::poll_fn(move |cx| receiver.poll_recv(cx))
+                .map(move |queued| submit_and_report(submitter.clone(), metrics.clone(), queued))
+                .buffered(self.max_in_flight)
+                .try_for_each(|()| async { Ok(()) });
+            self.tasks.push(tokio::spawn(submissions));
+            queue
+        });
+        if queue.send((item, permit)).is_err() {
+            // The task stopped because a submission failed, so report its error.
+            self.queues.remove(&key);
+            while !self.tasks.is_empty() {
+                self.complete_one().await?;
+            }
+            anyhow::bail!("Submission stopped unexpectedly.");
+        }
+        Ok(())
+    }
+
+    /// Wait for all submissions in flight to complete.
+    async fn finish(mut self) -> anyhow::Result<()> {
+        // Closing the queues lets their tasks complete once they are empty.
+        self.queues.clear();
+        while !self.tasks.is_empty() {
+            self.complete_one().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Submit a queued transaction and print its hash. The permit is released once
+/// the node has responded.
+async fn submit_and_report(
+    mut submitter: Submitter,
+    metrics: Option<Metrics>,
+    (item, permit): Queued,
+) -> anyhow::Result<()> {
+    let result = submit(&mut submitter, &item, metrics.as_ref()).await;
+    drop(permit);
+    let transaction_hash = result?;
+    if let BlockItem::AccountTransaction(tx) = &item {
+        println!(
+            "{}: Transaction {} submitted (nonce = {}, energy = {}).",
+            chrono::Utc::now(),
+            transaction_hash,
+            tx.header.nonce,
+            tx.header.energy_amount,
+        );
+    } else {
+        println!(
+            "{}: Transaction {} submitted.",
+            chrono::Utc::now(),
+            transaction_hash,
+        );
+    }
+    Ok(())
+}
+
+/// When to stop submitting transactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// Stop after the given number of transactions.
+    Count(u64),
+    /// Stop dispatching transactions once the given time has passed.
+    Duration(std::time::Duration),
+}
+
+/// Submit transactions made by the generator at the given rate. If `limit` is
+/// given, this stops after that many transactions have been submitted, or once
+/// the duration has passed and the submissions in flight have completed.
+pub async fn generate_transactions(
+    submitter: Submitter,
+    mut generator: impl Generate + Send + 'static,
+    tps: u16,
+    max_in_flight: usize,
+    metrics: Option<Metrics>,
+    limit: Option<Limit>,
+) -> anyhow::Result<()> {
+    // Create a channel between the task signing and the task sending transactions.
+    let (sender, mut rx) = tokio::sync::mpsc::channel(100);
+
+    // A task that will generate and sign transactions. Spawn it to run in the
+    // background.
+    let generated = metrics.as_ref().map(|metrics| metrics.generated.clone());
+    let count = match limit {
+        Some(Limit::Count(count)) => count,
+        _ => u64::MAX,
+    };
+    tokio::spawn(async move {
+        for _ in 0..count {
+            let tx = generator.generate();
+            if let (Some(generated), Ok(_)) = (&generated, &tx) {
+                generated.inc();
+            }
+            // The receiver is dropped when the duration has passed.
+            if sender.send(tx).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let deadline = match limit {
+        Some(Limit::Duration(duration)) => Some(tokio::time::Instant::now() + duration),
+        _ => None,
+    };
+    if let Some(metrics) = &metrics {
+        metrics.target_tps.set(tps.into());
+    }
+    let mut pipeline = Pipeline::new(submitter, metrics, max_in_flight);
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_micros(
+        1_000_000 / u64::from(tps),
+    ));
+    loop {
+        interval.tick().await;
+        let next = match deadline {
+            // Stop once the duration has passed, also while waiting for the generator.
+            Some(deadline) => tokio::time::timeout_at(deadline, rx.recv())
+                .await
+                .ok()
+                .flatten()
+                .filter(|_| tokio::time::Instant::now() < deadline),
+            None => rx.recv().await,
+        };
+        if let Some(item) = next.transpose()? {
+            if let Some(metrics) = &pipeline.metrics {
+                metrics.backlog.set(rx.len() as i64);
+            }
+            pipeline.dispatch(item).await?;
+        } else {
+            break pipeline.finish().await;
+        }
+    }
+}
+
+/// Generate `count` transactions using the generator and write them to the
+/// file at `out`. Each transaction is written as a serialized [`BlockItem`]
+/// prefixed by its length as a big-endian `u32`.
+///
+/// Note that the transactions use consecutive nonces of the sender account, so
+/// the sender must not send any other transactions before the file is
+/// replayed.
+pub fn sign_transactions(
+    mut generator: impl Generate,
+    out: &Path,
+    count: u64,
+) -> anyhow::Result<()> {
+    let file = std::fs::File::create(out).context("Could not create the output file.")?;
+    let mut writer = std::io::BufWriter::new(file);
+    for i in 0..count {
+        let item = generator.generate()?;
+        write_signed_transaction(&mut writer, &item)?;
+        if (i + 1) % 1000 == 0 {
+            println!("Signed {} transactions.", i + 1);
+        }
+    }
+    writer.flush()?;
+    println!("Wrote {count} signed transactions to {}.", out.display());
+    Ok(())
+}
+
+/// Read the transactions written by [`sign_transactions`] from a file.
+fn read_signed_transactions(file: &Path) -> anyhow::Result<Vec<BlockItem<EncodedPayload>>> {
+    let file = std::fs::File::open(file).context("Could not open the transactions file.")?;
+    let mut reader = std::io::BufReader::new(file);
+    let mut items = Vec::new();
+    loop {
+        let mut len = [0u8; 4];
+        match reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        let mut bytes = vec![0u8; u32::from_be_bytes(len) as usize];
+        reader
+            .read_exact(&mut bytes)
+            .context("The transactions file is truncated.")?;
+        let item = BlockItem::deserial(&mut Cursor::new(bytes))
+            .with_context(|| format!("Could not parse transaction number {}.", items.len() + 1))?;
+        items.push(item);
+    }
+    Ok(items)
+}
+
+/// Submit the transactions that were written to a file by
+/// [`sign_transactions`], either at the given rate or as fast as possible.
+pub async fn replay_transactions(
+    submitter: Submitter,
+    replay_args: ReplayArgs,
+    tps: u16,
+    max_in_flight: usize,
+    metrics: Option<Metrics>,
+) -> anyhow::Result<()> {
+    let items = read_signed_transactions(&replay_args.file)?;
+    println!("Read {} signed transactions.", items.len());
+    if let Some(metrics) = &metrics {
+        metrics.generated.inc_by(items.len() as u64);
+        if !replay_args.max_speed {
+            metrics.target_tps.set(tps.into());
+        }
+    }
+
+    let mut interval = if replay_args.max_speed {
+        None
+    } else {
+        Some(tokio::time::interval(tokio::time::Duration::from_micros(
+            1_000_000 / u64::from(tps),
+        )))
+    };
+    let count = items.len();
+    let start = tokio::time::Instant::now();
+    let mut pipeline = Pipeline::new(submitter, metrics, max_in_flight);
+    for (i, item) in items.into_iter().enumerate() {
+        if let Some(interval) = interval.as_mut() {
+            interval.tick().await;
+        }
+        if let Some(metrics) = &pipeline.metrics {
+            metrics.backlog.set((count - i - 1) as i64);
+        }
+        pipeline.dispatch(item).await?;
+    }
+    pipeline.finish().await?;
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "Submitted {count} transactions in {elapsed:.2}s ({:.2} transactions per second).",
+        count as f64 / elapsed,
+    );
+    Ok(())
+}
+
+/// Latencies of the invocations made by [`invoke_instances`] since the last
+/// report.
+#[derive(Default)]
+struct InvokeStats {
+    latencies: Vec<std::time::Duration>,
+    /// Number of invocations that were rejected by the contract, for example
+    /// because the token does not exist. They are included in the latencies.
+    rejected:  u64,
+    /// Number of invocations that failed at the node.
+    failed:    u64,
+}
+
+impl InvokeStats {
+    /// Print the number of invocations and the latency percentiles since the
+    /// last report, and start over.
+    fn report(&mut self, elapsed: std::time::Duration) {
+        let Self {
+            mut latencies,
+            rejected,
+            failed,
+        } = std::mem::take(self);
+        latencies.sort();
+        let percentile = |p: usize| {
+            let rank = (latencies.len() * p).div_ceil(100).saturating_sub(1);
+            latencies.get(rank).copied().unwrap_or_default()
+        };
+        println!(
+            "{}: {} invocations ({:.2} per second), {rejected} rejected, {failed} failed. Latency \
+             p50 = {:?}, p90 = {:?}, p99 = {:?}, max = {:?}.",
+            chrono::Utc::now(),
+            latencies.len(),
+            latencies.len() as f64 / elapsed.as_secs_f64(),
+            percentile(50),
+            percentile(90),
+            percentile(99),
+            percentile(100),
+        );
+    }
+}
+
+/// Invoke view entrypoints of a CIS-2 contract at the given rate with random
+/// arguments, and periodically report the latency of the invocations. The
+/// invocations are distributed between the nodes in turn. This runs until it
+/// is stopped.
+pub async fn invoke_instances(
+    clients: Vec<v2::Client>,
+    invoke_args: InvokeArgs,
+    tps: u16,
+    max_in_flight: usize,
+    seed: Option<u64>,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !invoke_args.entrypoints.is_empty(),
+        "At least one entrypoint must be given."
+    );
+    if let TokenIdKind::U8 = invoke_args.token_id {
+        anyhow::ensure!(
+            invoke_args.max_token_id <= u8::MAX.into(),
+            "The maximum token id must be at most 255 for `u8` token ids."
+        );
+    }
+    let mut client = clients
+        .first()
+        .context("At least one node must be given.")?
+        .clone();
+    let contract = invoke_args.contract;
+    let info = client
+        .get_instance_info(contract, BlockIdentifier::LastFinal)
+        .await
+        .with_context(|| format!("Could not get the contract instance {contract}."))?
+        .response;
+    let contract_name = info.name().as_contract_name().contract_name().to_string();
+    let accounts = get_receivers(&mut client, invoke_args.receivers).await?;
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let stats = std::sync::Arc::new(std::sync::Mutex::new(InvokeStats::default()));
+    let mut in_flight = futures::stream::FuturesUnordered::<tokio::task::JoinHandle<()>>::new();
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_micros(
+        1_000_000 / u64::from(tps),
+    ));
+    let report_interval = std::time::Duration::from_secs(invoke_args.report_interval.max(1));
+    let mut last_report = std::time::Instant::now();
+    for count in 0usize.. {
+        interval.tick().await;
+        while let Some(Some(result)) = in_flight.next().now_or_never() {
+            result.context("Invocation task panicked.")?;
+        }
+        while in_flight.len() >= max_in_flight.max(1) {
+            if let Some(result) = in_flight.next().await {
+                result.context("Invocation task panicked.")?;
+            }
+        }
+        if last_report.elapsed() >= report_interval {
+            stats
+                .lock()
+                .expect("Lock is not poisoned.")
+                .report(last_report.elapsed());
+            last_report = std::time::Instant::now();
+        }
+
+        let entrypoint = invoke_args.entrypoints[count % invoke_args.entrypoints.len()];
+        let token_id = invoke_args
+            .token_id
+            .token_id(rng.gen_range(0..=invoke_args.max_token_id));
+        let (name, parameter) = match entrypoint {
+            ViewEntrypoint::BalanceOf => {
+                let address = accounts[rng.gen_range(0..accounts.len())];
+                let params = BalanceOfQueryParams::new(vec![BalanceOfQuery {
+                    token_id,
+                    address: Address::Account(address),
+                }])?;
+                ("balanceOf", OwnedParameter::from_serial(&params)?)
             }
-            Some(receivers) => serde_json::from_str(
-                &std::fs::read_to_string(receivers)
-                    .context("Could not read the receivers file.")?,
+            ViewEntrypoint::TokenMetadata => {
+                let params = TokenMetadataQueryParams::new(vec![token_id])?;
+                ("tokenMetadata", OwnedParameter::from_serial(&params)?)
+            }
+        };
+        let context = ContractContext {
+            parameter,
+            ..ContractContext::new(
+                contract,
+                OwnedReceiveName::new(format!("{contract_name}.{name}"))?,
             )
-            .context("Could not parse the receivers file.")?,
         };
-        anyhow::ensure!(!accounts.is_empty(), "List of receivers must not be empty.");
+
+        let mut client = clients[count % clients.len()].clone();
+        let stats = stats.clone();
+        in_flight.push(tokio::spawn(async move {
+            let start = std::time::Instant::now();
+            let result = client
+                .invoke_instance(BlockIdentifier::LastFinal, &context)
+                .await;
+            let latency = start.elapsed();
+            let mut stats = stats.lock().expect("Lock is not poisoned.");
+            match result {
+                Ok(response) => {
+                    stats.latencies.push(latency);
+                    if let InvokeContractResult::Failure { .. } = response.response {
+                        stats.rejected += 1;
+                    }
+                }
+                Err(e) => {
+                    stats.failed += 1;
+                    eprintln!("Invoking {contract} failed: {e}");
+                }
+            }
+        }));
+    }
+    Ok(())
+}
+
+/// A generator that makes CCD transactions for a list of accounts.
+pub struct CcdGenerator {
+    args:      CommonArgs,
+    amount:    Amount,
+    accounts:  Vec<AccountAddress>,
+    random:    bool,
+    rng:       StdRng,
+    count:     usize,
+    nonce:     Nonce,
+    memo_size: Option<usize>,
+    schedule:  Option<u8>,
+}
+
+impl CcdGenerator {
+    pub async fn instantiate(
+        mut client: v2::Client,
+        args: CommonArgs,
+        ccd_args: CcdArgs,
+    ) -> anyhow::Result<Self> {
+        if let Some(memo_size) = ccd_args.memo_size {
+            anyhow::ensure!(
+                memo_size <= concordium_rust_sdk::constants::MAX_MEMO_SIZE,
+                "Memo size must be at most {} bytes.",
+                concordium_rust_sdk::constants::MAX_MEMO_SIZE
+            );
+        }
+        if let Some(schedule) = ccd_args.schedule {
+            anyhow::ensure!(schedule > 0, "The number of release points cannot be 0.");
+            anyhow::ensure!(
+                ccd_args.amount > Amount::zero(),
+                "Scheduled transfers require a non-zero amount."
+            );
+        }
+
+        // Get the list of receivers.
+        let accounts = get_receivers(&mut client, ccd_args.receivers).await?;
 
         // Filter accounts based on mode.
         let (random, accounts) = match ccd_args.mode {
@@ -267,7 +1593,7 @@ impl CcdGenerator {
             .await?;
         anyhow::ensure!(nonce.all_final, "Not all transactions are finalized.");
 
-        let rng = StdRng::from_entropy();
+        let rng = args.rng();
         Ok(Self {
             args,
             amount: ccd_args.amount,
@@ -276,12 +1602,14 @@ impl CcdGenerator {
             rng,
             count: 0,
             nonce: nonce.nonce,
+            memo_size: ccd_args.memo_size,
+            schedule: ccd_args.schedule,
         })
     }
 }
 
 impl Generate for CcdGenerator {
-    fn generate(&mut self) -> anyhow::Result<AccountTransaction<EncodedPayload>> {
+    fn generate(&mut self) -> anyhow::Result<BlockItem<EncodedPayload>> {
         let next_account = if self.random {
             let n = self.rng.gen_range(0..self.accounts.len());
             self.accounts[n]
@@ -290,535 +1618,4477 @@ impl Generate for CcdGenerator {
         };
 
         let expiry = TransactionTime::seconds_after(self.args.expiry);
-        let tx = send::transfer(
-            &self.args.keys,
-            self.args.keys.address,
-            self.nonce,
-            expiry,
-            next_account,
-            self.amount,
-        );
+        let memo = self
+            .memo_size
+            .map(|size| {
+                let mut bytes = vec![0u8; size];
+                self.rng.fill(&mut bytes[..]);
+                Memo::try_from(bytes)
+            })
+            .transpose()?;
+        let schedule = self.schedule.map(|num_releases| {
+            let now = chrono::Utc::now().timestamp_millis() as u64;
+            (1..=u64::from(num_releases))
+                .map(|i| {
+                    let release = Timestamp::from_timestamp_millis(now + i * 60_000);
+                    (release, self.amount)
+                })
+                .collect::<Vec<_>>()
+        });
+        let tx = match (schedule, memo) {
+            (None, None) => send::transfer(
+                &self.args.keys,
+                self.args.keys.address,
+                self.nonce,
+                expiry,
+                next_account,
+                self.amount,
+            ),
+            (None, Some(memo)) => send::transfer_with_memo(
+                &self.args.keys,
+                self.args.keys.address,
+                self.nonce,
+                expiry,
+                next_account,
+                self.amount,
+                memo,
+            ),
+            (Some(schedule), None) => send::transfer_with_schedule(
+                &self.args.keys,
+                self.args.keys.address,
+                self.nonce,
+                expiry,
+                next_account,
+                schedule,
+            ),
+            (Some(schedule), Some(memo)) => send::transfer_with_schedule_and_memo(
+                &self.args.keys,
+                self.args.keys.address,
+                self.nonce,
+                expiry,
+                next_account,
+                schedule,
+                memo,
+            ),
+        };
 
         self.nonce.next_mut();
         self.count += 1;
 
-        Ok(tx)
+        Ok(BlockItem::AccountTransaction(tx))
     }
 }
 
-/// A generator that makes transactions that mints CIS-2 NFT tokens for the
-/// sender.
-pub struct MintCis2Generator {
-    client:  Cis2Contract,
-    args:    CommonArgs,
-    nonce:   Nonce,
-    next_id: u32,
+/// A generator that makes transactions that register data of a fixed size.
+pub struct RegisterDataGenerator {
+    args:  CommonArgs,
+    size:  usize,
+    rng:   StdRng,
+    nonce: Nonce,
 }
 
-#[derive(concordium_std::Serial)]
-struct MintCis2NftParams {
-    owner:  concordium_std::Address,
-    #[concordium(size_length = 1)]
-    tokens: collections::BTreeSet<TokenId>,
-}
+impl RegisterDataGenerator {
+    pub async fn instantiate(
+        mut client: v2::Client,
+        args: CommonArgs,
+        register_data_args: RegisterDataArgs,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            register_data_args.size <= concordium_rust_sdk::constants::MAX_REGISTERED_DATA_SIZE,
+            "Data size must be at most {} bytes.",
+            concordium_rust_sdk::constants::MAX_REGISTERED_DATA_SIZE
+        );
 
-impl MintCis2Generator {
-    pub async fn instantiate(mut client: v2::Client, args: CommonArgs) -> anyhow::Result<Self> {
         // Get the initial nonce.
-        let mut nonce = client
+        let nonce = client
             .get_next_account_sequence_number(&args.keys.address)
             .await?;
+        anyhow::ensure!(nonce.all_final, "Not all transactions are finalized.");
 
-        // Deploy and initialize the contract.
-        let info = ContractDeploymentInfo {
-            module:      MINT_CIS2_MODULE,
-            name:        "init_cis2_nft",
-            // Determined by running the transaction and inspecting the energy cost with
-            // concordium-client.
-            init_energy: Energy::from(2397),
-        };
-        let contract_address = info
-            .deploy_and_init_contract(
-                &mut client,
-                &args,
-                OwnedParameter::empty(),
-                &mut nonce.nonce,
-            )
-            .await
-            .context("Could not deploy/init the contract.")?;
-
-        let client = Cis2Contract::create(client, contract_address).await?;
+        let rng = args.rng();
         Ok(Self {
-            client,
             args,
+            size: register_data_args.size,
+            rng,
             nonce: nonce.nonce,
-            next_id: 0,
         })
     }
 }
 
-impl Generate for MintCis2Generator {
-    fn generate(&mut self) -> anyhow::Result<AccountTransaction<EncodedPayload>> {
-        // We mint a single token for ourselves.
-        let params = MintCis2NftParams {
-            owner:  Address::Account(self.args.keys.address),
-            tokens: [TokenId::new_u32(self.next_id)].into(),
-        };
+impl Generate for RegisterDataGenerator {
+    fn generate(&mut self) -> anyhow::Result<BlockItem<EncodedPayload>> {
+        let mut bytes = vec![0u8; self.size];
+        self.rng.fill(&mut bytes[..]);
 
-        let metadata = ContractTransactionMetadata {
-            sender_address: self.args.keys.address,
-            nonce:          self.nonce,
-            expiry:         TransactionTime::seconds_after(self.args.expiry),
-            // Determined by running the transaction and inspecting the energy cost with
-            // concordium-client and then adding extra to account for variance.
-            energy:         GivenEnergy::Absolute(Energy::from(3500)),
-            amount:         Amount::zero(),
-        };
-        let tx = self.client.make_update::<_, anyhow::Error>(
+        let tx = send::register_data(
             &self.args.keys,
-            &metadata,
-            "mint",
-            &params,
-        )?;
+            self.args.keys.address,
+            self.nonce,
+            TransactionTime::seconds_after(self.args.expiry),
+            RegisteredData::try_from(bytes)?,
+        );
         self.nonce.next_mut();
-        self.next_id += 1;
 
-        Ok(tx)
+        Ok(BlockItem::AccountTransaction(tx))
     }
 }
 
-/// A generator that makes transactions that transfer CIS-2 tokens to a list of
-/// accounts.
-pub struct TransferCis2Generator {
-    client:   Cis2Contract,
-    args:     CommonArgs,
-    accounts: Vec<AccountAddress>,
-    nonce:    Nonce,
-    count:    usize,
-}
-
-#[derive(concordium_std::Serial)]
-struct MintCis2TokenParam {
-    token_amount: TokenAmount,
-    metadata_url: MetadataUrl,
-}
-
-#[derive(concordium_std::Serial)]
-struct MintCis2TokenParams {
-    owner:  Address,
-    tokens: BTreeMap<TokenId, MintCis2TokenParam>,
+/// A generator that makes transactions that move CCD between the public and
+/// the encrypted balance of the sender, and make encrypted transfers to a list
+/// of accounts.
+pub struct EncryptedTransferGenerator {
+    args:       CommonArgs,
+    amount:     Amount,
+    /// The receivers together with their public encryption keys.
+    accounts:   Vec<(AccountAddress, elgamal::PublicKey<ArCurve>)>,
+    secret_key: elgamal::SecretKey<ArCurve>,
+    global:     GlobalContext<ArCurve>,
+    /// The encrypted balance of the sender that the next transaction will
+    /// use, together with its decryption.
+    balance:    AggregatedDecryptedAmount<ArCurve>,
+    rng:        StdRng,
+    nonce:      Nonce,
+    count:      usize,
 }
 
-impl TransferCis2Generator {
+impl EncryptedTransferGenerator {
     pub async fn instantiate(
         mut client: v2::Client,
         args: CommonArgs,
-        transfer_cis2_args: TransferCis2Args,
+        encrypted_args: EncryptedTransferArgs,
     ) -> anyhow::Result<Self> {
-        // Get the list of receivers.
-        let accounts: Vec<AccountAddress> = match transfer_cis2_args.receivers {
-            None => {
-                client
-                    .get_account_list(BlockIdentifier::LastFinal)
-                    .await
-                    .context("Could not obtain a list of accounts.")?
-                    .response
-                    .try_collect()
-                    .await?
-            }
-            Some(receivers) => serde_json::from_str(
-                &std::fs::read_to_string(receivers)
-                    .context("Could not read the receivers file.")?,
+        let secret_key: elgamal::SecretKey<ArCurve> = {
+            let value: serde_json::Value = serde_json::from_str(
+                &std::fs::read_to_string(&encrypted_args.encryption_key)
+                    .context("Could not read the encryption key file.")?,
             )
-            .context("Could not parse the receivers file.")?,
+            .context("Could not parse the encryption key file.")?;
+            let key = value
+                .get("encryptionSecretKey")
+                .context("The encryption key file has no `encryptionSecretKey` field.")?;
+            serde_json::from_value(key.clone()).context("Could not parse the encryption key.")?
         };
-        anyhow::ensure!(!accounts.is_empty(), "List of receivers must not be empty.");
 
-        // Get the initial nonce.
-        let mut nonce = client
-            .get_next_account_sequence_number(&args.keys.address)
+        let global = client
+            .get_cryptographic_parameters(BlockIdentifier::LastFinal)
+            .await?
+            .response;
+
+        // Encrypted transfers to oneself are not allowed, so the sender is excluded
+        // from the receivers.
+        let receivers: Vec<_> = get_receivers(&mut client, encrypted_args.receivers)
+            .await?
+            .into_iter()
+            .filter(|addr| *addr != args.keys.address)
+            .collect();
+        anyhow::ensure!(
+            !receivers.is_empty(),
+            "List of receivers must contain an account other than the sender."
+        );
+        println!(
+            "Getting encryption keys of {} receivers...",
+            receivers.len()
+        );
+        let accounts = futures::stream::iter(receivers)
+            .map(|addr| {
+                let mut client = client.clone();
+                async move {
+                    let info = client
+                        .get_account_info(
+                            &AccountIdentifier::Address(addr),
+                            BlockIdentifier::LastFinal,
+                        )
+                        .await?;
+                    Ok::<_, anyhow::Error>((addr, info.response.account_encryption_key))
+                }
+            })
+            .buffered(16)
+            .try_collect()
             .await?;
 
-        // Deploy and initialize the contract.
-        let info = ContractDeploymentInfo {
-            module:      TRANSFER_CIS2_MODULE,
-            name:        "init_cis2_multi",
-            // Determined by running the transaction and inspecting the energy cost with
-            // concordium-client.
-            init_energy: Energy::from(2353),
-        };
-        let contract_address = info
-            .deploy_and_init_contract(
-                &mut client,
-                &args,
-                OwnedParameter::empty(),
-                &mut nonce.nonce,
+        let info = client
+            .get_account_info(
+                &AccountIdentifier::Address(args.keys.address),
+                BlockIdentifier::LastFinal,
             )
-            .await
-            .context("Could not deploy/init the contract.")?;
-
-        let mut client = Cis2Contract::create(client, contract_address).await?;
-
-        // The rest of the function mints u64::MAX tokens for the sender.
-        println!("Minting u64::MAX tokens for ourselves...");
-
-        let param = MintCis2TokenParam {
-            token_amount: TokenAmount::from(u64::MAX),
-            metadata_url: MetadataUrl::new("https://example.com".into(), None)?,
-        };
-        let params = MintCis2TokenParams {
-            owner:  Address::Account(args.keys.address),
-            tokens: [(TokenId::new_u8(0), param)].into(),
-        };
+            .await?
+            .response;
+        anyhow::ensure!(
+            elgamal::PublicKey::from(&secret_key) == info.account_encryption_key,
+            "The encryption key does not belong to the sender account."
+        );
 
-        let metadata = ContractTransactionMetadata {
-            sender_address: args.keys.address,
-            nonce:          nonce.nonce,
-            expiry:         TransactionTime::seconds_after(args.expiry),
-            // Determined by running the transaction and inspecting the energy cost with
-            // concordium-client.
-            energy:         GivenEnergy::Absolute(Energy::from(2740)),
-            amount:         Amount::zero(),
+        // Only the self amount of the sender is used, so that incoming encrypted
+        // amounts do not affect the generated transactions.
+        let self_amount = info.account_encrypted_amount.self_amount;
+        let table =
+            elgamal::BabyStepGiantStep::new(global.encryption_in_exponent_generator(), 1 << 16);
+        let balance = AggregatedDecryptedAmount {
+            agg_amount:           encrypted_transfers::decrypt_amount(
+                &table,
+                &secret_key,
+                &self_amount,
+            ),
+            agg_encrypted_amount: self_amount,
+            agg_index:            EncryptedAmountAggIndex::from(
+                info.account_encrypted_amount.start_index,
+            ),
         };
-        let transaction_hash = client
-            .update::<_, anyhow::Error>(&args.keys, &metadata, "mint", &params)
-            .await?;
-        nonce.nonce.next_mut();
 
-        let (_, summary) = client
-            .client
-            .wait_until_finalized(&transaction_hash)
+        // Get the initial nonce.
+        let nonce = client
+            .get_next_account_sequence_number(&args.keys.address)
             .await?;
-        anyhow::ensure!(
-            summary.is_success(),
-            "Mint transaction failed (hash = {transaction_hash})."
-        );
-        println!(
-            "Minted u64::MAX tokens (hash = {transaction_hash}, energy = {}).",
-            summary.energy_cost,
-        );
+        anyhow::ensure!(nonce.all_final, "Not all transactions are finalized.");
 
+        let rng = args.rng();
         Ok(Self {
-            client,
             args,
+            amount: encrypted_args.amount,
             accounts,
+            secret_key,
+            global,
+            balance,
+            rng,
             nonce: nonce.nonce,
             count: 0,
         })
     }
 }
 
-impl Generate for TransferCis2Generator {
-    fn generate(&mut self) -> anyhow::Result<AccountTransaction<EncodedPayload>> {
-        let next_account = self.accounts[self.count % self.accounts.len()];
-        let transfer = Transfer {
-            token_id: TokenId::new_u8(0),
-            amount:   TokenAmount::from(1u32),
-            from:     Address::Account(self.args.keys.address),
-            to:       Receiver::Account(next_account),
-            data:     AdditionalData::new(vec![])?,
-        };
+impl Generate for EncryptedTransferGenerator {
+    fn generate(&mut self) -> anyhow::Result<BlockItem<EncodedPayload>> {
+        let expiry = TransactionTime::seconds_after(self.args.expiry);
 
-        let metadata = Cis2TransactionMetadata {
-            sender_address: self.args.keys.address,
-            nonce:          self.nonce,
-            expiry:         TransactionTime::seconds_after(self.args.expiry),
-            // Determined by running the transaction and inspecting the energy cost with
-            // concordium-client and then adding extra to account for variance.
-            energy:         GivenEnergy::Absolute(Energy::from(3500)),
-            amount:         Amount::zero(),
+        // We cycle between moving twice the amount to the encrypted balance, making an
+        // encrypted transfer, and moving the amount back to the public balance, so
+        // that the encrypted balance stays the same over each cycle. The encrypted
+        // balance is tracked locally, since the transactions are generated before
+        // the previous ones are executed.
+        let tx = match self.count % 3 {
+            // Transfer to encrypted
+            0 => {
+                let amount = self.amount * 2;
+                let encrypted =
+                    encrypted_transfers::encrypt_amount_with_fixed_randomness(&self.global, amount);
+                self.balance.agg_encrypted_amount =
+                    encrypted_transfers::aggregate(&self.balance.agg_encrypted_amount, &encrypted);
+                self.balance.agg_amount += amount;
+                send::transfer_to_encrypted(
+                    &self.args.keys,
+                    self.args.keys.address,
+                    self.nonce,
+                    expiry,
+                    amount,
+                )
+            }
+            // Encrypted transfer
+            1 => {
+                let (receiver, receiver_pk) = self.accounts[(self.count / 3) % self.accounts.len()];
+                let data = encrypted_transfers::make_transfer_data(
+                    &self.global,
+                    &receiver_pk,
+                    &self.secret_key,
+                    &self.balance,
+                    self.amount,
+                    &mut self.rng,
+                )
+                .context("Insufficient encrypted balance for the encrypted transfer.")?;
+                self.balance = AggregatedDecryptedAmount {
+                    agg_encrypted_amount: data.remaining_amount.clone(),
+                    agg_amount:           self.balance.agg_amount - self.amount,
+                    agg_index:            data.index,
+                };
+                send::encrypted_transfer(
+                    &self.args.keys,
+                    self.args.keys.address,
+                    self.nonce,
+                    expiry,
+                    receiver,
+                    data,
+                )
+            }
+            // Transfer to public
+            _ => {
+                let data = encrypted_transfers::make_sec_to_pub_transfer_data(
+                    &self.global,
+                    &self.secret_key,
+                    &self.balance,
+                    self.amount,
+                    &mut self.rng,
+                )
+                .context("Insufficient encrypted balance for the transfer to public.")?;
+                self.balance = AggregatedDecryptedAmount {
+                    agg_encrypted_amount: data.remaining_amount.clone(),
+                    agg_amount:           self.balance.agg_amount - self.amount,
+                    agg_index:            data.index,
+                };
+                send::transfer_to_public(
+                    &self.args.keys,
+                    self.args.keys.address,
+                    self.nonce,
+                    expiry,
+                    data,
+                )
+            }
         };
-        let tx = self
-            .client
-            .make_transfer_single(&self.args.keys, metadata, transfer)?;
         self.nonce.next_mut();
         self.count += 1;
 
-        Ok(tx)
+        Ok(BlockItem::AccountTransaction(tx))
     }
 }
 
-/// A generator that makes transactions that wrap, unwrap, and transfer WCCDs.
-pub struct WccdGenerator {
-    client:   Cis2Contract,
-    args:     CommonArgs,
-    nonce:    Nonce,
-    count:    usize,
-    accounts: Vec<AccountAddress>,
+/// The stake of an account in the [`StakingGenerator`].
+#[derive(Debug, Clone)]
+enum Stake {
+    None,
+    Baker {
+        stake:   Amount,
+        restake: bool,
+    },
+    Delegator {
+        stake:   Amount,
+        restake: bool,
+        target:  DelegationTarget,
+    },
 }
 
-#[derive(concordium_std::Serial)]
-struct SetMetadataUrlParams {
-    url:  String,
-    hash: Option<[u8; 32]>,
+/// An account used by the [`StakingGenerator`] together with its locally
+/// tracked staking state.
+struct Staker {
+    keys:           WalletAccount,
+    /// The id of the account as a baker, which is its account index.
+    baker_id:       BakerId,
+    nonce:          Nonce,
+    /// Whether the account should act as a baker or as a delegator.
+    baker:          bool,
+    /// The stake of the account, assuming that all previously generated
+    /// transactions succeed.
+    stake:          Stake,
+    /// The public balance of the account, minus the maximal cost of the
+    /// transactions generated since.
+    balance:        Amount,
+    /// If there is a pending stake change, the time after which it has taken
+    /// effect, and the stake after the change. No stake changes are made until
+    /// then.
+    pending_change: Option<(chrono::DateTime<chrono::Utc>, Stake)>,
+    /// Counter used to cycle through the kinds of changes.
+    count:          usize,
 }
 
-#[derive(concordium_std::Serial)]
-struct WrapParams {
-    to:   Receiver,
-    data: AdditionalData,
+/// A baker pool as tracked by the [`StakingGenerator`].
+struct Pool {
+    equity:    Amount,
+    delegated: Amount,
+    /// The time from which the pool is used as a delegation target, or [`None`]
+    /// if it is not open for delegation.
+    open_from: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-#[derive(concordium_std::Serial)]
-struct UnwrapParams {
-    amount:   TokenAmount,
-    owner:    Address,
-    receiver: Address,
-    data:     AdditionalData,
+/// The baker pools, starting from their state when the [`StakingGenerator`]
+/// started and updated with the stake changes made by the generator, assuming
+/// that all of them succeed.
+struct Pools {
+    pools:          BTreeMap<BakerId, Pool>,
+    /// The total capital staked in all pools and by passive delegation.
+    total:          Amount,
+    leverage_bound: LeverageFactor,
+    capital_bound:  CapitalBound,
+    /// How long it takes before a new pool is used as a delegation target. This
+    /// is the expiry of the transaction adding the baker, after which it is in
+    /// a block if it is ever going to be.
+    delay:          chrono::Duration,
+    /// Delegations that were moved to another target, with the time until
+    /// which they are still counted against the pool they left. On chain the
+    /// new target takes effect at the next payday, so until then the stake
+    /// is counted against both pools.
+    moving:         Vec<(chrono::DateTime<chrono::Utc>, BakerId, Amount)>,
 }
 
-impl WccdGenerator {
-    pub async fn instantiate(mut client: v2::Client, args: CommonArgs) -> anyhow::Result<Self> {
-        // Get the initial nonce.
-        let mut nonce = client
-            .get_next_account_sequence_number(&args.keys.address)
-            .await?;
+impl Pools {
+    /// Update the pools when the stake of the account with the given baker id
+    /// changes from `old` to `new`. A pool is removed when the removal of its
+    /// baker takes effect.
+    fn update(
+        &mut self,
+        baker_id: BakerId,
+        old: &Stake,
+        new: &Stake,
+        now: chrono::DateTime<chrono::Utc>,
+    ) {
+        let sub = |a: Amount, b: Amount| a.checked_sub(b).unwrap_or_else(Amount::zero);
+        match old {
+            Stake::None => {}
+            Stake::Baker { stake, .. } => {
+                self.total = sub(self.total, *stake);
+                if let Some(pool) = self.pools.get_mut(&baker_id) {
+                    pool.equity = sub(pool.equity, *stake);
+                }
+            }
+            Stake::Delegator { stake, target, .. } => {
+                self.total = sub(self.total, *stake);
+                if let DelegationTarget::Baker { baker_id } = target {
+                    if let Some(pool) = self.pools.get_mut(baker_id) {
+                        pool.delegated = sub(pool.delegated, *stake);
+                    }
+                }
+            }
+        }
+        match new {
+            Stake::None => {
+                if let Stake::Baker { .. } = old {
+                    self.pools.remove(&baker_id);
+                }
+            }
+            Stake::Baker { stake, .. } => {
+                self.total += *stake;
+                let open_from = Some(now + self.delay);
+                self.pools
+                    .entry(baker_id)
+                    .or_insert(Pool {
+                        equity: Amount::zero(),
+                        delegated: Amount::zero(),
+                        open_from,
+                    })
+                    .equity += *stake;
+            }
+            Stake::Delegator { stake, target, .. } => {
+                self.total += *stake;
+                if let DelegationTarget::Baker { baker_id } = target {
+                    if let Some(pool) = self.pools.get_mut(baker_id) {
+                        pool.delegated += *stake;
+                    }
+                }
+            }
+        }
+    }
 
-        let info = ContractDeploymentInfo {
-            module:      WCCD_MODULE,
-            name:        "init_cis2_wCCD",
-            // Determined by running the transaction and inspecting the energy cost with
-            // concordium-client.
-            init_energy: Energy::from(2596),
+    /// Keep counting `amount` against the target a delegation moved away from,
+    /// until the given time.
+    fn move_delegation(
+        &mut self,
+        from: &DelegationTarget,
+        amount: Amount,
+        until: chrono::DateTime<chrono::Utc>,
+    ) {
+        if let DelegationTarget::Baker { baker_id } = from {
+            if let Some(pool) = self.pools.get_mut(baker_id) {
+                pool.delegated += amount;
+                self.moving.push((until, *baker_id, amount));
+            }
+        }
+    }
+
+    /// Stop counting the moved delegations against the pools they left, once
+    /// the move has taken effect.
+    fn settle(&mut self, now: chrono::DateTime<chrono::Utc>) {
+        let (done, moving) = std::mem::take(&mut self.moving)
+            .into_iter()
+            .partition(|(until, ..)| *until <= now);
+        self.moving = moving;
+        for (_, baker_id, amount) in done {
+            if let Some(pool) = self.pools.get_mut(&baker_id) {
+                pool.delegated = pool
+                    .delegated
+                    .checked_sub(amount)
+                    .unwrap_or_else(Amount::zero);
+            }
+        }
+    }
+
+    /// Stop using a pool as a delegation target, because the removal of its
+    /// baker is pending.
+    fn close(&mut self, baker_id: BakerId) {
+        if let Some(pool) = self.pools.get_mut(&baker_id) {
+            pool.open_from = None;
+        }
+    }
+
+    /// The amount that can still be delegated to a pool. As for the
+    /// `delegated_capital_cap` reported by the node, the capital of the pool
+    /// must be at most the leverage bound times its equity capital, and at most
+    /// the capital bound fraction of the total capital.
+    fn room(&self, pool: &Pool) -> Amount {
+        let equity = u128::from(pool.equity.micro_ccd());
+        let delegated = u128::from(pool.delegated.micro_ccd());
+        let leverage_cap = (equity * u128::from(self.leverage_bound.numerator)
+            / u128::from(self.leverage_bound.denominator))
+        .saturating_sub(equity);
+        let bound = u128::from(u32::from(PartsPerHundredThousands::from(
+            self.capital_bound.bound,
+        )));
+        let cap = if bound >= 100_000 {
+            leverage_cap
+        } else {
+            // The capital of the pool is at most `bound * total`, and the other pools
+            // keep their capital, so `equity + cap <= bound / (1 - bound) * others`.
+            let others = u128::from(self.total.micro_ccd()).saturating_sub(equity + delegated);
+            let bound_cap = (others * bound / (100_000 - bound)).saturating_sub(equity);
+            leverage_cap.min(bound_cap)
         };
-        let params = SetMetadataUrlParams {
-            url:  "https://example.com".into(),
-            hash: None,
+        Amount::from_micro_ccd(cap.saturating_sub(delegated).try_into().unwrap_or(u64::MAX))
+    }
+
+    /// Whether `amount` more can be delegated to the target.
+    fn has_room(&self, target: &DelegationTarget, amount: Amount) -> bool {
+        match target {
+            DelegationTarget::Passive => true,
+            DelegationTarget::Baker { baker_id } => self
+                .pools
+                .get(baker_id)
+                .is_some_and(|pool| self.room(pool) >= amount),
+        }
+    }
+
+    /// The delegation targets that are open at the given time and have room
+    /// for delegating `amount`. Passive delegation is always a target.
+    fn targets(&self, amount: Amount, now: chrono::DateTime<chrono::Utc>) -> Vec<DelegationTarget> {
+        let open = self.pools.iter().filter(|(_, pool)| {
+            pool.open_from.is_some_and(|time| time <= now) && self.room(pool) >= amount
+        });
+        std::iter::once(DelegationTarget::Passive)
+            .chain(open.map(|(&baker_id, _)| DelegationTarget::from(baker_id)))
+            .collect()
+    }
+}
+
+/// A generator that makes transactions that add, change and remove stake of a
+/// pool of accounts, both as bakers and as delegators.
+pub struct StakingGenerator {
+    args:               CommonArgs,
+    amount:             Amount,
+    stakers:            Vec<Staker>,
+    pools:              Pools,
+    chain_parameters:   v2::ChainParameters,
+    pool_parameters:    PoolParameters,
+    /// Estimates of how long it takes for a stake change of a baker and a
+    /// delegator, respectively, to take effect.
+    baker_cooldown:     chrono::Duration,
+    delegator_cooldown: chrono::Duration,
+    /// Estimate of how long it takes before the next payday, when a change of
+    /// delegation target takes effect.
+    payday_delay:       chrono::Duration,
+    rng:                StdRng,
+    count:              usize,
+}
+
+impl StakingGenerator {
+    pub async fn instantiate(
+        mut client: v2::Client,
+        args: CommonArgs,
+        staking_args: StakingArgs,
+    ) -> anyhow::Result<Self> {
+        let chain_parameters = client
+            .get_block_chain_parameters(BlockIdentifier::LastFinal)
+            .await?
+            .response;
+        let (pool_parameters, cooldown_parameters, reward_period_length) = match &chain_parameters {
+            v2::ChainParameters::V0(_) => {
+                anyhow::bail!("Staking mode requires protocol version 4 or later.")
+            }
+            v2::ChainParameters::V1(cp) => (
+                cp.pool_parameters.clone(),
+                cp.cooldown_parameters,
+                cp.time_parameters.reward_period_length,
+            ),
+            v2::ChainParameters::V2(cp) => (
+                cp.pool_parameters.clone(),
+                cp.cooldown_parameters,
+                cp.time_parameters.reward_period_length,
+            ),
         };
-        let contract_address = info
-            .deploy_and_init_contract(
-                &mut client,
-                &args,
-                OwnedParameter::from_serial(&params)?,
-                &mut nonce.nonce,
-            )
-            .await
-            .context("Could not deploy/init the contract.")?;
+        // Changes take effect at the first payday after the cooldown has passed, so
+        // we add the length of a reward period and a margin to the cooldown.
+        let epoch_duration = client.get_consensus_info().await?.epoch_duration;
+        let reward_period = epoch_duration * Epoch::from(reward_period_length).epoch as i32;
+        let margin = chrono::Duration::minutes(1);
+        let payday_delay = reward_period + margin;
+        let baker_cooldown =
+            chrono::Duration::seconds(cooldown_parameters.pool_owner_cooldown.seconds as i64)
+                + payday_delay;
+        let delegator_cooldown =
+            chrono::Duration::seconds(cooldown_parameters.delegator_cooldown.seconds as i64)
+                + payday_delay;
 
-        // Give everyone on the network a wCCD token to increase the size of the state
-        // of the contract.
-        let accounts: Vec<_> = client
-            .get_account_list(BlockIdentifier::LastFinal)
-            .await
-            .context("Could not obtain a list of accounts.")?
+        // Get the pools and their capital.
+        let bakers: Vec<_> = client
+            .get_baker_list(BlockIdentifier::LastFinal)
+            .await?
             .response
             .try_collect()
             .await?;
+        let now = chrono::Utc::now();
+        let mut pools = Pools {
+            pools:          BTreeMap::new(),
+            total:          Amount::zero(),
+            leverage_bound: pool_parameters.leverage_bound,
+            capital_bound:  pool_parameters.capital_bound,
+            delay:          chrono::Duration::seconds(args.expiry.into()),
+            moving:         Vec::new(),
+        };
+        for baker_id in bakers {
+            let pool = client
+                .get_pool_info(BlockIdentifier::LastFinal, baker_id)
+                .await?
+                .response;
+            pools.total = pool.all_pool_total_capital;
+            pools.pools.insert(baker_id, Pool {
+                equity:    pool.baker_equity_capital,
+                delegated: pool.delegated_capital,
+                open_from: (pool.pool_info.open_status == OpenStatus::OpenForAll).then_some(now),
+            });
+        }
 
-        let client = Cis2Contract::create(client, contract_address).await?;
+        // Read the keys of the accounts.
+        let mut paths = std::fs::read_dir(&staking_args.accounts)
+            .context("Could not read the accounts directory.")?
+            .map(|entry| Ok(entry?.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        paths.sort();
+        let mut stakers = Vec::new();
+        for path in paths {
+            if path.extension() != Some(std::ffi::OsStr::new("json")) {
+                continue;
+            }
+            let Ok(keys) = WalletAccount::from_json_file(&path) else {
+                println!("Skipping {}, which is not a key file.", path.display());
+                continue;
+            };
+            let info = client
+                .get_account_info(
+                    &AccountIdentifier::Address(keys.address),
+                    BlockIdentifier::LastFinal,
+                )
+                .await?
+                .response;
+            let (stake, pending_change) = match info.account_stake {
+                None => (Stake::None, None),
+                Some(AccountStakingInfo::Baker {
+                    staked_amount,
+                    restake_earnings,
+                    pending_change,
+                    ..
+                }) => (
+                    Stake::Baker {
+                        stake:   staked_amount,
+                        restake: restake_earnings,
+                    },
+                    pending_change,
+                ),
+                Some(AccountStakingInfo::Delegated {
+                    staked_amount,
+                    restake_earnings,
+                    delegation_target,
+                    pending_change,
+                }) => (
+                    Stake::Delegator {
+                        stake:   staked_amount,
+                        restake: restake_earnings,
+                        target:  delegation_target,
+                    },
+                    pending_change,
+                ),
+            };
+            // We do not track what the stake is after an existing pending change, so we
+            // wait until it has taken effect and assume that the stake is removed, which
+            // is the worst case.
+            let pending_change =
+                pending_change.map(|change| (change.effective_time() + margin, Stake::None));
+            let nonce = client
+                .get_next_account_sequence_number(&keys.address)
+                .await?;
+            anyhow::ensure!(
+                nonce.all_final,
+                "Not all transactions of {} are finalized.",
+                keys.address
+            );
+            stakers.push(Staker {
+                keys,
+                baker_id: BakerId::from(info.account_index),
+                nonce: nonce.nonce,
+                baker: stakers.len() < staking_args.bakers,
+                stake,
+                balance: info.account_amount,
+                pending_change,
+                count: 0,
+            });
+        }
+        anyhow::ensure!(
+            !stakers.is_empty(),
+            "No key files found in the accounts directory."
+        );
+        anyhow::ensure!(
+            staking_args.bakers <= stakers.len(),
+            "There are fewer accounts than the requested number of bakers."
+        );
 
+        let rng = args.rng();
         Ok(Self {
-            client,
             args,
-            nonce: nonce.nonce,
+            amount: staking_args.amount,
+            stakers,
+            pools,
+            chain_parameters,
+            pool_parameters,
+            baker_cooldown,
+            delegator_cooldown,
+            payday_delay,
+            rng,
             count: 0,
-            accounts,
         })
     }
-}
 
-impl Generate for WccdGenerator {
-    fn generate(&mut self) -> anyhow::Result<AccountTransaction<EncodedPayload>> {
-        let mut metadata = ContractTransactionMetadata {
-            sender_address: self.args.keys.address,
-            nonce:          self.nonce,
-            expiry:         TransactionTime::seconds_after(self.args.expiry),
-            // Determined by running the transaction and inspecting the energy cost with
-            // concordium-client and then adding extra to account for variance.
-            energy:         GivenEnergy::Absolute(Energy::from(3500)),
-            amount:         Amount::zero(),
-        };
+    /// Make the payload for adding the staker as a baker with the minimum
+    /// stake, fresh keys, and the maximal commission rates.
+    fn add_baker_payload(
+        &mut self,
+        address: AccountAddress,
+        stake: Amount,
+    ) -> ConfigureBakerPayload {
+        let keys = BakerKeyPairs::generate(&mut self.rng);
+        let bounds = &self.pool_parameters.commission_bounds;
+        let mut payload = ConfigureBakerPayload::new();
+        payload
+            .set_capital(stake)
+            .set_restake_earnings(true)
+            .set_open_for_delegation(OpenStatus::OpenForAll)
+            .add_keys(&keys, address, &mut self.rng)
+            .set_metadata_url(UrlText::try_from(String::new()).expect("Empty URL is valid."))
+            .set_transaction_fee_commission(bounds.transaction.max)
+            .set_baking_reward_commission(bounds.baking.max)
+            .set_finalization_reward_commission(bounds.finalization.max);
+        payload
+    }
+}
 
-        // Before doing anything else, we transfer a single wCCD to each account on the
-        // network to increase the size of the contract state.
-        if self.count < self.accounts.len() {
-            let recv = self.accounts[self.count];
-            let params = WrapParams {
-                to:   Receiver::Account(recv),
-                data: AdditionalData::new(vec![])?,
-            };
-            metadata.amount = Amount::from_micro_ccd(1);
+/// A change to the stake of an account.
+enum StakeChange {
+    Baker(Box<ConfigureBakerPayload>),
+    Delegation(ConfigureDelegationPayload),
+}
 
-            let tx = self.client.make_update::<_, anyhow::Error>(
-                &self.args.keys,
-                &metadata,
-                "wrap",
-                &params,
-            )?;
-            self.nonce.next_mut();
-            self.count += 1;
+impl Generate for StakingGenerator {
+    fn generate(&mut self) -> anyhow::Result<BlockItem<EncodedPayload>> {
+        let now = chrono::Utc::now();
+        let index = self.count % self.stakers.len();
+        self.count += 1;
 
-            return Ok(tx);
+        let min_baker_stake = self.pool_parameters.minimum_equity_capital + self.amount;
+        self.pools.settle(now);
+        let staker = &mut self.stakers[index];
+        if let Some((time, stake)) = &staker.pending_change {
+            if *time <= now {
+                self.pools
+                    .update(staker.baker_id, &staker.stake, stake, now);
+                staker.stake = stake.clone();
+                staker.pending_change = None;
+            }
         }
+        // Pick a target among the ones with room for `amount`.
+        let next_target = |amount| {
+            let targets = self.pools.targets(amount, now);
+            targets[self.count % targets.len()].clone()
+        };
+        let in_cooldown = staker.pending_change.is_some();
+        // CCD that is kept on the account for paying transaction fees.
+        let reserve = Amount::from_ccd(10);
+        let address = staker.keys.address;
+        let balance = staker.balance;
+        staker.count += 1;
+        let step = staker.count;
 
-        // We modulate between wrapping, transferring, and unwrapping. All wCCD are
-        // minted for and transferred to our own account, which is fine for testing,
-        // since there is no special logic for this in the contract.
-        let tx = match (self.count - self.accounts.len()) % 3 {
-            // Wrap
-            0 => {
-                let params = WrapParams {
-                    to:   Receiver::Account(self.args.keys.address),
-                    data: AdditionalData::new(vec![])?,
-                };
-                metadata.amount = Amount::from_micro_ccd(1);
-
-                self.client.make_update::<_, anyhow::Error>(
-                    &self.args.keys,
-                    &metadata,
-                    "wrap",
-                    &params,
-                )?
+        // Each case gives the change, the stake after the change if it takes effect
+        // immediately, and the pending change otherwise.
+        let (change, stake, pending_change) = match staker.stake.clone() {
+            Stake::None => {
+                anyhow::ensure!(
+                    !in_cooldown,
+                    "Account without stake cannot have a pending change."
+                );
+                if staker.baker {
+                    anyhow::ensure!(
+                        min_baker_stake + reserve <= balance,
+                        "Account {address} does not have enough CCD to become a baker."
+                    );
+                    let payload = self.add_baker_payload(address, min_baker_stake);
+                    let stake = Stake::Baker {
+                        stake:   min_baker_stake,
+                        restake: true,
+                    };
+                    (StakeChange::Baker(Box::new(payload)), stake, None)
+                } else {
+                    anyhow::ensure!(
+                        self.amount + reserve <= balance,
+                        "Account {address} does not have enough CCD to delegate."
+                    );
+                    let target = next_target(self.amount);
+                    let mut payload = ConfigureDelegationPayload::new();
+                    payload
+                        .set_capital(self.amount)
+                        .set_restake_earnings(true)
+                        .set_delegation_target(target.clone());
+                    let stake = Stake::Delegator {
+                        stake: self.amount,
+                        restake: true,
+                        target,
+                    };
+                    (StakeChange::Delegation(payload), stake, None)
+                }
             }
-            // Transfer
-            1 => {
-                let transfer = Transfer {
-                    // The token id of wCCD is the empty list of bytes.
-                    token_id: TokenId::new(vec![])?,
-                    amount:   TokenAmount::from(1u32),
-                    from:     Address::Account(self.args.keys.address),
-                    to:       Receiver::Account(self.args.keys.address),
-                    data:     AdditionalData::new(vec![])?,
+            Stake::Baker { stake, restake } => {
+                let toggle_restake = || {
+                    let mut payload = ConfigureBakerPayload::new();
+                    payload.set_restake_earnings(!restake);
+                    let stake = Stake::Baker {
+                        stake,
+                        restake: !restake,
+                    };
+                    (StakeChange::Baker(Box::new(payload)), stake, None)
                 };
-
-                self.client
-                    .make_transfer_single(&self.args.keys, metadata, transfer)?
+                let remove = || {
+                    let pending = (now + self.baker_cooldown, Stake::None);
+                    let payload = ConfigureBakerPayload::new_remove_baker();
+                    (
+                        StakeChange::Baker(Box::new(payload)),
+                        Stake::Baker { stake, restake },
+                        Some(pending),
+                    )
+                };
+                if in_cooldown {
+                    // While a stake change is pending, only the restake flag is changed.
+                    toggle_restake()
+                } else if !staker.baker {
+                    // The account is removed and added as a delegator once the removal
+                    // has taken effect.
+                    remove()
+                } else {
+                    match step % 4 {
+                        0 if stake + self.amount + reserve <= balance => {
+                            let mut payload = ConfigureBakerPayload::new();
+                            payload.set_capital(stake + self.amount);
+                            let stake = Stake::Baker {
+                                stake: stake + self.amount,
+                                restake,
+                            };
+                            (StakeChange::Baker(Box::new(payload)), stake, None)
+                        }
+                        2 if stake >= min_baker_stake => {
+                            let mut payload = ConfigureBakerPayload::new();
+                            payload.set_capital(stake - self.amount);
+                            let pending = (now + self.baker_cooldown, Stake::Baker {
+                                stake: stake - self.amount,
+                                restake,
+                            });
+                            (
+                                StakeChange::Baker(Box::new(payload)),
+                                Stake::Baker { stake, restake },
+                                Some(pending),
+                            )
+                        }
+                        3 => remove(),
+                        _ => toggle_restake(),
+                    }
+                }
             }
-            // Unwrap
-            _ => {
-                let params = UnwrapParams {
-                    amount:   TokenAmount::from(1u32),
-                    owner:    Address::Account(self.args.keys.address),
-                    receiver: Address::Account(self.args.keys.address),
-                    data:     AdditionalData::new(vec![])?,
+            Stake::Delegator {
+                stake,
+                restake,
+                target,
+            } => {
+                let toggle_restake = || {
+                    let mut payload = ConfigureDelegationPayload::new();
+                    payload.set_restake_earnings(!restake);
+                    let stake = Stake::Delegator {
+                        stake,
+                        restake: !restake,
+                        target: target.clone(),
+                    };
+                    (StakeChange::Delegation(payload), stake, None)
                 };
+                let remove = || {
+                    let pending = (now + self.delegator_cooldown, Stake::None);
+                    let payload = ConfigureDelegationPayload::new_remove_delegation();
+                    let stake = Stake::Delegator {
+                        stake,
+                        restake,
+                        target: target.clone(),
+                    };
+                    (StakeChange::Delegation(payload), stake, Some(pending))
+                };
+                if in_cooldown {
+                    // While a stake change is pending, only the restake flag is changed.
+                    toggle_restake()
+                } else if staker.baker {
+                    // The account is removed and added as a baker once the removal has
+                    // taken effect.
+                    remove()
+                } else {
+                    match step % 5 {
+                        0 if stake + self.amount + reserve <= balance
+                            && self.pools.has_room(&target, self.amount) =>
+                        {
+                            let mut payload = ConfigureDelegationPayload::new();
+                            payload.set_capital(stake + self.amount);
+                            let stake = Stake::Delegator {
+                                stake: stake + self.amount,
+                                restake,
+                                target: target.clone(),
+                            };
+                            (StakeChange::Delegation(payload), stake, None)
+                        }
+                        1 => {
+                            let next_target = next_target(stake);
+                            let mut payload = ConfigureDelegationPayload::new();
+                            payload.set_delegation_target(next_target.clone());
+                            let stake = Stake::Delegator {
+                                stake,
+                                restake,
+                                target: next_target,
+                            };
+                            (StakeChange::Delegation(payload), stake, None)
+                        }
+                        3 if stake > self.amount => {
+                            let mut payload = ConfigureDelegationPayload::new();
+                            payload.set_capital(stake - self.amount);
+                            let pending = (now + self.delegator_cooldown, Stake::Delegator {
+                                stake: stake - self.amount,
+                                restake,
+                                target: target.clone(),
+                            });
+                            let stake = Stake::Delegator {
+                                stake,
+                                restake,
+                                target: target.clone(),
+                            };
+                            (StakeChange::Delegation(payload), stake, Some(pending))
+                        }
+                        4 => remove(),
+                        _ => toggle_restake(),
+                    }
+                }
+            }
+        };
 
-                self.client.make_update::<_, anyhow::Error>(
-                    &self.args.keys,
-                    &metadata,
-                    "unwrap",
-                    &params,
-                )?
+        let staker = &mut self.stakers[index];
+        if let (
+            Stake::Delegator {
+                stake: amount,
+                target: from,
+                ..
+            },
+            Stake::Delegator { target: to, .. },
+        ) = (&staker.stake, &stake)
+        {
+            if from != to {
+                self.pools
+                    .move_delegation(from, *amount, now + self.payday_delay);
             }
+        }
+        self.pools
+            .update(staker.baker_id, &staker.stake, &stake, now);
+        if let (Stake::Baker { .. }, Some((_, Stake::None))) = (&stake, &pending_change) {
+            // No delegations are made to a pool whose removal is pending.
+            self.pools.close(staker.baker_id);
+        }
+        staker.stake = stake;
+        if pending_change.is_some() {
+            staker.pending_change = pending_change;
+        }
+
+        let expiry = TransactionTime::seconds_after(self.args.expiry);
+        let tx = match change {
+            StakeChange::Baker(payload) => send::configure_baker(
+                &staker.keys,
+                staker.keys.address,
+                staker.nonce,
+                expiry,
+                *payload,
+            ),
+            StakeChange::Delegation(payload) => send::configure_delegation(
+                &staker.keys,
+                staker.keys.address,
+                staker.nonce,
+                expiry,
+                payload,
+            ),
         };
-        self.nonce.next_mut();
-        self.count += 1;
+        staker.nonce.next_mut();
+        // The balance is only used to check that the account can afford a change, so
+        // the maximal cost of the transaction is deducted.
+        let cost = self.chain_parameters.ccd_cost(tx.header.energy_amount);
+        staker.balance = staker
+            .balance
+            .checked_sub(cost)
+            .unwrap_or_else(Amount::zero);
 
-        Ok(tx)
+        Ok(BlockItem::AccountTransaction(tx))
     }
 }
 
-/// A generator that makes transactions that register dummy Web3 ID credentials.
-pub struct RegisterCredentialsGenerator {
-    client: Cis4Contract,
-    args:   CommonArgs,
-    nonce:  Nonce,
-    rng:    StdRng,
+/// A generator that creates accounts by deploying credentials derived from an
+/// identity object and the seed phrase it was created with.
+pub struct CreateAccountsGenerator {
+    wallet:         ConcordiumHdWallet,
+    id_object:      IdentityObjectV1<IpPairing, ArCurve, AttributeKind>,
+    ip_info:        IpInfo<IpPairing>,
+    ars:            BTreeMap<ArIdentity, ArInfo<ArCurve>>,
+    global:         GlobalContext<ArCurve>,
+    identity_index: u32,
+    expiry:         u32,
+    next_index:     u16,
 }
 
-#[derive(concordium_std::Serial)]
-struct CredentialType {
-    #[concordium(size_length = 1)]
-    credential_type: String,
-}
+impl CreateAccountsGenerator {
+    pub async fn instantiate(
+        mut client: v2::Client,
+        create_accounts_args: CreateAccountsArgs,
+        expiry: u32,
+    ) -> anyhow::Result<Self> {
+        let id_object: Versioned<serde_json::Value> = serde_json::from_str(
+            &std::fs::read_to_string(&create_accounts_args.identity)
+                .context("Could not read the identity object.")?,
+        )
+        .context("Could not parse the identity object.")?;
+        anyhow::ensure!(
+            id_object.version == VERSION_0,
+            "Unsupported version of identity object: {}",
+            id_object.version
+        );
+        let id_object: IdentityObjectV1<IpPairing, ArCurve, AttributeKind> =
+            serde_json::from_value(id_object.value)
+                .context("Could not parse the identity object.")?;
 
-#[derive(concordium_std::Serial)]
-struct RegisterCredentialsInitParams {
-    issuer_metadata: MetadataUrl,
-    credential_type: CredentialType,
-    schema:          SchemaRef,
-    issuer_account:  Option<AccountAddress>,
-    issuer_key:      CredentialHolderId,
-    #[concordium(size_length = 1)]
-    revocation_keys: Vec<CredentialHolderId>,
-}
+        let seed_phrase = std::fs::read_to_string(&create_accounts_args.seed_phrase)
+            .context("Could not read the seed phrase.")?;
+        let seed_words: Vec<_> = seed_phrase.split_whitespace().collect();
+        let wallet = ConcordiumHdWallet {
+            seed: words_to_seed(&seed_words.join(" ")),
+            net:  if create_accounts_args.mainnet {
+                Net::Mainnet
+            } else {
+                Net::Testnet
+            },
+        };
 
-#[derive(concordium_std::Serial)]
-struct RegisterCredentialParams {
-    credential_info: CredentialInfo,
-    #[concordium(size_length = 2)]
-    auxiliary_data:  Vec<u8>,
-}
+        let ip_index = create_accounts_args.ip_index;
+        let identity_index = create_accounts_args.identity_index;
 
-impl RegisterCredentialsGenerator {
-    pub async fn instantiate(mut client: v2::Client, args: CommonArgs) -> anyhow::Result<Self> {
-        // Get the initial nonce.
-        let mut nonce = client
-            .get_next_account_sequence_number(&args.keys.address)
+        let ip_info = client
+            .get_identity_providers(BlockIdentifier::LastFinal)
+            .await?
+            .response
+            .try_filter(|ip_info| futures::future::ready(ip_info.ip_identity.0 == ip_index))
+            .try_next()
+            .await?
+            .with_context(|| format!("Identity provider {ip_index} does not exist."))?;
+        let ars = client
+            .get_anonymity_revokers(BlockIdentifier::LastFinal)
+            .await?
+            .response
+            .map_ok(|ar_info| (ar_info.ar_identity, ar_info))
+            .try_collect()
             .await?;
+        let global = client
+            .get_cryptographic_parameters(BlockIdentifier::LastFinal)
+            .await?
+            .response;
 
-        let mut rng = StdRng::from_entropy();
-        let issuer_public_key = KeyPair::generate(&mut rng).public();
-
-        let info = ContractDeploymentInfo {
-            module:      REGISTER_CREDENTIALS_MODULE,
-            name:        "init_credential_registry",
-            // Determined by running the transaction and inspecting the energy cost with
-            // concordium-client.
-            init_energy: Energy::from(2970),
-        };
-        // The parameters don't matter, since we can still issue, so they are all dummy
-        // values.
-        let params = RegisterCredentialsInitParams {
-            issuer_metadata: MetadataUrl::new("https://example.com".into(), None)?,
-            credential_type: CredentialType {
-                credential_type: "TestCredential".into(),
-            },
-            schema:          SchemaRef {
-                schema_ref: MetadataUrl::new("https://example.com".into(), None)?,
+        // Skip the credential indices that were already used to create accounts, for
+        // example in a previous run.
+        let prf_key = wallet
+            .get_prf_key(ip_index, identity_index)
+            .context("Failed to get PRF key.")?;
+        let mut next_index = 0u16;
+        while next_index <= id_object.alist.max_accounts.into() {
+            let cred_id_exp = prf_key
+                .prf_exponent(next_index as u8)
+                .context("Failed to compute PRF exponent.")?;
+            let reg_id = CredentialRegistrationID::from_exponent(&global, cred_id_exp);
+            match client
+                .get_account_info(
+                    &AccountIdentifier::CredId(reg_id),
+                    BlockIdentifier::LastFinal,
+                )
+                .await
+            {
+                Ok(_) => next_index += 1,
+                Err(e) if e.is_not_found() => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        println!("Creating accounts starting from credential index {next_index}.");
+
+        Ok(Self {
+            wallet,
+            id_object,
+            ip_info,
+            ars,
+            global,
+            identity_index,
+            expiry,
+            next_index,
+        })
+    }
+
+    /// Derive the secrets of the identity. They are derived again for each
+    /// credential rather than stored in the generator, since they cannot be
+    /// sent between threads.
+    fn id_use_data(&self) -> anyhow::Result<IdObjectUseData<IpPairing, ArCurve>> {
+        let ip_index = self.ip_info.ip_identity.0;
+        let randomness = self
+            .wallet
+            .get_blinding_randomness(ip_index, self.identity_index)
+            .context("Failed to get blinding randomness.")?;
+        let prf_key = self
+            .wallet
+            .get_prf_key(ip_index, self.identity_index)
+            .context("Failed to get PRF key.")?;
+        let id_cred_sec = self
+            .wallet
+            .get_id_cred_sec(ip_index, self.identity_index)
+            .context("Failed to get idCredSec.")?;
+        let aci = AccCredentialInfo {
+            cred_holder_info: CredentialHolderInfo {
+                id_cred: IdCredentials {
+                    id_cred_sec: PedersenValue::new(id_cred_sec),
+                },
             },
-            issuer_account:  None,
-            issuer_key:      CredentialHolderId::new(issuer_public_key),
-            revocation_keys: vec![],
+            prf_key,
+        };
+        Ok(IdObjectUseData { aci, randomness })
+    }
+
+    /// Derive the keys of the account with the given credential index.
+    fn account_keys(&self, acc_index: u8) -> anyhow::Result<CredentialData> {
+        let secret = self
+            .wallet
+            .get_account_signing_key(
+                self.ip_info.ip_identity.0,
+                self.identity_index,
+                acc_index.into(),
+            )
+            .context("Failed to get account signing key.")?;
+        let public = (&secret).into();
+        let mut keys = BTreeMap::new();
+        keys.insert(KeyIndex(0), KeyPair { secret, public });
+
+        Ok(CredentialData {
+            keys,
+            threshold: SignatureThreshold::ONE,
+        })
+    }
+
+    /// Create the credential deployment for the next unused credential index,
+    /// and return it together with the keys of the account that it creates.
+    fn next_account(&mut self) -> anyhow::Result<(WalletAccount, BlockItem<EncodedPayload>)> {
+        anyhow::ensure!(
+            self.next_index <= self.id_object.alist.max_accounts.into(),
+            "All {} accounts of the identity have been created.",
+            self.id_object.alist.max_accounts
+        );
+        let acc_index = self.next_index as u8;
+
+        let context = IpContext::new(&self.ip_info, &self.ars, &self.global);
+        let policy: Policy<ArCurve, AttributeKind> = Policy {
+            valid_to:   self.id_object.get_attribute_list().valid_to,
+            created_at: self.id_object.get_attribute_list().created_at,
+            policy_vec: BTreeMap::new(),
+            _phantom:   Default::default(),
+        };
+        let id_use_data = self.id_use_data()?;
+        let acc_data = self.account_keys(acc_index)?;
+        let credential_context = CredentialContext {
+            wallet:                  self.wallet.clone(),
+            identity_provider_index: self.ip_info.ip_identity,
+            identity_index:          self.identity_index,
+            credential_index:        acc_index,
+        };
+
+        let message_expiry = TransactionTime::seconds_after(self.expiry);
+        let (cdi, _) = create_credential(
+            context,
+            &self.id_object,
+            &id_use_data,
+            acc_index,
+            policy,
+            &acc_data,
+            &credential_context,
+            &Either::Left(message_expiry),
+        )
+        .context("Could not generate the credential.")?;
+        self.next_index += 1;
+
+        let account = WalletAccount {
+            address: account_address_from_registration_id(&cdi.values.cred_id),
+            keys:    AccountKeys::from(acc_data),
         };
+        let item = BlockItem::CredentialDeployment(Box::new(AccountCredentialMessage {
+            message_expiry,
+            credential: AccountCredential::Normal { cdi },
+        }));
+        Ok((account, item))
+    }
+}
+
+impl Generate for CreateAccountsGenerator {
+    fn generate(&mut self) -> anyhow::Result<BlockItem<EncodedPayload>> {
+        let (_, item) = self.next_account()?;
+        Ok(item)
+    }
+}
+
+/// Submit the transactions in a batch, and wait until all of them are
+/// finalized.
+pub(crate) async fn submit_batch(
+    client: &mut v2::Client,
+    batch: &[BlockItem<EncodedPayload>],
+) -> anyhow::Result<()> {
+    let mut hashes = Vec::with_capacity(batch.len());
+    for item in batch {
+        hashes.push(client.send_block_item(item).await?);
+    }
+    for transaction_hash in hashes {
+        let (_, summary) = client.wait_until_finalized(&transaction_hash).await?;
+        anyhow::ensure!(
+            summary.is_success(),
+            "Transaction failed (hash = {transaction_hash})."
+        );
+    }
+    Ok(())
+}
+
+/// Create fresh accounts from an identity, write their key files and a list of
+/// their addresses, and optionally fund them from the sender. The credentials
+/// and the transfers are submitted in batches, waiting for each batch to be
+/// finalized before submitting the next.
+pub async fn bootstrap(
+    mut client: v2::Client,
+    bootstrap_args: BootstrapArgs,
+    sender: Option<WalletAccount>,
+    expiry: u32,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        bootstrap_args.batch_size > 0,
+        "The batch size must be positive."
+    );
+    let sender = match bootstrap_args.fund {
+        Some(amount) => Some((
+            sender.context("The `--sender` option is required for funding accounts.")?,
+            amount,
+        )),
+        None => None,
+    };
+    let mut generator =
+        CreateAccountsGenerator::instantiate(client.clone(), bootstrap_args.identity, expiry)
+            .await?;
+    let available =
+        usize::from(generator.id_object.alist.max_accounts) + 1 - usize::from(generator.next_index);
+    anyhow::ensure!(
+        bootstrap_args.count <= available,
+        "The identity can only be used to create {available} more accounts."
+    );
+    std::fs::create_dir_all(&bootstrap_args.out)
+        .context("Could not create the output directory.")?;
+
+    let mut addresses = Vec::with_capacity(bootstrap_args.count);
+    let mut created = 0;
+    while created < bootstrap_args.count {
+        let batch_size = bootstrap_args
+            .batch_size
+            .min(bootstrap_args.count - created);
+        let mut batch = Vec::with_capacity(batch_size);
+        for _ in 0..batch_size {
+            let acc_index = generator.next_index;
+            let (account, item) = generator.next_account()?;
+            // Write the keys before submitting the credential, so that they are not lost
+            // if the generator is stopped.
+            let mut path = bootstrap_args.out.clone();
+            path.push(format!("account-{acc_index}.json"));
+            let json = serde_json::json!({
+                "address": account.address,
+                "accountKeys": account.keys,
+            });
+            std::fs::write(&path, serde_json::to_string_pretty(&json)?)
+                .with_context(|| format!("Could not write {}.", path.display()))?;
+            addresses.push(account.address);
+            batch.push(item);
+        }
+        submit_batch(&mut client, &batch).await?;
+        created += batch_size;
+        println!("Created {created} accounts.");
+    }
+
+    let mut path = bootstrap_args.out.clone();
+    path.push("receivers.json");
+    std::fs::write(&path, serde_json::to_string_pretty(&addresses)?)
+        .context("Could not write the list of receivers.")?;
+    println!("Wrote the addresses of the accounts to {}.", path.display());
+
+    if let Some((keys, amount)) = sender {
+        let mut nonce = client
+            .get_next_account_sequence_number(&keys.address)
+            .await?
+            .nonce;
+        for (i, chunk) in addresses.chunks(bootstrap_args.batch_size).enumerate() {
+            let batch: Vec<_> = chunk
+                .iter()
+                .map(|receiver| {
+                    let tx = send::transfer(
+                        &keys,
+                        keys.address,
+                        nonce,
+                        TransactionTime::seconds_after(expiry),
+                        *receiver,
+                        amount,
+                    );
+                    nonce.next_mut();
+                    BlockItem::AccountTransaction(tx)
+                })
+                .collect();
+            submit_batch(&mut client, &batch).await?;
+            println!(
+                "Funded {} accounts.",
+                i * bootstrap_args.batch_size + chunk.len()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// A generator that makes transactions that mints CIS-2 NFT tokens for the
+/// sender.
+pub struct MintCis2Generator {
+    estimator: EnergyEstimator,
+    args:      CommonArgs,
+    nonce:     Nonce,
+    next_id:   u32,
+}
+
+#[derive(concordium_std::Serial)]
+struct MintCis2NftParams {
+    owner:  concordium_std::Address,
+    #[concordium(size_length = 1)]
+    tokens: collections::BTreeSet<TokenId>,
+}
+
+impl MintCis2Generator {
+    pub async fn instantiate(
+        mut client: v2::Client,
+        args: CommonArgs,
+        instance_args: InstanceArgs,
+    ) -> anyhow::Result<Self> {
+        // Get the initial nonce.
+        let mut nonce = client
+            .get_next_account_sequence_number(&args.keys.address)
+            .await?;
 
+        // Deploy and initialize the contract.
+        let info = ContractDeploymentInfo {
+            module:      MINT_CIS2_MODULE,
+            name:        "init_cis2_nft",
+            init_energy: None,
+            owner_only:  true,
+        };
         let contract_address = info
-            .deploy_and_init_contract(
+            .existing_or_deploy(
                 &mut client,
                 &args,
-                OwnedParameter::from_serial(&params)?,
+                instance_args.contract,
+                OwnedParameter::empty(),
                 &mut nonce.nonce,
             )
-            .await
-            .context("Could not deploy/init the contract.")?;
+            .await?;
 
-        let client = Cis4Contract::create(client, contract_address).await?;
+        let client = Cis2Contract::create(client, contract_address).await?;
+        let next_id = next_unused_token_id(&client).await?;
+        let mut estimator = EnergyEstimator::new(client, &args);
+        let payload = estimator.payload(
+            "mint",
+            Amount::zero(),
+            mint_nft_param(args.keys.address, next_id..next_id + 1)?,
+        )?;
+        estimator.estimate(&payload).await?;
         Ok(Self {
-            client,
+            estimator,
             args,
             nonce: nonce.nonce,
-            rng,
+            next_id,
         })
     }
 }
 
-impl Generate for RegisterCredentialsGenerator {
-    fn generate(&mut self) -> anyhow::Result<AccountTransaction<EncodedPayload>> {
-        // Create 32 byte holder id.
-        let public_key = KeyPair::generate(&mut self.rng).public();
-
-        let cred_info = CredentialInfo {
-            holder_id:        CredentialHolderId::new(public_key),
-            holder_revocable: false,
-            valid_from:       Timestamp::from_timestamp_millis(0),
-            valid_until:      None,
-            metadata_url:     MetadataUrl::new("https://example.com".into(), None)?,
-        };
-
-        let metadata = Cis4TransactionMetadata {
-            sender_address: self.args.keys.address,
-            nonce:          self.nonce,
-            expiry:         TransactionTime::seconds_after(self.args.expiry),
-            // Determined by running the transaction and inspecting the energy cost with
-            // concordium-client and then adding extra to account for variance.
-            energy:         GivenEnergy::Absolute(Energy::from(5000)),
-            amount:         Amount::zero(),
-        };
-        let tx =
-            self.client
-                .make_register_credential(&self.args.keys, &metadata, &cred_info, &[])?;
+/// Find the first token id that is not minted in a `cis2_nft` instance.
+/// [`MintCis2Generator`] mints consecutive token ids starting from 0, so
+/// this is found by an exponential search followed by a binary search. Querying
+/// the metadata of a token that is not minted is rejected by the contract.
+pub(crate) async fn next_unused_token_id(client: &Cis2Contract) -> anyhow::Result<u32> {
+    let is_minted = |id: u64| {
+        let mut client = client.clone();
+        async move {
+            let token_id = TokenId::new_u32(u32::try_from(id)?);
+            match client
+                .token_metadata_single(BlockIdentifier::LastFinal, token_id)
+                .await
+            {
+                Ok(_) => Ok(true),
+                Err(Cis2QueryError::NodeRejected(_)) => Ok(false),
+                Err(e) => Err(anyhow::Error::from(e)),
+            }
+        }
+    };
+    if !is_minted(0).await? {
+        return Ok(0);
+    }
+    // Invariant: `low` is minted and `high` is not.
+    let mut low = 0u64;
+    let mut high = 1u64;
+    while is_minted(high).await? {
+        low = high;
+        high *= 2;
+    }
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        if is_minted(mid).await? {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(u32::try_from(high)?)
+}
+
+impl Generate for MintCis2Generator {
+    fn generate(&mut self) -> anyhow::Result<BlockItem<EncodedPayload>> {
+        // We mint a single token for ourselves.
+        let params = MintCis2NftParams {
+            owner:  Address::Account(self.args.keys.address),
+            tokens: [TokenId::new_u32(self.next_id)].into(),
+        };
+
+        let tx = self.estimator.make_update(
+            &self.args,
+            self.nonce,
+            "mint",
+            Amount::zero(),
+            OwnedParameter::from_serial(&params)?,
+        )?;
+        self.nonce.next_mut();
+        self.next_id += 1;
+
+        Ok(BlockItem::AccountTransaction(tx))
+    }
+}
+
+/// A generator that makes transactions that transfer CIS-2 tokens to a list of
+/// accounts.
+pub struct TransferCis2Generator {
+    estimator:  EnergyEstimator,
+    /// Arguments with the keys of the account that signs the transactions,
+    /// which is the operator in the `operator-transfer` variant.
+    args:       CommonArgs,
+    /// The owner of the transferred tokens.
+    owner:      AccountAddress,
+    accounts:   Vec<AccountAddress>,
+    nonce:      Nonce,
+    count:      usize,
+    variant:    Cis2Variant,
+    batch_size: usize,
+}
+
+#[derive(concordium_std::Serial)]
+struct MintCis2TokenParam {
+    token_amount: TokenAmount,
+    metadata_url: MetadataUrl,
+}
+
+#[derive(concordium_std::Serial)]
+struct MintCis2TokenParams {
+    owner:  Address,
+    tokens: BTreeMap<TokenId, MintCis2TokenParam>,
+}
+
+impl TransferCis2Generator {
+    pub async fn instantiate(
+        mut client: v2::Client,
+        args: CommonArgs,
+        transfer_cis2_args: TransferCis2Args,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            (1..=usize::from(u16::MAX)).contains(&transfer_cis2_args.batch_size),
+            "The batch size must be between 1 and {}.",
+            u16::MAX
+        );
+        let operator = match transfer_cis2_args.variant {
+            Cis2Variant::OperatorTransfer => Some(
+                WalletAccount::from_json_file(
+                    transfer_cis2_args
+                        .operator
+                        .context("The `operator-transfer` variant requires `--operator`.")?,
+                )
+                .context("Could not parse the operator keys file.")?,
+            ),
+            _ => None,
+        };
+
+        // Get the list of receivers.
+        let accounts = get_receivers(&mut client, transfer_cis2_args.receivers).await?;
+
+        // Get the initial nonce.
+        let mut nonce = client
+            .get_next_account_sequence_number(&args.keys.address)
+            .await?;
+
+        // Deploy and initialize the contract.
+        let info = ContractDeploymentInfo {
+            module:      TRANSFER_CIS2_MODULE,
+            name:        "init_cis2_multi",
+            init_energy: None,
+            owner_only:  true,
+        };
+        let contract_address = info
+            .existing_or_deploy(
+                &mut client,
+                &args,
+                transfer_cis2_args.instance.contract,
+                OwnedParameter::empty(),
+                &mut nonce.nonce,
+            )
+            .await?;
+
+        let mut contract = Cis2Contract::create(client.clone(), contract_address).await?;
+
+        // An existing instance may already have been set up by a previous run, in
+        // which case the sender owns tokens. The balance query is rejected if the
+        // token has not been minted.
+        let minted = match contract
+            .balance_of_single(
+                BlockIdentifier::LastFinal,
+                TokenId::new_u8(0),
+                Address::Account(args.keys.address),
+            )
+            .await
+        {
+            Ok(balance) => balance != TokenAmount::from(0u32),
+            Err(Cis2QueryError::NodeRejected(_)) => false,
+            Err(e) => return Err(e.into()),
+        };
+        let is_operator = match &operator {
+            Some(operator) => {
+                contract
+                    .operator_of_single(
+                        BlockIdentifier::LastFinal,
+                        Address::Account(args.keys.address),
+                        Address::Account(operator.address),
+                    )
+                    .await?
+            }
+            None => true,
+        };
+        let mut estimator = EnergyEstimator::new(contract, &args);
+
+        // The rest of the function mints u64::MAX tokens for the sender.
+        if minted {
+            println!("The sender already owns tokens, skipping minting.");
+        } else {
+            println!("Minting u64::MAX tokens for ourselves...");
+
+            let param = MintCis2TokenParam {
+                token_amount: TokenAmount::from(u64::MAX),
+                metadata_url: MetadataUrl::new("https://example.com".into(), None)?,
+            };
+            let params = MintCis2TokenParams {
+                owner:  Address::Account(args.keys.address),
+                tokens: [(TokenId::new_u8(0), param)].into(),
+            };
+
+            let payload = estimator.payload(
+                "mint",
+                Amount::zero(),
+                OwnedParameter::from_serial(&params)?,
+            )?;
+            let energy = estimator.estimate(&payload).await?;
+            let tx = send::update_contract(
+                &args.keys,
+                args.keys.address,
+                nonce.nonce,
+                TransactionTime::seconds_after(args.expiry),
+                payload,
+                energy,
+            );
+            let transaction_hash = client
+                .send_block_item(&BlockItem::AccountTransaction(tx))
+                .await?;
+            nonce.nonce.next_mut();
+
+            let (_, summary) = client.wait_until_finalized(&transaction_hash).await?;
+            anyhow::ensure!(
+                summary.is_success(),
+                "Mint transaction failed (hash = {transaction_hash})."
+            );
+            println!(
+                "Minted u64::MAX tokens (hash = {transaction_hash}, energy = {}).",
+                summary.energy_cost,
+            );
+        }
+
+        let owner = args.keys.address;
+        let (args, nonce) = match operator {
+            Some(operator) => {
+                if !is_operator {
+                    println!("Adding {} as operator of the sender...", operator.address);
+                    let params = UpdateOperatorParams::new(vec![UpdateOperator {
+                        update:   OperatorUpdate::Add,
+                        operator: Address::Account(operator.address),
+                    }])?;
+                    let payload = estimator.payload(
+                        "updateOperator",
+                        Amount::zero(),
+                        OwnedParameter::from_serial(&params)?,
+                    )?;
+                    let energy = estimator.estimate(&payload).await?;
+                    let tx = send::update_contract(
+                        &args.keys,
+                        args.keys.address,
+                        nonce.nonce,
+                        TransactionTime::seconds_after(args.expiry),
+                        payload,
+                        energy,
+                    );
+                    let transaction_hash = client
+                        .send_block_item(&BlockItem::AccountTransaction(tx))
+                        .await?;
+                    let (_, summary) = client.wait_until_finalized(&transaction_hash).await?;
+                    anyhow::ensure!(
+                        summary.is_success(),
+                        "Operator update failed (hash = {transaction_hash})."
+                    );
+                }
+                // From now on the operator signs the transactions.
+                let nonce = client
+                    .get_next_account_sequence_number(&operator.address)
+                    .await?;
+                estimator.sender = operator.address;
+                (
+                    CommonArgs {
+                        keys: operator,
+                        ..args
+                    },
+                    nonce,
+                )
+            }
+            None => (args, nonce),
+        };
+
+        let mut generator = Self {
+            estimator,
+            args,
+            owner,
+            accounts,
+            nonce: nonce.nonce,
+            count: 0,
+            variant: transfer_cis2_args.variant,
+            batch_size: transfer_cis2_args.batch_size,
+        };
+        let (entrypoint, message) = generator.next_update()?;
+        let payload = generator
+            .estimator
+            .payload(entrypoint, Amount::zero(), message)?;
+        generator.estimator.estimate(&payload).await?;
+        Ok(generator)
+    }
+
+    /// The receivers of the next batch.
+    fn next_batch(&self, count: usize) -> impl Iterator<Item = AccountAddress> + '_ {
+        (0..self.batch_size)
+            .map(move |i| self.accounts[(count * self.batch_size + i) % self.accounts.len()])
+    }
+
+    /// The entrypoint and parameter of the next update.
+    fn next_update(&self) -> anyhow::Result<(&'static str, OwnedParameter)> {
+        Ok(match self.variant {
+            Cis2Variant::Transfer | Cis2Variant::OperatorTransfer => {
+                let transfers = self
+                    .next_batch(self.count)
+                    .map(|receiver| {
+                        Ok(Transfer {
+                            token_id: TokenId::new_u8(0),
+                            amount:   TokenAmount::from(1u32),
+                            from:     Address::Account(self.owner),
+                            to:       Receiver::Account(receiver),
+                            data:     AdditionalData::new(vec![])?,
+                        })
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                let params = TransferParams::new(transfers)?;
+                ("transfer", OwnedParameter::from_serial(&params)?)
+            }
+            Cis2Variant::UpdateOperator => {
+                // Alternately add and remove the same batch of operators, so that the
+                // state of the contract does not grow.
+                let update = match self.count % 2 {
+                    0 => OperatorUpdate::Add,
+                    _ => OperatorUpdate::Remove,
+                };
+                let updates = self
+                    .next_batch(self.count / 2)
+                    .map(|operator| UpdateOperator {
+                        update,
+                        operator: Address::Account(operator),
+                    })
+                    .collect();
+                let params = UpdateOperatorParams::new(updates)?;
+                ("updateOperator", OwnedParameter::from_serial(&params)?)
+            }
+        })
+    }
+}
+
+impl Generate for TransferCis2Generator {
+    fn generate(&mut self) -> anyhow::Result<BlockItem<EncodedPayload>> {
+        let (entrypoint, message) = self.next_update()?;
+        let tx = self.estimator.make_update(
+            &self.args,
+            self.nonce,
+            entrypoint,
+            Amount::zero(),
+            message,
+        )?;
         self.nonce.next_mut();
+        self.count += 1;
+
+        Ok(BlockItem::AccountTransaction(tx))
+    }
+}
+
+/// A generator that makes transactions that wrap, unwrap, and transfer WCCDs.
+pub struct WccdGenerator {
+    estimator: EnergyEstimator,
+    args:      CommonArgs,
+    nonce:     Nonce,
+    count:     usize,
+    accounts:  Vec<AccountAddress>,
+}
+
+#[derive(concordium_std::Serial)]
+struct SetMetadataUrlParams {
+    url:  String,
+    hash: Option<[u8; 32]>,
+}
 
-        Ok(tx)
+#[derive(concordium_std::Serial)]
+struct WrapParams {
+    to:   Receiver,
+    data: AdditionalData,
+}
+
+#[derive(concordium_std::Serial)]
+struct UnwrapParams {
+    amount:   TokenAmount,
+    owner:    Address,
+    receiver: Address,
+    data:     AdditionalData,
+}
+
+impl WccdGenerator {
+    pub async fn instantiate(
+        mut client: v2::Client,
+        args: CommonArgs,
+        instance_args: InstanceArgs,
+    ) -> anyhow::Result<Self> {
+        // Get the initial nonce.
+        let mut nonce = client
+            .get_next_account_sequence_number(&args.keys.address)
+            .await?;
+
+        let info = ContractDeploymentInfo {
+            module:      WCCD_MODULE,
+            name:        "init_cis2_wCCD",
+            init_energy: None,
+            owner_only:  false,
+        };
+        let params = SetMetadataUrlParams {
+            url:  "https://example.com".into(),
+            hash: None,
+        };
+        let contract_address = info
+            .existing_or_deploy(
+                &mut client,
+                &args,
+                instance_args.contract,
+                OwnedParameter::from_serial(&params)?,
+                &mut nonce.nonce,
+            )
+            .await?;
+
+        // Give everyone on the network a wCCD token to increase the size of the state
+        // of the contract.
+        let accounts: Vec<_> = client
+            .get_account_list(BlockIdentifier::LastFinal)
+            .await
+            .context("Could not obtain a list of accounts.")?
+            .response
+            .try_collect()
+            .await?;
+
+        let mut client = Cis2Contract::create(client, contract_address).await?;
+
+        // An existing instance may already have given a token to some of the accounts
+        // in a previous run, so only the remaining accounts are given one.
+        let accounts = if instance_args.contract.is_some() {
+            // The token id of wCCD is the empty list of bytes.
+            let token_id = TokenId::new(vec![])?;
+            let mut remaining = Vec::new();
+            for chunk in accounts.chunks(1000) {
+                let queries = chunk
+                    .iter()
+                    .map(|&account| BalanceOfQuery {
+                        token_id: token_id.clone(),
+                        address:  Address::Account(account),
+                    })
+                    .collect();
+                let balances: Vec<TokenAmount> = client
+                    .balance_of(BlockIdentifier::LastFinal, queries)
+                    .await?
+                    .into();
+                remaining.extend(
+                    chunk
+                        .iter()
+                        .zip(balances)
+                        .filter(|(_, balance)| *balance == TokenAmount::from(0u32))
+                        .map(|(&account, _)| account),
+                );
+            }
+            println!(
+                "{} of {} accounts do not own wCCD yet.",
+                remaining.len(),
+                accounts.len()
+            );
+            remaining
+        } else {
+            accounts
+        };
+        let mut estimator = EnergyEstimator::new(client, &args);
+        let params = WrapParams {
+            to:   Receiver::Account(args.keys.address),
+            data: AdditionalData::new(vec![])?,
+        };
+        let payload = estimator.payload(
+            "wrap",
+            Amount::from_micro_ccd(1),
+            OwnedParameter::from_serial(&params)?,
+        )?;
+        estimator.estimate(&payload).await?;
+
+        // The sender does not own any wCCD until its first wrap is finalized, so
+        // invoking transfer and unwrap with an amount of 1 would fail at that point.
+        // Estimate them with an amount of 0 instead, which is good enough until the
+        // estimates are refreshed.
+        let transfer = Transfer {
+            token_id: TokenId::new(vec![])?,
+            amount:   TokenAmount::from(0u32),
+            from:     Address::Account(args.keys.address),
+            to:       Receiver::Account(args.keys.address),
+            data:     AdditionalData::new(vec![])?,
+        };
+        let payload = estimator.payload(
+            "transfer",
+            Amount::zero(),
+            OwnedParameter::from_serial(&TransferParams::new(vec![transfer])?)?,
+        )?;
+        estimator.estimate(&payload).await?;
+        let params = UnwrapParams {
+            amount:   TokenAmount::from(0u32),
+            owner:    Address::Account(args.keys.address),
+            receiver: Address::Account(args.keys.address),
+            data:     AdditionalData::new(vec![])?,
+        };
+        let payload = estimator.payload(
+            "unwrap",
+            Amount::zero(),
+            OwnedParameter::from_serial(&params)?,
+        )?;
+        estimator.estimate(&payload).await?;
+
+        Ok(Self {
+            estimator,
+            args,
+            nonce: nonce.nonce,
+            count: 0,
+            accounts,
+        })
+    }
+}
+
+impl Generate for WccdGenerator {
+    fn generate(&mut self) -> anyhow::Result<BlockItem<EncodedPayload>> {
+        // Before doing anything else, we transfer a single wCCD to each account on the
+        // network to increase the size of the contract state.
+        if self.count < self.accounts.len() {
+            let recv = self.accounts[self.count];
+            let params = WrapParams {
+                to:   Receiver::Account(recv),
+                data: AdditionalData::new(vec![])?,
+            };
+            let tx = self.estimator.make_update(
+                &self.args,
+                self.nonce,
+                "wrap",
+                Amount::from_micro_ccd(1),
+                OwnedParameter::from_serial(&params)?,
+            )?;
+            self.nonce.next_mut();
+            self.count += 1;
+
+            return Ok(BlockItem::AccountTransaction(tx));
+        }
+
+        // We modulate between wrapping, transferring, and unwrapping. All wCCD are
+        // minted for and transferred to our own account, which is fine for testing,
+        // since there is no special logic for this in the contract.
+        let tx = match (self.count - self.accounts.len()) % 3 {
+            // Wrap
+            0 => {
+                let params = WrapParams {
+                    to:   Receiver::Account(self.args.keys.address),
+                    data: AdditionalData::new(vec![])?,
+                };
+                self.estimator.make_update(
+                    &self.args,
+                    self.nonce,
+                    "wrap",
+                    Amount::from_micro_ccd(1),
+                    OwnedParameter::from_serial(&params)?,
+                )?
+            }
+            // Transfer
+            1 => {
+                let transfer = Transfer {
+                    // The token id of wCCD is the empty list of bytes.
+                    token_id: TokenId::new(vec![])?,
+                    amount:   TokenAmount::from(1u32),
+                    from:     Address::Account(self.args.keys.address),
+                    to:       Receiver::Account(self.args.keys.address),
+                    data:     AdditionalData::new(vec![])?,
+                };
+
+                self.estimator.make_update(
+                    &self.args,
+                    self.nonce,
+                    "transfer",
+                    Amount::zero(),
+                    OwnedParameter::from_serial(&TransferParams::new(vec![transfer])?)?,
+                )?
+            }
+            // Unwrap
+            _ => {
+                let params = UnwrapParams {
+                    amount:   TokenAmount::from(1u32),
+                    owner:    Address::Account(self.args.keys.address),
+                    receiver: Address::Account(self.args.keys.address),
+                    data:     AdditionalData::new(vec![])?,
+                };
+
+                self.estimator.make_update(
+                    &self.args,
+                    self.nonce,
+                    "unwrap",
+                    Amount::zero(),
+                    OwnedParameter::from_serial(&params)?,
+                )?
+            }
+        };
+        self.nonce.next_mut();
+        self.count += 1;
+
+        Ok(BlockItem::AccountTransaction(tx))
+    }
+}
+
+/// A generator that makes transactions that register dummy Web3 ID credentials.
+pub struct RegisterCredentialsGenerator {
+    estimator: EnergyEstimator,
+    args:      CommonArgs,
+    nonce:     Nonce,
+    rng:       StdRng,
+    /// Set in the `--lifecycle` variant.
+    lifecycle: Option<CredentialLifecycle>,
+}
+
+/// State of the `--lifecycle` variant, where each credential is registered,
+/// its metadata is updated, and it is then revoked.
+struct CredentialLifecycle {
+    /// Payloads of updates of a credential that was registered and finalized
+    /// when the generator started. They are used to estimate the energy of the
+    /// updates of credentials that are not finalized yet.
+    probe_update_metadata: UpdateContractPayload,
+    probe_revoke_issuer:   UpdateContractPayload,
+    probe_revoke_holder:   UpdateContractPayload,
+    /// The keys of the holder of the current credential.
+    holder:                KeyPair,
+    /// Number of transactions generated so far.
+    count:                 u64,
+}
+
+#[derive(concordium_std::Serial)]
+struct UpdateCredentialMetadataParam {
+    credential_id: CredentialHolderId,
+    metadata_url:  MetadataUrl,
+}
+
+#[derive(concordium_std::Serial)]
+struct RevokeCredentialIssuerParams {
+    credential_id:  CredentialHolderId,
+    reason:         Option<Reason>,
+    #[concordium(size_length = 2)]
+    auxiliary_data: Vec<u8>,
+}
+
+#[derive(concordium_std::Serial)]
+struct RevocationSigningData {
+    contract_address: ContractAddress,
+    entry_point:      OwnedEntrypointName,
+    nonce:            u64,
+    timestamp:        Timestamp,
+}
+
+#[derive(concordium_std::Serial)]
+struct RevocationDataHolder {
+    credential_id: CredentialHolderId,
+    signing_data:  RevocationSigningData,
+    reason:        Option<Reason>,
+}
+
+#[derive(concordium_std::Serial)]
+struct RevokeCredentialHolderParams {
+    signature: SignatureEd25519,
+    data:      RevocationDataHolder,
+}
+
+/// The parameter for registering a credential held by the given key.
+fn register_credential_param(
+    holder: &KeyPair,
+    holder_revocable: bool,
+    metadata_url: &str,
+) -> anyhow::Result<OwnedParameter> {
+    let params = RegisterCredentialParams {
+        credential_info: CredentialInfo {
+            holder_id: CredentialHolderId::new(holder.public()),
+            holder_revocable,
+            valid_from: Timestamp::from_timestamp_millis(0),
+            valid_until: None,
+            metadata_url: MetadataUrl::new(metadata_url.into(), None)?,
+        },
+        auxiliary_data:  vec![],
+    };
+    Ok(OwnedParameter::from_serial(&params)?)
+}
+
+/// The parameter for updating the metadata URL of a credential.
+fn update_metadata_param(holder: &KeyPair, url: &str) -> anyhow::Result<OwnedParameter> {
+    let params = vec![UpdateCredentialMetadataParam {
+        credential_id: CredentialHolderId::new(holder.public()),
+        metadata_url:  MetadataUrl::new(url.into(), None)?,
+    }];
+    Ok(OwnedParameter::from_serial(&params)?)
+}
+
+/// The parameter for revoking a credential as the issuer.
+fn revoke_issuer_param(holder: &KeyPair) -> anyhow::Result<OwnedParameter> {
+    let params = RevokeCredentialIssuerParams {
+        credential_id:  CredentialHolderId::new(holder.public()),
+        reason:         None,
+        auxiliary_data: vec![],
+    };
+    Ok(OwnedParameter::from_serial(&params)?)
+}
+
+/// The parameter for revoking a credential as the holder, signed by the holder
+/// key. The signature expires at the given time.
+fn revoke_holder_param(
+    holder: &KeyPair,
+    contract_address: ContractAddress,
+    nonce: u64,
+    timestamp: Timestamp,
+) -> anyhow::Result<OwnedParameter> {
+    let data = RevocationDataHolder {
+        credential_id: CredentialHolderId::new(holder.public()),
+        signing_data:  RevocationSigningData {
+            contract_address,
+            entry_point: OwnedEntrypointName::new_unchecked("revokeCredentialHolder".into()),
+            nonce,
+            timestamp,
+        },
+        reason:        None,
+    };
+    let mut message = REVOKE_DOMAIN_STRING.to_vec();
+    message.extend_from_slice(&concordium_std::to_bytes(&data));
+    let params = RevokeCredentialHolderParams {
+        signature: SignatureEd25519(holder.sign(&message).to_bytes()),
+        data,
+    };
+    Ok(OwnedParameter::from_serial(&params)?)
+}
+
+#[derive(concordium_std::Serial)]
+struct CredentialType {
+    #[concordium(size_length = 1)]
+    credential_type: String,
+}
+
+#[derive(concordium_std::Serial)]
+struct RegisterCredentialsInitParams {
+    issuer_metadata: MetadataUrl,
+    credential_type: CredentialType,
+    schema:          SchemaRef,
+    issuer_account:  Option<AccountAddress>,
+    issuer_key:      CredentialHolderId,
+    #[concordium(size_length = 1)]
+    revocation_keys: Vec<CredentialHolderId>,
+}
+
+#[derive(concordium_std::Serial)]
+struct RegisterCredentialParams {
+    credential_info: CredentialInfo,
+    #[concordium(size_length = 2)]
+    auxiliary_data:  Vec<u8>,
+}
+
+/// Deploy and initialize a credential registry with the sender as the issuer,
+/// unless an existing instance is given.
+async fn existing_or_deploy_credential_registry(
+    client: &mut v2::Client,
+    args: &CommonArgs,
+    contract: Option<ContractAddress>,
+    nonce: &mut Nonce,
+    rng: &mut StdRng,
+) -> anyhow::Result<ContractAddress> {
+    let issuer_public_key = KeyPair::generate(rng).public();
+
+    let info = ContractDeploymentInfo {
+        module:      REGISTER_CREDENTIALS_MODULE,
+        name:        "init_credential_registry",
+        init_energy: None,
+        owner_only:  true,
+    };
+    // The parameters don't matter, since we can still issue, so they are all dummy
+    // values.
+    let params = RegisterCredentialsInitParams {
+        issuer_metadata: MetadataUrl::new("https://example.com".into(), None)?,
+        credential_type: CredentialType {
+            credential_type: "TestCredential".into(),
+        },
+        schema:          SchemaRef {
+            schema_ref: MetadataUrl::new("https://example.com".into(), None)?,
+        },
+        issuer_account:  None,
+        issuer_key:      CredentialHolderId::new(issuer_public_key),
+        revocation_keys: vec![],
+    };
+
+    info.existing_or_deploy(
+        client,
+        args,
+        contract,
+        OwnedParameter::from_serial(&params)?,
+        nonce,
+    )
+    .await
+}
+
+impl RegisterCredentialsGenerator {
+    pub async fn instantiate(
+        mut client: v2::Client,
+        args: CommonArgs,
+        register_credentials_args: RegisterCredentialsArgs,
+    ) -> anyhow::Result<Self> {
+        let instance_args = register_credentials_args.instance;
+        // Get the initial nonce.
+        let mut nonce = client
+            .get_next_account_sequence_number(&args.keys.address)
+            .await?;
+
+        let mut rng = args.rng();
+        let contract_address = existing_or_deploy_credential_registry(
+            &mut client,
+            &args,
+            instance_args.contract,
+            &mut nonce.nonce,
+            &mut rng,
+        )
+        .await?;
+
+        let contract = Cis4Contract::create(client.clone(), contract_address).await?;
+        let mut estimator = EnergyEstimator::new(contract, &args);
+
+        let lifecycle = if register_credentials_args.lifecycle {
+            // Register a credential that is never revoked, and use it to estimate the
+            // energy of metadata updates and revocations.
+            println!("Registering a credential for estimating energy...");
+            let probe = KeyPair::generate(&mut rng);
+            let payload = estimator.payload(
+                "registerCredential",
+                Amount::zero(),
+                register_credential_param(&probe, true, "https://example.com")?,
+            )?;
+            let energy = estimator.estimate(&payload).await?;
+            let tx = send::update_contract(
+                &args.keys,
+                args.keys.address,
+                nonce.nonce,
+                TransactionTime::seconds_after(args.expiry),
+                payload,
+                energy,
+            );
+            let transaction_hash = client
+                .send_block_item(&BlockItem::AccountTransaction(tx))
+                .await?;
+            nonce.nonce.next_mut();
+            let (_, summary) = client.wait_until_finalized(&transaction_hash).await?;
+            anyhow::ensure!(
+                summary.is_success(),
+                "Registering the credential failed (hash = {transaction_hash})."
+            );
+
+            let lifecycle = CredentialLifecycle {
+                probe_update_metadata: estimator.payload(
+                    "updateCredentialMetadata",
+                    Amount::zero(),
+                    update_metadata_param(&probe, "https://example.com/updated")?,
+                )?,
+                probe_revoke_issuer:   estimator.payload(
+                    "revokeCredentialIssuer",
+                    Amount::zero(),
+                    revoke_issuer_param(&probe)?,
+                )?,
+                // The signature of the probe must not expire.
+                probe_revoke_holder:   estimator.payload(
+                    "revokeCredentialHolder",
+                    Amount::zero(),
+                    revoke_holder_param(
+                        &probe,
+                        contract_address,
+                        0,
+                        Timestamp::from_timestamp_millis(u64::MAX),
+                    )?,
+                )?,
+                holder:                probe,
+                count:                 0,
+            };
+            estimator.estimate(&lifecycle.probe_update_metadata).await?;
+            estimator.estimate(&lifecycle.probe_revoke_issuer).await?;
+            estimator.estimate(&lifecycle.probe_revoke_holder).await?;
+            Some(lifecycle)
+        } else {
+            let payload = estimator.payload(
+                "registerCredential",
+                Amount::zero(),
+                register_credential_param(
+                    &KeyPair::generate(&mut rng),
+                    false,
+                    "https://example.com",
+                )?,
+            )?;
+            estimator.estimate(&payload).await?;
+            None
+        };
+
+        Ok(Self {
+            estimator,
+            args,
+            nonce: nonce.nonce,
+            rng,
+            lifecycle,
+        })
+    }
+
+    /// Make the next transaction of the `--lifecycle` variant. Each credential
+    /// is registered, its metadata is updated, and it is then revoked by the
+    /// issuer or by the holder, alternately.
+    fn generate_lifecycle(&mut self) -> anyhow::Result<AccountTransaction<EncodedPayload>> {
+        let lifecycle = self
+            .lifecycle
+            .as_mut()
+            .context("Not in the lifecycle variant.")?;
+        let credential = lifecycle.count / 3;
+        let step = lifecycle.count % 3;
+        lifecycle.count += 1;
+        match step {
+            0 => {
+                lifecycle.holder = KeyPair::generate(&mut self.rng);
+                self.estimator.make_update(
+                    &self.args,
+                    self.nonce,
+                    "registerCredential",
+                    Amount::zero(),
+                    register_credential_param(&lifecycle.holder, true, "https://example.com")?,
+                )
+            }
+            1 => {
+                let payload = self.estimator.payload(
+                    "updateCredentialMetadata",
+                    Amount::zero(),
+                    update_metadata_param(
+                        &lifecycle.holder,
+                        &format!("https://example.com/{credential}"),
+                    )?,
+                )?;
+                self.estimator.make_update_estimated_by(
+                    &self.args,
+                    self.nonce,
+                    payload,
+                    &lifecycle.probe_update_metadata,
+                )
+            }
+            _ if credential % 2 == 0 => {
+                let payload = self.estimator.payload(
+                    "revokeCredentialIssuer",
+                    Amount::zero(),
+                    revoke_issuer_param(&lifecycle.holder)?,
+                )?;
+                self.estimator.make_update_estimated_by(
+                    &self.args,
+                    self.nonce,
+                    payload,
+                    &lifecycle.probe_revoke_issuer,
+                )
+            }
+            _ => {
+                // The revocation nonce of a new credential is 0. The signature expires
+                // together with the transaction.
+                let expiry = TransactionTime::seconds_after(self.args.expiry);
+                let payload = self.estimator.payload(
+                    "revokeCredentialHolder",
+                    Amount::zero(),
+                    revoke_holder_param(
+                        &lifecycle.holder,
+                        self.estimator.address,
+                        0,
+                        Timestamp::from_timestamp_millis(expiry.seconds * 1000),
+                    )?,
+                )?;
+                self.estimator.make_update_estimated_by(
+                    &self.args,
+                    self.nonce,
+                    payload,
+                    &lifecycle.probe_revoke_holder,
+                )
+            }
+        }
+    }
+}
+
+impl Generate for RegisterCredentialsGenerator {
+    fn generate(&mut self) -> anyhow::Result<BlockItem<EncodedPayload>> {
+        let tx = if self.lifecycle.is_some() {
+            self.generate_lifecycle()?
+        } else {
+            // Create 32 byte holder id.
+            let holder = KeyPair::generate(&mut self.rng);
+            self.estimator.make_update(
+                &self.args,
+                self.nonce,
+                "registerCredential",
+                Amount::zero(),
+                register_credential_param(&holder, false, "https://example.com")?,
+            )?
+        };
+        self.nonce.next_mut();
+
+        Ok(BlockItem::AccountTransaction(tx))
+    }
+}
+
+/// A call to an entrypoint of a user-supplied contract, as given in the file
+/// passed to `--entrypoints`.
+#[derive(serde::Deserialize)]
+struct EntrypointTemplate {
+    /// The name of the entrypoint, without the contract name.
+    entrypoint: String,
+    /// The parameter in the JSON format of the schema. String values that are
+    /// equal to one of the placeholders `$SENDER`, `$ACCOUNT` or `$COUNTER`
+    /// are replaced before each call. If not given, the empty parameter is
+    /// used.
+    #[serde(default)]
+    parameter:  Option<serde_json::Value>,
+    /// The energy to use for each call. If not given, it is estimated by
+    /// invoking the entrypoint.
+    energy:     Option<Energy>,
+    /// The amount of CCD to send with each call.
+    #[serde(default = "Amount::zero")]
+    amount:     Amount,
+}
+
+/// An entrypoint of a user-supplied contract together with the schema of its
+/// parameter.
+struct ContractEntrypoint {
+    receive_name: OwnedReceiveName,
+    template:     Option<(serde_json::Value, Type)>,
+    energy:       Option<Energy>,
+    amount:       Amount,
+}
+
+/// Replace the placeholders in a JSON parameter template. Every string value
+/// for which `resolve` returns a value is replaced by that value.
+fn fill_template(
+    template: &serde_json::Value,
+    resolve: &mut impl FnMut(&str) -> Option<serde_json::Value>,
+) -> serde_json::Value {
+    use serde_json::Value;
+    match template {
+        Value::String(s) => resolve(s).unwrap_or_else(|| template.clone()),
+        Value::Array(values) => {
+            Value::Array(values.iter().map(|v| fill_template(v, resolve)).collect())
+        }
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(k, v)| (k.clone(), fill_template(v, resolve)))
+                .collect(),
+        ),
+        _ => template.clone(),
+    }
+}
+
+/// The schema embedded in a module, if any.
+pub(crate) fn embedded_schema(module: &WasmModule) -> Option<VersionedModuleSchema> {
+    let source: &[u8] = module.source.as_ref();
+    match module.version {
+        WasmVersion::V0 => get_embedded_schema_v0(source).ok(),
+        WasmVersion::V1 => get_embedded_schema_v1(source).ok(),
+    }
+}
+
+/// Serialize a JSON value according to a schema type into a parameter.
+pub(crate) fn serial_parameter(
+    schema: &Type,
+    value: &serde_json::Value,
+) -> anyhow::Result<OwnedParameter> {
+    let bytes = schema
+        .serial_value(value)
+        .map_err(|e| anyhow::anyhow!("Parameter does not match the schema: {e}"))?;
+    Ok(OwnedParameter::try_from(bytes)?)
+}
+
+/// A generator that deploys a user-supplied contract and calls its
+/// entrypoints in a round-robin fashion with parameters filled in from
+/// templates.
+pub struct ContractGenerator {
+    args:        CommonArgs,
+    estimator:   EnergyEstimator,
+    entrypoints: Vec<ContractEntrypoint>,
+    accounts:    Vec<AccountAddress>,
+    rng:         StdRng,
+    nonce:       Nonce,
+    count:       u64,
+}
+
+impl ContractGenerator {
+    pub async fn instantiate(
+        mut client: v2::Client,
+        args: CommonArgs,
+        contract_args: ContractArgs,
+    ) -> anyhow::Result<Self> {
+        let accounts = get_receivers(&mut client, contract_args.receivers).await?;
+
+        let module_bytes =
+            std::fs::read(&contract_args.module).context("Could not read the module file.")?;
+        let module = WasmModule::deserial(&mut Cursor::new(&module_bytes))
+            .context("Could not parse the module file.")?;
+        let init_name = OwnedContractName::new(contract_args.init_name)
+            .context("Invalid name of the init function.")?;
+        let contract_name = init_name.as_contract_name().contract_name();
+
+        let schema = match contract_args.schema {
+            Some(path) => Some(
+                VersionedModuleSchema::new(
+                    &std::fs::read(path).context("Could not read the schema file.")?,
+                    &None,
+                )
+                .context("Could not parse the schema file.")?,
+            ),
+            None => embedded_schema(&module),
+        };
+        let schema = || {
+            schema.as_ref().context(
+                "The module has no embedded schema, so a schema must be given with `--schema`.",
+            )
+        };
+
+        let init_param = match contract_args.init_param {
+            None => OwnedParameter::empty(),
+            Some(path) => {
+                let value: serde_json::Value = serde_json::from_str(
+                    &std::fs::read_to_string(path)
+                        .context("Could not read the init parameter file.")?,
+                )
+                .context("Could not parse the init parameter file.")?;
+                let param_schema = schema()?
+                    .get_init_param_schema(contract_name)
+                    .context("The schema has no type for the init parameter.")?;
+                serial_parameter(&param_schema, &value).context("Invalid init parameter.")?
+            }
+        };
+
+        let templates: Vec<EntrypointTemplate> = serde_json::from_str(
+            &std::fs::read_to_string(contract_args.entrypoints)
+                .context("Could not read the entrypoints file.")?,
+        )
+        .context("Could not parse the entrypoints file.")?;
+        anyhow::ensure!(
+            !templates.is_empty(),
+            "List of entrypoints must not be empty."
+        );
+        let mut entrypoints = Vec::with_capacity(templates.len());
+        // A parameter for each entrypoint, used for the first energy estimate.
+        let mut samples = Vec::with_capacity(templates.len());
+        for template in templates {
+            let receive_name =
+                OwnedReceiveName::new(format!("{contract_name}.{}", template.entrypoint))
+                    .with_context(|| format!("Invalid entrypoint {}.", template.entrypoint))?;
+            let template_and_schema = match template.parameter {
+                None => {
+                    samples.push(OwnedParameter::empty());
+                    None
+                }
+                Some(value) => {
+                    let param_schema = schema()?
+                        .get_receive_param_schema(contract_name, &template.entrypoint)
+                        .with_context(|| {
+                            format!("The schema has no parameter type for {receive_name}.")
+                        })?;
+                    // Check that the template is valid before deploying anything.
+                    let sample = fill_template(&value, &mut |placeholder| match placeholder {
+                        "$SENDER" | "$ACCOUNT" => Some(args.keys.address.to_string().into()),
+                        "$COUNTER" => Some(0.into()),
+                        _ => None,
+                    });
+                    samples.push(
+                        serial_parameter(&param_schema, &sample)
+                            .with_context(|| format!("Invalid parameter for {receive_name}."))?,
+                    );
+                    Some((value, param_schema))
+                }
+            };
+            entrypoints.push(ContractEntrypoint {
+                receive_name,
+                template: template_and_schema,
+                energy: template.energy,
+                amount: template.amount,
+            });
+        }
+
+        // Get the initial nonce.
+        let mut nonce = client
+            .get_next_account_sequence_number(&args.keys.address)
+            .await?;
+
+        let info = ContractDeploymentInfo {
+            module:      &module_bytes,
+            name:        init_name.as_contract_name().get_chain_name(),
+            init_energy: contract_args.init_energy,
+            owner_only:  false,
+        };
+        let address = info
+            .deploy_and_init_contract(&mut client, &args, init_param, &mut nonce.nonce)
+            .await
+            .context("Could not deploy/init the contract.")?;
+        let mut estimator =
+            EnergyEstimator::new(ContractClient::<()>::new(client, address, init_name), &args);
+        for (entrypoint, message) in entrypoints.iter().zip(samples) {
+            if entrypoint.energy.is_none() {
+                let payload = UpdateContractPayload {
+                    amount: entrypoint.amount,
+                    address,
+                    receive_name: entrypoint.receive_name.clone(),
+                    message,
+                };
+                estimator.estimate(&payload).await?;
+            }
+        }
+
+        Ok(Self {
+            rng: args.rng(),
+            args,
+            estimator,
+            entrypoints,
+            accounts,
+            nonce: nonce.nonce,
+            count: 0,
+        })
+    }
+}
+
+impl Generate for ContractGenerator {
+    fn generate(&mut self) -> anyhow::Result<BlockItem<EncodedPayload>> {
+        let entrypoint = &self.entrypoints[self.count as usize % self.entrypoints.len()];
+
+        let message = match &entrypoint.template {
+            None => OwnedParameter::empty(),
+            Some((template, param_schema)) => {
+                let value = fill_template(template, &mut |placeholder| match placeholder {
+                    "$SENDER" => Some(self.args.keys.address.to_string().into()),
+                    "$ACCOUNT" => {
+                        let account = self.accounts[self.rng.gen_range(0..self.accounts.len())];
+                        Some(account.to_string().into())
+                    }
+                    "$COUNTER" => Some(self.count.into()),
+                    _ => None,
+                });
+                serial_parameter(param_schema, &value)?
+            }
+        };
+
+        let payload = UpdateContractPayload {
+            amount: entrypoint.amount,
+            address: self.estimator.address,
+            receive_name: entrypoint.receive_name.clone(),
+            message,
+        };
+        let energy = match entrypoint.energy {
+            Some(energy) => energy,
+            None => self.estimator.energy(&payload)?,
+        };
+        let expiry = TransactionTime::seconds_after(self.args.expiry);
+        let tx = send::update_contract(
+            &self.args.keys,
+            self.args.keys.address,
+            self.nonce,
+            expiry,
+            payload,
+            energy,
+        );
+        self.nonce.next_mut();
+        self.count += 1;
+
+        Ok(BlockItem::AccountTransaction(tx))
+    }
+}
+
+/// A generator that grows the state of a contract without bound, either by
+/// minting new CIS-2 tokens or by registering credentials with large metadata.
+/// The size of the state and the cost of an update are reported periodically.
+pub struct StateGrowthGenerator {
+    estimator:    EnergyEstimator,
+    args:         CommonArgs,
+    nonce:        Nonce,
+    rng:          StdRng,
+    workload:     GrowthWorkload,
+    /// The next token id to mint in the `tokens` workload.
+    next_id:      u32,
+    items:        u8,
+    metadata_url: String,
+}
+
+/// The parameter for minting the given range of token ids of a `cis2_nft`
+/// contract for the owner.
+pub(crate) fn mint_nft_param(
+    owner: AccountAddress,
+    ids: std::ops::Range<u32>,
+) -> anyhow::Result<OwnedParameter> {
+    let params = MintCis2NftParams {
+        owner:  Address::Account(owner),
+        tokens: ids.map(TokenId::new_u32).collect(),
+    };
+    Ok(OwnedParameter::from_serial(&params)?)
+}
+
+impl StateGrowthGenerator {
+    pub async fn instantiate(
+        mut client: v2::Client,
+        args: CommonArgs,
+        state_growth_args: StateGrowthArgs,
+    ) -> anyhow::Result<Self> {
+        let items = state_growth_args.items_per_transaction;
+        anyhow::ensure!(
+            items > 0,
+            "At least one token must be minted per transaction."
+        );
+        // A URL of the given length.
+        let prefix = "https://example.com/";
+        let metadata_url = format!(
+            "{prefix}{}",
+            "a".repeat(usize::from(state_growth_args.payload_size).saturating_sub(prefix.len()))
+        );
+
+        let mut nonce = client
+            .get_next_account_sequence_number(&args.keys.address)
+            .await?;
+        let mut rng = args.rng();
+        let contract = state_growth_args.instance.contract;
+        let (mut estimator, next_id, probe) = match state_growth_args.workload {
+            GrowthWorkload::Tokens => {
+                let info = ContractDeploymentInfo {
+                    module:      MINT_CIS2_MODULE,
+                    name:        "init_cis2_nft",
+                    init_energy: None,
+                    owner_only:  true,
+                };
+                let contract_address = info
+                    .existing_or_deploy(
+                        &mut client,
+                        &args,
+                        contract,
+                        OwnedParameter::empty(),
+                        &mut nonce.nonce,
+                    )
+                    .await?;
+                let contract = Cis2Contract::create(client.clone(), contract_address).await?;
+                let next_id = next_unused_token_id(&contract).await?;
+                let estimator = EnergyEstimator::new(contract, &args);
+                // Token ids at the end of the range are not minted for a long time.
+                let probe = estimator.payload(
+                    "mint",
+                    Amount::zero(),
+                    mint_nft_param(args.keys.address, u32::MAX - u32::from(items)..u32::MAX)?,
+                )?;
+                (estimator, next_id, probe)
+            }
+            GrowthWorkload::Metadata => {
+                let contract_address = existing_or_deploy_credential_registry(
+                    &mut client,
+                    &args,
+                    contract,
+                    &mut nonce.nonce,
+                    &mut rng,
+                )
+                .await?;
+                let contract = Cis4Contract::create(client.clone(), contract_address).await?;
+                let estimator = EnergyEstimator::new(contract, &args);
+                let probe = estimator.payload(
+                    "registerCredential",
+                    Amount::zero(),
+                    register_credential_param(&KeyPair::generate(&mut rng), false, &metadata_url)?,
+                )?;
+                (estimator, 0, probe)
+            }
+        };
+        estimator.estimate(&probe).await?;
+
+        let interval = std::time::Duration::from_secs(state_growth_args.report_interval.max(1));
+        let sender = args.keys.address;
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                if let Err(e) = report_state(client.clone(), sender, &probe).await {
+                    eprintln!("Could not report the state of the contract: {e:#}");
+                }
+            }
+        });
+
+        Ok(Self {
+            estimator,
+            args,
+            nonce: nonce.nonce,
+            rng,
+            workload: state_growth_args.workload,
+            next_id,
+            items,
+            metadata_url,
+        })
+    }
+}
+
+/// Print the size of the state of the contract updated by the probe, and the
+/// energy needed to execute the probe.
+async fn report_state(
+    mut client: v2::Client,
+    sender: AccountAddress,
+    probe: &UpdateContractPayload,
+) -> anyhow::Result<()> {
+    let address = probe.address;
+    let (entries, bytes) = client
+        .get_instance_state(address, BlockIdentifier::LastFinal)
+        .await?
+        .response
+        .try_fold((0u64, 0u64), |(entries, bytes), (key, value)| async move {
+            Ok((entries + 1, bytes + (key.len() + value.len()) as u64))
+        })
+        .await?;
+    let context = ContractContext::new_from_payload(sender, None, probe.clone());
+    let energy = match client
+        .invoke_instance(BlockIdentifier::LastFinal, &context)
+        .await?
+        .response
+    {
+        InvokeContractResult::Success { used_energy, .. } => used_energy.to_string(),
+        InvokeContractResult::Failure { reason, .. } => format!("rejected ({reason:?})"),
+    };
+    println!(
+        "{}: The state of {address} has {entries} entries and {bytes} bytes. Executing {} costs \
+         {energy} NRG.",
+        chrono::Utc::now(),
+        probe.receive_name,
+    );
+    Ok(())
+}
+
+impl Generate for StateGrowthGenerator {
+    fn generate(&mut self) -> anyhow::Result<BlockItem<EncodedPayload>> {
+        let tx = match self.workload {
+            GrowthWorkload::Tokens => {
+                let end = self
+                    .next_id
+                    .checked_add(u32::from(self.items))
+                    .context("All token ids have been minted.")?;
+                let tx = self.estimator.make_update(
+                    &self.args,
+                    self.nonce,
+                    "mint",
+                    Amount::zero(),
+                    mint_nft_param(self.args.keys.address, self.next_id..end)?,
+                )?;
+                self.next_id = end;
+                tx
+            }
+            GrowthWorkload::Metadata => {
+                let holder = KeyPair::generate(&mut self.rng);
+                self.estimator.make_update(
+                    &self.args,
+                    self.nonce,
+                    "registerCredential",
+                    Amount::zero(),
+                    register_credential_param(&holder, false, &self.metadata_url)?,
+                )?
+            }
+        };
+        self.nonce.next_mut();
+
+        Ok(BlockItem::AccountTransaction(tx))
+    }
+}
+
+/// A generator that makes level 2 chain updates. The updates set the chain
+/// parameters to their values when the generator started, so that the
+/// behaviour of the chain does not change.
+pub struct ChainUpdatesGenerator {
+    updates: Vec<ChainUpdate>,
+    expiry:  u32,
+    count:   usize,
+}
+
+/// An update made in turn by the [`ChainUpdatesGenerator`].
+struct ChainUpdate {
+    payload:    UpdatePayload,
+    /// The keys that sign the update.
+    signer:     BTreeMap<UpdateKeysIndex, UpdateKeyPair>,
+    /// The sequence number of the next update of this kind.
+    seq_number: UpdateSequenceNumber,
+}
+
+/// Read the level 2 keys from the files `level2-key-<index>.json` in the
+/// directory.
+fn read_level2_keys(dir: &Path) -> anyhow::Result<BTreeMap<UpdateKeysIndex, UpdateKeyPair>> {
+    let mut keys = BTreeMap::new();
+    for entry in std::fs::read_dir(dir).context("Could not read the keys directory.")? {
+        let path = entry?.path();
+        let Some(index) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix("level2-key-"))
+            .and_then(|name| name.strip_suffix(".json"))
+            .and_then(|index| index.parse().ok())
+        else {
+            continue;
+        };
+        let key: UpdateKeyPair = serde_json::from_str(&std::fs::read_to_string(&path)?)
+            .with_context(|| format!("Could not parse the key {}.", path.display()))?;
+        keys.insert(UpdateKeysIndex { index }, key);
+    }
+    anyhow::ensure!(
+        !keys.is_empty(),
+        "No level 2 keys found in {}.",
+        dir.display()
+    );
+    Ok(keys)
+}
+
+/// Choose keys among the given ones that meet the threshold of the access
+/// structure.
+fn signer_for(
+    keys: &BTreeMap<UpdateKeysIndex, UpdateKeyPair>,
+    access_structure: &AccessStructure,
+    kind: UpdateKind,
+) -> anyhow::Result<BTreeMap<UpdateKeysIndex, UpdateKeyPair>> {
+    let threshold = usize::from(u16::from(access_structure.threshold));
+    let signer: BTreeMap<_, _> = access_structure
+        .authorized_keys
+        .iter()
+        .filter_map(|index| Some((*index, keys.get(index)?.clone())))
+        .take(threshold)
+        .collect();
+    anyhow::ensure!(
+        signer.len() == threshold,
+        "Only {} of the {threshold} keys needed for {kind:?} updates are given.",
+        signer.len()
+    );
+    Ok(signer)
+}
+
+impl ChainUpdatesGenerator {
+    pub async fn instantiate(
+        mut client: v2::Client,
+        chain_updates_args: ChainUpdatesArgs,
+        expiry: u32,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            !chain_updates_args.updates.is_empty(),
+            "At least one update must be given."
+        );
+        let keys = read_level2_keys(&chain_updates_args.keys)?;
+        let parameters = client
+            .get_block_chain_parameters(BlockIdentifier::LastFinal)
+            .await?
+            .response;
+        let authorizations = parameters.common_update_keys();
+        for (index, key) in &keys {
+            anyhow::ensure!(
+                authorizations.keys.get(usize::from(index.index))
+                    == Some(&UpdatePublicKey::from(key)),
+                "The level 2 key with index {index} is not a key of the chain."
+            );
+        }
+        let seq_numbers = client
+            .get_next_update_sequence_numbers(BlockIdentifier::LastFinal)
+            .await?
+            .response;
+
+        let (micro_ccd_per_euro, euro_per_energy) = match &parameters {
+            v2::ChainParameters::V0(cp) => (cp.micro_ccd_per_euro, cp.euro_per_energy),
+            v2::ChainParameters::V1(cp) => (cp.micro_ccd_per_euro, cp.euro_per_energy),
+            v2::ChainParameters::V2(cp) => (cp.micro_ccd_per_euro, cp.euro_per_energy),
+        };
+        let mut updates = Vec::new();
+        for (i, &kind) in chain_updates_args.updates.iter().enumerate() {
+            // Updates of the same kind would use the same sequence numbers.
+            anyhow::ensure!(
+                !chain_updates_args.updates[..i].contains(&kind),
+                "The update `{kind:?}` is given more than once."
+            );
+            let (payload, access_structure, seq_number) = match kind {
+                UpdateKind::MicroCcdPerEuro => (
+                    UpdatePayload::MicroGTUPerEuro(micro_ccd_per_euro),
+                    &authorizations.micro_gtu_per_euro,
+                    seq_numbers.micro_ccd_per_euro,
+                ),
+                UpdateKind::EuroPerEnergy => (
+                    UpdatePayload::EuroPerEnergy(euro_per_energy),
+                    &authorizations.euro_per_energy,
+                    seq_numbers.euro_per_energy,
+                ),
+                UpdateKind::GasRewards => (
+                    match &parameters {
+                        v2::ChainParameters::V0(cp) => {
+                            UpdatePayload::GASRewards(cp.gas_rewards.clone())
+                        }
+                        v2::ChainParameters::V1(cp) => {
+                            UpdatePayload::GASRewards(cp.gas_rewards.clone())
+                        }
+                        v2::ChainParameters::V2(cp) => {
+                            UpdatePayload::GASRewardsCPV2(cp.gas_rewards.clone())
+                        }
+                    },
+                    &authorizations.param_gas_rewards,
+                    seq_numbers.gas_rewards,
+                ),
+                UpdateKind::PoolParameters => (
+                    match &parameters {
+                        v2::ChainParameters::V0(_) => anyhow::bail!(
+                            "Pool parameter updates require protocol version 4 or later."
+                        ),
+                        v2::ChainParameters::V1(cp) => {
+                            UpdatePayload::PoolParametersCPV1(cp.pool_parameters.clone())
+                        }
+                        v2::ChainParameters::V2(cp) => {
+                            UpdatePayload::PoolParametersCPV1(cp.pool_parameters.clone())
+                        }
+                    },
+                    &authorizations.pool_parameters,
+                    seq_numbers.pool_parameters,
+                ),
+            };
+            updates.push(ChainUpdate {
+                payload,
+                signer: signer_for(&keys, access_structure, kind)?,
+                seq_number,
+            });
+        }
+
+        Ok(Self {
+            updates,
+            expiry,
+            count: 0,
+        })
+    }
+}
+
+impl Generate for ChainUpdatesGenerator {
+    fn generate(&mut self) -> anyhow::Result<BlockItem<EncodedPayload>> {
+        let index = self.count % self.updates.len();
+        self.count += 1;
+        let update = &mut self.updates[index];
+
+        // The updates take effect immediately.
+        let instruction = update::update(
+            &update.signer,
+            update.seq_number,
+            TransactionTime { seconds: 0 },
+            TransactionTime::seconds_after(self.expiry),
+            update.payload.clone(),
+        );
+        update.seq_number.next_mut();
+
+        Ok(BlockItem::UpdateInstruction(instruction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fake_node::{FakeNode, Invoked, State},
+        submitter::{Distribution, OutputFormat},
+    };
+    use concordium_rust_sdk::{
+        base::contracts_common::Duration,
+        common::types::Ratio,
+        id::{
+            account_holder::generate_pio_v1,
+            chain::verify_cdi,
+            identity_provider::verify_credentials_v1,
+            ps_sig,
+            secret_sharing::Threshold,
+            types::{AttributeList, Description, IpIdentity, YearMonth},
+        },
+        smart_contracts::common::schema::Fields,
+        types::{
+            queries::NextUpdateSequenceNumbers, AccountEncryptedAmount, AccountIndex, AccountInfo,
+            AccountReleaseSchedule, AmountFraction, AuthorizationsV0, AuthorizationsV1,
+            BakerPoolInfo, BakerPoolStatus, CommissionRanges, CommissionRates, CooldownParameters,
+            CredentialsPerBlockLimit, DurationSeconds, ExchangeRate,
+            FinalizationCommitteeParameters, GASRewardsV1, HigherLevelAccessStructure,
+            InclusiveRange, MintDistributionV1, MintRate, PoolPendingChange, RewardPeriodLength,
+            TimeParameters, TimeoutParameters, TransactionFeeDistribution,
+            UpdateKeysCollectionSkeleton, UpdateKeysThreshold,
+        },
+    };
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    };
+
+    /// Arguments of a generator sending from a fixed account.
+    fn common_args(seed: Option<u64>) -> CommonArgs {
+        CommonArgs {
+            keys: WalletAccount {
+                address: AccountAddress([1; 32]),
+                keys:    AccountKeys::singleton(&mut StdRng::seed_from_u64(0)),
+            },
+            expiry: 100,
+            energy_margin: 20,
+            reestimate_interval: 60,
+            seed,
+        }
+    }
+
+    /// The state of a fake node with three accounts, where the sender has
+    /// nonce 5.
+    fn sender_state() -> State {
+        State {
+            accounts: vec![
+                AccountAddress([2; 32]),
+                AccountAddress([3; 32]),
+                AccountAddress([4; 32]),
+            ],
+            nonces: [(AccountAddress([1; 32]), Nonce { nonce: 5 })].into(),
+            ..State::default()
+        }
+    }
+
+    /// A fake node with the accounts of [`sender_state`].
+    async fn fake_node() -> anyhow::Result<v2::Client> {
+        let (client, _) = FakeNode::start(sender_state()).await?;
+        Ok(client)
+    }
+
+    fn ccd_args(mode: Option<Mode>, memo_size: Option<usize>) -> CcdArgs {
+        CcdArgs {
+            receivers: None,
+            amount: Amount::from_micro_ccd(1),
+            mode,
+            memo_size,
+            schedule: None,
+        }
+    }
+
+    /// Generate `count` account transactions and decode their payloads.
+    fn generate_payloads(
+        generator: &mut impl Generate,
+        count: usize,
+    ) -> anyhow::Result<Vec<(Nonce, Payload)>> {
+        (0..count)
+            .map(|_| match generator.generate()? {
+                BlockItem::AccountTransaction(tx) => Ok((tx.header.nonce, tx.payload.decode()?)),
+                _ => anyhow::bail!("Expected an account transaction."),
+            })
+            .collect()
+    }
+
+    /// A fake node with a `cis2_nft` instance at `<0,0>` owned by `owner`,
+    /// where the tokens `0..minted` are minted, and minting uses the energy in
+    /// `mint_energy`.
+    async fn fake_nft_node(
+        owner: AccountAddress,
+        minted: u32,
+        mint_energy: Arc<AtomicU64>,
+    ) -> anyhow::Result<(v2::Client, Arc<Mutex<State>>)> {
+        let invoke = move |entrypoint: &str, parameter: &[u8]| match entrypoint {
+            "cis2_nft.tokenMetadata" => {
+                // The parameter is a single `u32` token id, which is at the end.
+                let id = parameter[parameter.len() - 4..]
+                    .try_into()
+                    .map(u32::from_le_bytes)
+                    .expect("The token id has 4 bytes.");
+                if id < minted {
+                    // A single empty metadata URL without a hash.
+                    Invoked::Success {
+                        return_value: vec![1, 0, 0, 0, 0],
+                        used_energy:  100,
+                    }
+                } else {
+                    Invoked::Rejected { reason: -42000001 }
+                }
+            }
+            "cis2_nft.mint" => Invoked::Success {
+                return_value: Vec::new(),
+                used_energy:  mint_energy.load(Ordering::SeqCst),
+            },
+            _ => Invoked::Rejected { reason: -1 },
+        };
+        let state = with_instance(
+            State::default(),
+            MINT_CIS2_MODULE,
+            "init_cis2_nft",
+            owner,
+            invoke,
+        )?;
+        FakeNode::start(state).await
+    }
+
+    /// Add an instance of the contract `init_name` in `module` at `<0,0>`,
+    /// owned by `owner`, whose entrypoints are answered by `invoke`.
+    fn with_instance(
+        state: State,
+        module: &[u8],
+        init_name: &str,
+        owner: AccountAddress,
+        invoke: impl FnMut(&str, &[u8]) -> Invoked + Send + 'static,
+    ) -> anyhow::Result<State> {
+        let instance = InstanceInfo::V1 {
+            owner,
+            amount: Amount::zero(),
+            methods: Default::default(),
+            name: OwnedContractName::new(init_name.into())?,
+            source_module: WasmModule::deserial(&mut Cursor::new(module))?.get_module_ref(),
+        };
+        Ok(State {
+            instances: [(ContractAddress::new(0, 0), instance)].into(),
+            invoke: Some(Box::new(invoke)),
+            ..state
+        })
+    }
+
+    fn existing_instance() -> InstanceArgs {
+        InstanceArgs {
+            contract: Some(ContractAddress::new(0, 0)),
+        }
+    }
+
+    /// The contract update in a payload.
+    fn expect_update(payload: Payload) -> UpdateContractPayload {
+        match payload {
+            Payload::Update { payload } => payload,
+            payload => panic!("Expected a contract update, got {payload:?}."),
+        }
+    }
+
+    /// An account with 1000 CCD and without stake, whose encrypted balance is
+    /// zero under the given key.
+    fn account_info(
+        global: &GlobalContext<ArCurve>,
+        address: AccountAddress,
+        index: u64,
+        secret_key: &elgamal::SecretKey<ArCurve>,
+    ) -> AccountInfo {
+        AccountInfo {
+            account_nonce:            Nonce { nonce: 1 },
+            account_amount:           Amount::from_ccd(1000),
+            account_release_schedule: AccountReleaseSchedule {
+                total:    Amount::zero(),
+                schedule: Vec::new(),
+            },
+            account_credentials:      BTreeMap::new(),
+            account_threshold:        concordium_std::AccountThreshold::ONE,
+            account_encrypted_amount: AccountEncryptedAmount {
+                self_amount:       encrypted_transfers::encrypt_amount_with_fixed_randomness(
+                    global,
+                    Amount::zero(),
+                ),
+                start_index:       0,
+                aggregated_amount: None,
+                incoming_amounts:  Vec::new(),
+            },
+            account_encryption_key:   elgamal::PublicKey::from(secret_key),
+            account_index:            AccountIndex { index },
+            account_stake:            None,
+            account_address:          address,
+        }
+    }
+
+    /// Chain parameters of protocol version 6, where every update is
+    /// authorized by all of the given level 2 keys with threshold 1.
+    fn chain_parameters(keys: Vec<UpdatePublicKey>) -> anyhow::Result<v2::ChainParametersV2> {
+        let fraction = AmountFraction::new_unchecked;
+        let threshold = UpdateKeysThreshold::try_from(1).expect("The threshold is not zero.");
+        let all_keys = AccessStructure {
+            authorized_keys: (0..keys.len() as u16)
+                .map(|index| UpdateKeysIndex { index })
+                .collect(),
+            threshold,
+        };
+        let commission_range = InclusiveRange {
+            min: fraction(1_000),
+            max: fraction(10_000),
+        };
+        Ok(v2::ChainParametersV2 {
+            timeout_parameters: TimeoutParameters::new(
+                Duration::from_millis(10_000),
+                Ratio::new(6, 5)?,
+                Ratio::new(1, 2)?,
+            )?,
+            min_block_time: Duration::from_millis(2_000),
+            block_energy_limit: Energy::from(3_000_000),
+            euro_per_energy: ExchangeRate::new_unchecked(1, 50_000),
+            micro_ccd_per_euro: ExchangeRate::new_unchecked(100, 1),
+            cooldown_parameters: CooldownParameters {
+                pool_owner_cooldown: DurationSeconds { seconds: 3_600 },
+                delegator_cooldown:  DurationSeconds { seconds: 1_800 },
+            },
+            time_parameters: TimeParameters {
+                reward_period_length: RewardPeriodLength::from(Epoch { epoch: 4 }),
+                mint_per_payday:      MintRate {
+                    mantissa: 1,
+                    exponent: 10,
+                },
+            },
+            account_creation_limit: CredentialsPerBlockLimit { limit: 10 },
+            mint_distribution: MintDistributionV1 {
+                baking_reward:       fraction(60_000),
+                finalization_reward: fraction(30_000),
+            },
+            transaction_fee_distribution: TransactionFeeDistribution {
+                baker:       fraction(45_000),
+                gas_account: fraction(45_000),
+            },
+            gas_rewards: GASRewardsV1 {
+                baker:            fraction(25_000),
+                account_creation: fraction(2_000),
+                chain_update:     fraction(5),
+            },
+            foundation_account: AccountAddress([0; 32]),
+            pool_parameters: PoolParameters {
+                passive_finalization_commission: fraction(100_000),
+                passive_baking_commission:       fraction(12_000),
+                passive_transaction_commission:  fraction(12_000),
+                commission_bounds:               CommissionRanges {
+                    finalization: commission_range,
+                    baking:       commission_range,
+                    transaction:  commission_range,
+                },
+                minimum_equity_capital:          Amount::from_ccd(100),
+                capital_bound:                   CapitalBound {
+                    bound: fraction(100_000),
+                },
+                leverage_bound:                  LeverageFactor {
+                    numerator:   3,
+                    denominator: 1,
+                },
+            },
+            finalization_committee_parameters: FinalizationCommitteeParameters {
+                min_finalizers: 1,
+                max_finalizers: 10,
+                finalizers_relative_stake_threshold: PartsPerHundredThousands::new_unchecked(1_000),
+            },
+            keys: UpdateKeysCollectionSkeleton {
+                root_keys:    HigherLevelAccessStructure {
+                    keys: keys.clone(),
+                    threshold,
+                    _phantom: Default::default(),
+                },
+                level_1_keys: HigherLevelAccessStructure {
+                    keys: keys.clone(),
+                    threshold,
+                    _phantom: Default::default(),
+                },
+                level_2_keys: AuthorizationsV1 {
+                    v0:                  AuthorizationsV0 {
+                        keys,
+                        emergency: all_keys.clone(),
+                        protocol: all_keys.clone(),
+                        election_difficulty: all_keys.clone(),
+                        euro_per_energy: all_keys.clone(),
+                        micro_gtu_per_euro: all_keys.clone(),
+                        foundation_account: all_keys.clone(),
+                        mint_distribution: all_keys.clone(),
+                        transaction_fee_distribution: all_keys.clone(),
+                        param_gas_rewards: all_keys.clone(),
+                        pool_parameters: all_keys.clone(),
+                        add_anonymity_revoker: all_keys.clone(),
+                        add_identity_provider: all_keys.clone(),
+                    },
+                    cooldown_parameters: all_keys.clone(),
+                    time_parameters:     all_keys,
+                },
+            },
+        })
+    }
+
+    /// The energy of a contract update, excluding the base cost of the
+    /// transaction.
+    fn update_energy(item: &BlockItem<EncodedPayload>) -> anyhow::Result<Energy> {
+        let BlockItem::AccountTransaction(tx) = item else {
+            anyhow::bail!("Expected an account transaction.");
+        };
+        let size = TRANSACTION_HEADER_SIZE + u64::from(u32::from(tx.payload.size()));
+        Ok(Energy::from(
+            tx.header.energy_amount.energy - cost::base_cost(size, 1).energy,
+        ))
+    }
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("generator-{}-{name}", std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn ccd_generator_cycles_receivers() -> anyhow::Result<()> {
+        let client = fake_node().await?;
+        let mut generator =
+            CcdGenerator::instantiate(client, common_args(None), ccd_args(None, None)).await?;
+        let payloads = generate_payloads(&mut generator, 4)?;
+        let expected_receivers = [2, 3, 4, 2];
+        for (i, (nonce, payload)) in payloads.into_iter().enumerate() {
+            assert_eq!(nonce, Nonce {
+                nonce: 5 + i as u64,
+            });
+            match payload {
+                Payload::Transfer { to_address, amount } => {
+                    assert_eq!(to_address, AccountAddress([expected_receivers[i]; 32]));
+                    assert_eq!(amount, Amount::from_micro_ccd(1));
+                }
+                _ => panic!("Expected a transfer, got {payload:?}."),
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn ccd_generator_adds_memos() -> anyhow::Result<()> {
+        let client = fake_node().await?;
+        let mut generator =
+            CcdGenerator::instantiate(client, common_args(None), ccd_args(None, Some(10))).await?;
+        for (_, payload) in generate_payloads(&mut generator, 2)? {
+            match payload {
+                Payload::TransferWithMemo { memo, .. } => assert_eq!(memo.as_ref().len(), 10),
+                _ => panic!("Expected a transfer with memo, got {payload:?}."),
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn register_data_generator_uses_size() -> anyhow::Result<()> {
+        let client = fake_node().await?;
+        let mut generator =
+            RegisterDataGenerator::instantiate(client, common_args(None), RegisterDataArgs {
+                size: 50,
+            })
+            .await?;
+        for (i, (nonce, payload)) in generate_payloads(&mut generator, 3)?
+            .into_iter()
+            .enumerate()
+        {
+            assert_eq!(nonce, Nonce {
+                nonce: 5 + i as u64,
+            });
+            match payload {
+                Payload::RegisterData { data } => assert_eq!(data.as_ref().len(), 50),
+                _ => panic!("Expected registered data, got {payload:?}."),
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn seeded_generators_are_reproducible() -> anyhow::Result<()> {
+        let client = fake_node().await?;
+        let mut payloads = Vec::new();
+        for _ in 0..2 {
+            let mut ccd = CcdGenerator::instantiate(
+                client.clone(),
+                common_args(Some(42)),
+                ccd_args(Some(Mode::Random), Some(8)),
+            )
+            .await?;
+            let mut register_data = RegisterDataGenerator::instantiate(
+                client.clone(),
+                common_args(Some(42)),
+                RegisterDataArgs { size: 16 },
+            )
+            .await?;
+            let mut run = generate_payloads(&mut ccd, 10)?;
+            run.extend(generate_payloads(&mut register_data, 10)?);
+            let encoded: Vec<_> = run
+                .into_iter()
+                .map(|(_, payload)| Vec::<u8>::from(payload.encode()))
+                .collect();
+            payloads.push(encoded);
+        }
+        assert_eq!(payloads[0], payloads[1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn mint_generator_continues_existing_instance() -> anyhow::Result<()> {
+        let (client, _) =
+            fake_nft_node(AccountAddress([1; 32]), 3, Arc::new(AtomicU64::new(1000))).await?;
+        // Re-estimating on every transaction must not block the single-threaded
+        // runtime.
+        let args = CommonArgs {
+            reestimate_interval: 0,
+            ..common_args(None)
+        };
+        let mut generator =
+            MintCis2Generator::instantiate(client, args, existing_instance()).await?;
+        for i in 0..3 {
+            let item = generator.generate()?;
+            assert_eq!(update_energy(&item)?, Energy::from(1200));
+            let BlockItem::AccountTransaction(tx) = item else {
+                anyhow::bail!("Expected an account transaction.");
+            };
+            assert_eq!(tx.header.nonce, Nonce { nonce: 1 + i });
+            match tx.payload.decode()? {
+                Payload::Update { payload } => {
+                    let id = 3 + i as u32;
+                    assert_eq!(
+                        payload.receive_name.as_receive_name().entrypoint_name(),
+                        "mint"
+                    );
+                    assert_eq!(
+                        payload.message,
+                        mint_nft_param(AccountAddress([1; 32]), id..id + 1)?
+                    );
+                }
+                payload => panic!("Expected a contract update, got {payload:?}."),
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn energy_estimates_are_refreshed_in_the_background() -> anyhow::Result<()> {
+        let energy = Arc::new(AtomicU64::new(1000));
+        let (client, _) = fake_nft_node(AccountAddress([1; 32]), 0, energy.clone()).await?;
+        let args = CommonArgs {
+            reestimate_interval: 0,
+            ..common_args(None)
+        };
+        let mut generator =
+            MintCis2Generator::instantiate(client, args, existing_instance()).await?;
+        energy.store(2000, Ordering::SeqCst);
+        // The outdated estimate is used until the refresh is done.
+        assert_eq!(update_energy(&generator.generate()?)?, Energy::from(1200));
+        let mut refreshed = false;
+        for _ in 0..100 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            if update_energy(&generator.generate()?)? == Energy::from(2400) {
+                refreshed = true;
+                break;
+            }
+        }
+        assert!(refreshed, "The estimate was not refreshed.");
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mint_generator_rejects_instance_of_other_owner() -> anyhow::Result<()> {
+        let (client, _) =
+            fake_nft_node(AccountAddress([9; 32]), 0, Arc::new(AtomicU64::new(1000))).await?;
+        let error =
+            match MintCis2Generator::instantiate(client, common_args(None), existing_instance())
+                .await
+            {
+                Ok(_) => panic!("The instance of another owner was accepted."),
+                Err(error) => error,
+            };
+        assert!(error.to_string().contains("is owned by"), "Got {error:#}.");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn transfer_cis2_generator_transfers_batches() -> anyhow::Result<()> {
+        let owner = AccountAddress([1; 32]);
+        // The sender already owns a token, so nothing is minted.
+        let invoke = |entrypoint: &str, _: &[u8]| match entrypoint {
+            "cis2_multi.balanceOf" => Invoked::Success {
+                return_value: vec![1, 0, 1],
+                used_energy:  100,
+            },
+            "cis2_multi.transfer" => Invoked::Success {
+                return_value: Vec::new(),
+                used_energy:  1000,
+            },
+            _ => Invoked::Rejected { reason: -1 },
+        };
+        let state = with_instance(
+            sender_state(),
+            TRANSFER_CIS2_MODULE,
+            "init_cis2_multi",
+            owner,
+            invoke,
+        )?;
+        let (client, _) = FakeNode::start(state).await?;
+        let mut generator =
+            TransferCis2Generator::instantiate(client, common_args(None), TransferCis2Args {
+                receivers:  None,
+                instance:   existing_instance(),
+                variant:    Cis2Variant::Transfer,
+                batch_size: 2,
+                operator:   None,
+            })
+            .await?;
+        let payloads = generate_payloads(&mut generator, 3)?;
+        let expected_receivers = [[2, 3], [4, 2], [3, 4]];
+        for (i, ((nonce, payload), receivers)) in
+            payloads.into_iter().zip(expected_receivers).enumerate()
+        {
+            assert_eq!(nonce, Nonce {
+                nonce: 5 + i as u64,
+            });
+            let update = expect_update(payload);
+            assert_eq!(
+                update.receive_name.as_receive_name().entrypoint_name(),
+                "transfer"
+            );
+            let transfers = receivers
+                .into_iter()
+                .map(|receiver| {
+                    Ok(Transfer {
+                        token_id: TokenId::new_u8(0),
+                        amount:   TokenAmount::from(1u32),
+                        from:     Address::Account(owner),
+                        to:       Receiver::Account(AccountAddress([receiver; 32])),
+                        data:     AdditionalData::new(vec![])?,
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?;
+            assert_eq!(
+                update.message,
+                OwnedParameter::from_serial(&TransferParams::new(transfers)?)?
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn wccd_generator_gives_tokens_before_cycling() -> anyhow::Result<()> {
+        let sender = AccountAddress([1; 32]);
+        // Of the accounts of the node, only [3; 32] owns wCCD already.
+        let invoke = |entrypoint: &str, parameter: &[u8]| match entrypoint {
+            "cis2_wCCD.balanceOf" => {
+                // The queries follow their `u16` count, each with the empty token id and
+                // an account address.
+                let mut balances = parameter[..2].to_vec();
+                for query in parameter[2..].chunks(34) {
+                    balances.push(u8::from(query[2..] == [3; 32]));
+                }
+                Invoked::Success {
+                    return_value: balances,
+                    used_energy:  100,
+                }
+            }
+            "cis2_wCCD.wrap" | "cis2_wCCD.transfer" | "cis2_wCCD.unwrap" => Invoked::Success {
+                return_value: Vec::new(),
+                used_energy:  1000,
+            },
+            _ => Invoked::Rejected { reason: -1 },
+        };
+        let state = with_instance(
+            sender_state(),
+            WCCD_MODULE,
+            "init_cis2_wCCD",
+            AccountAddress([9; 32]),
+            invoke,
+        )?;
+        let (client, _) = FakeNode::start(state).await?;
+        let mut generator =
+            WccdGenerator::instantiate(client, common_args(None), existing_instance()).await?;
+
+        let wrap = |to: AccountAddress| -> anyhow::Result<OwnedParameter> {
+            Ok(OwnedParameter::from_serial(&WrapParams {
+                to:   Receiver::Account(to),
+                data: AdditionalData::new(vec![])?,
+            })?)
+        };
+        let transfer = Transfer {
+            token_id: TokenId::new(vec![])?,
+            amount:   TokenAmount::from(1u32),
+            from:     Address::Account(sender),
+            to:       Receiver::Account(sender),
+            data:     AdditionalData::new(vec![])?,
+        };
+        let unwrap = UnwrapParams {
+            amount:   TokenAmount::from(1u32),
+            owner:    Address::Account(sender),
+            receiver: Address::Account(sender),
+            data:     AdditionalData::new(vec![])?,
+        };
+        let one = Amount::from_micro_ccd(1);
+        let expected = [
+            ("wrap", one, wrap(AccountAddress([2; 32]))?),
+            ("wrap", one, wrap(AccountAddress([4; 32]))?),
+            ("wrap", one, wrap(sender)?),
+            (
+                "transfer",
+                Amount::zero(),
+                OwnedParameter::from_serial(&TransferParams::new(vec![transfer])?)?,
+            ),
+            (
+                "unwrap",
+                Amount::zero(),
+                OwnedParameter::from_serial(&unwrap)?,
+            ),
+        ];
+        let payloads = generate_payloads(&mut generator, expected.len())?;
+        for (i, ((nonce, payload), (entrypoint, amount, message))) in
+            payloads.into_iter().zip(expected).enumerate()
+        {
+            assert_eq!(nonce, Nonce {
+                nonce: 5 + i as u64,
+            });
+            let update = expect_update(payload);
+            assert_eq!(
+                update.receive_name.as_receive_name().entrypoint_name(),
+                entrypoint
+            );
+            assert_eq!(update.amount, amount);
+            assert_eq!(update.message, message);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn register_credentials_generator_registers_fresh_holders() -> anyhow::Result<()> {
+        let invoke = |entrypoint: &str, _: &[u8]| match entrypoint {
+            "credential_registry.registerCredential" => Invoked::Success {
+                return_value: Vec::new(),
+                used_energy:  1000,
+            },
+            _ => Invoked::Rejected { reason: -1 },
+        };
+        let state = with_instance(
+            sender_state(),
+            REGISTER_CREDENTIALS_MODULE,
+            "init_credential_registry",
+            AccountAddress([1; 32]),
+            invoke,
+        )?;
+        let (client, _) = FakeNode::start(state).await?;
+        let mut generator = RegisterCredentialsGenerator::instantiate(
+            client,
+            common_args(Some(7)),
+            RegisterCredentialsArgs {
+                instance:  existing_instance(),
+                lifecycle: false,
+            },
+        )
+        .await?;
+        // The generator draws the key of the issuer and the holder used for the
+        // estimate before the holders of the generated transactions.
+        let mut rng = StdRng::seed_from_u64(7);
+        KeyPair::generate(&mut rng);
+        KeyPair::generate(&mut rng);
+        for (i, (nonce, payload)) in generate_payloads(&mut generator, 3)?
+            .into_iter()
+            .enumerate()
+        {
+            assert_eq!(nonce, Nonce {
+                nonce: 5 + i as u64,
+            });
+            let update = expect_update(payload);
+            assert_eq!(
+                update.receive_name.as_receive_name().entrypoint_name(),
+                "registerCredential"
+            );
+            let holder = KeyPair::generate(&mut rng);
+            assert_eq!(
+                update.message,
+                register_credential_param(&holder, false, "https://example.com")?
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn contract_generator_fills_templates_in_turn() -> anyhow::Result<()> {
+        let (client, _) = FakeNode::start(State {
+            invoke: Some(Box::new(|_: &str, _: &[u8]| Invoked::Success {
+                return_value: Vec::new(),
+                used_energy:  1000,
+            })),
+            ..State::default()
+        })
+        .await?;
+        let args = common_args(None);
+        let address = ContractAddress::new(0, 0);
+        let contract = ContractClient::<()>::new(
+            client,
+            address,
+            OwnedContractName::new("init_counter".into())?,
+        );
+        let mut estimator = EnergyEstimator::new(contract, &args);
+        let update = OwnedReceiveName::new("counter.update".into())?;
+        estimator
+            .estimate(&UpdateContractPayload {
+                amount: Amount::zero(),
+                address,
+                receive_name: update.clone(),
+                message: OwnedParameter::empty(),
+            })
+            .await?;
+        let template = serde_json::json!({
+            "sender": "$SENDER",
+            "account": "$ACCOUNT",
+            "counter": "$COUNTER",
+        });
+        let schema = Type::Struct(Fields::Named(vec![
+            ("sender".into(), Type::AccountAddress),
+            ("account".into(), Type::AccountAddress),
+            ("counter".into(), Type::U64),
+        ]));
+        // Instantiating deploys the contract, which the fake node does not support,
+        // so the generator is constructed as `instantiate` would from an entrypoints
+        // file.
+        let mut generator = ContractGenerator {
+            rng: args.rng(),
+            args,
+            estimator,
+            entrypoints: vec![
+                ContractEntrypoint {
+                    receive_name: update,
+                    template:     Some((template, schema)),
+                    energy:       None,
+                    amount:       Amount::zero(),
+                },
+                ContractEntrypoint {
+                    receive_name: OwnedReceiveName::new("counter.reset".into())?,
+                    template:     None,
+                    energy:       Some(Energy::from(500)),
+                    amount:       Amount::from_micro_ccd(3),
+                },
+            ],
+            accounts: vec![AccountAddress([2; 32])],
+            nonce: Nonce { nonce: 5 },
+            count: 0,
+        };
+        for i in 0..4u64 {
+            let item = generator.generate()?;
+            let energy = update_energy(&item)?;
+            let BlockItem::AccountTransaction(tx) = item else {
+                anyhow::bail!("Expected an account transaction.");
+            };
+            assert_eq!(tx.header.nonce, Nonce { nonce: 5 + i });
+            let update = expect_update(tx.payload.decode()?);
+            if i % 2 == 0 {
+                assert_eq!(
+                    update.receive_name.as_receive_name().entrypoint_name(),
+                    "update"
+                );
+                assert_eq!(energy, Energy::from(1200));
+                assert_eq!(update.amount, Amount::zero());
+                let mut message = [[1; 32], [2; 32]].concat();
+                message.extend(i.to_le_bytes());
+                assert_eq!(update.message, OwnedParameter::try_from(message)?);
+            } else {
+                assert_eq!(
+                    update.receive_name.as_receive_name().entrypoint_name(),
+                    "reset"
+                );
+                assert_eq!(energy, Energy::from(500));
+                assert_eq!(update.amount, Amount::from_micro_ccd(3));
+                assert_eq!(update.message, OwnedParameter::empty());
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn state_growth_generator_mints_new_tokens() -> anyhow::Result<()> {
+        let sender = AccountAddress([1; 32]);
+        let (client, state) = fake_nft_node(sender, 3, Arc::new(AtomicU64::new(1000))).await?;
+        let mut generator =
+            StateGrowthGenerator::instantiate(client.clone(), common_args(None), StateGrowthArgs {
+                instance:              existing_instance(),
+                workload:              GrowthWorkload::Tokens,
+                items_per_transaction: 2,
+                payload_size:          1000,
+                report_interval:       3600,
+            })
+            .await?;
+        for (i, (nonce, payload)) in generate_payloads(&mut generator, 2)?
+            .into_iter()
+            .enumerate()
+        {
+            assert_eq!(nonce, Nonce {
+                nonce: 1 + i as u64,
+            });
+            let update = expect_update(payload);
+            assert_eq!(
+                update.receive_name.as_receive_name().entrypoint_name(),
+                "mint"
+            );
+            let first = 3 + 2 * i as u32;
+            assert_eq!(update.message, mint_nft_param(sender, first..first + 2)?);
+        }
+
+        // The report reads the state of the instance and invokes the probe.
+        let invocations = {
+            let mut state = state.lock().expect("Lock is not poisoned.");
+            state
+                .instance_states
+                .insert(ContractAddress::new(0, 0), vec![(vec![0], vec![1, 2])]);
+            state.invocations
+        };
+        let probe = UpdateContractPayload {
+            amount:       Amount::zero(),
+            address:      ContractAddress::new(0, 0),
+            receive_name: OwnedReceiveName::new("cis2_nft.mint".into())?,
+            message:      mint_nft_param(sender, u32::MAX - 2..u32::MAX)?,
+        };
+        report_state(client, sender, &probe).await?;
+        assert_eq!(
+            state.lock().expect("Lock is not poisoned.").invocations,
+            invocations + 1
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn encrypted_transfer_generator_tracks_encrypted_balance() -> anyhow::Result<()> {
+        let global = GlobalContext::<ArCurve>::generate("genesis".into());
+        let mut rng = StdRng::seed_from_u64(0);
+        // The sender followed by the accounts of the node.
+        let addresses = [1, 2, 3, 4].map(|i| AccountAddress([i; 32]));
+        let secret_keys: Vec<_> = addresses
+            .iter()
+            .map(|_| elgamal::SecretKey::generate(global.elgamal_generator(), &mut rng))
+            .collect();
+        let public_key = |i: usize| elgamal::PublicKey::from(&secret_keys[i]);
+        let account_infos = addresses
+            .iter()
+            .zip(&secret_keys)
+            .enumerate()
+            .map(|(i, (&address, secret_key))| {
+                (
+                    address,
+                    account_info(&global, address, i as u64, secret_key),
+                )
+            })
+            .collect();
+        let (client, _) = FakeNode::start(State {
+            account_infos,
+            cryptographic_parameters: Some(global.clone()),
+            ..sender_state()
+        })
+        .await?;
+        let key_file = temp_file("encryption-key.json");
+        std::fs::write(
+            &key_file,
+            serde_json::json!({ "encryptionSecretKey": secret_keys[0] }).to_string(),
+        )?;
+        let generator = EncryptedTransferGenerator::instantiate(
+            client,
+            common_args(None),
+            EncryptedTransferArgs {
+                receivers:      None,
+                encryption_key: key_file.clone(),
+                amount:         Amount::from_micro_ccd(5),
+            },
+        )
+        .await;
+        std::fs::remove_file(&key_file)?;
+        let mut generator = generator?;
+
+        let table =
+            elgamal::BabyStepGiantStep::new(global.encryption_in_exponent_generator(), 1 << 8);
+        let mut balance =
+            encrypted_transfers::encrypt_amount_with_fixed_randomness(&global, Amount::zero());
+        for (i, (nonce, payload)) in generate_payloads(&mut generator, 6)?
+            .into_iter()
+            .enumerate()
+        {
+            assert_eq!(nonce, Nonce {
+                nonce: 5 + i as u64,
+            });
+            match (i % 3, payload) {
+                (0, Payload::TransferToEncrypted { amount }) => {
+                    assert_eq!(amount, Amount::from_micro_ccd(10));
+                    let encrypted =
+                        encrypted_transfers::encrypt_amount_with_fixed_randomness(&global, amount);
+                    balance = encrypted_transfers::aggregate(&balance, &encrypted);
+                }
+                (1, Payload::EncryptedAmountTransfer { to, data }) => {
+                    let receiver = 1 + i / 3;
+                    assert_eq!(to, addresses[receiver]);
+                    assert!(encrypted_transfers::verify_transfer_data(
+                        &global,
+                        &public_key(receiver),
+                        &public_key(0),
+                        &balance,
+                        &data
+                    ));
+                    assert_eq!(
+                        encrypted_transfers::decrypt_amount(
+                            &table,
+                            &secret_keys[receiver],
+                            &data.transfer_amount
+                        ),
+                        Amount::from_micro_ccd(5)
+                    );
+                    balance = data.remaining_amount;
+                }
+                (2, Payload::TransferToPublic { data }) => {
+                    assert!(encrypted_transfers::verify_sec_to_pub_transfer_data(
+                        &global,
+                        &public_key(0),
+                        &balance,
+                        &data
+                    ));
+                    assert_eq!(data.transfer_amount, Amount::from_micro_ccd(5));
+                    balance = data.remaining_amount;
+                }
+                (_, payload) => panic!("Unexpected payload {payload:?} at position {i}."),
+            }
+        }
+        // Each cycle leaves the encrypted balance as it was.
+        assert_eq!(
+            encrypted_transfers::decrypt_amount(&table, &secret_keys[0], &balance),
+            Amount::zero()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn staking_generator_adds_and_changes_stake() -> anyhow::Result<()> {
+        let global = GlobalContext::<ArCurve>::generate("genesis".into());
+        let mut rng = StdRng::seed_from_u64(0);
+        let dir = temp_file("staking");
+        std::fs::create_dir_all(&dir)?;
+        // The key files are read in the order of their names, and the first account
+        // acts as baker.
+        let (baker, delegator) = (AccountAddress([10; 32]), AccountAddress([11; 32]));
+        let mut account_infos = BTreeMap::new();
+        for (name, address, index) in [("a.json", baker, 10), ("b.json", delegator, 11)] {
+            let keys = AccountKeys::singleton(&mut rng);
+            std::fs::write(
+                dir.join(name),
+                serde_json::json!({ "address": address, "accountKeys": keys }).to_string(),
+            )?;
+            let secret_key = elgamal::SecretKey::generate(global.elgamal_generator(), &mut rng);
+            account_infos.insert(address, account_info(&global, address, index, &secret_key));
+        }
+        let rates = CommissionRates {
+            finalization: AmountFraction::new_unchecked(100_000),
+            baking:       AmountFraction::new_unchecked(10_000),
+            transaction:  AmountFraction::new_unchecked(10_000),
+        };
+        let pool = BakerPoolStatus {
+            baker_id:                   BakerId::from(AccountIndex { index: 9 }),
+            baker_address:              AccountAddress([9; 32]),
+            baker_equity_capital:       Amount::from_ccd(1000),
+            delegated_capital:          Amount::zero(),
+            delegated_capital_cap:      Amount::from_ccd(2000),
+            pool_info:                  BakerPoolInfo {
+                open_status:      OpenStatus::OpenForAll,
+                metadata_url:     UrlText::try_from(String::new()).expect("Empty URL is valid."),
+                commission_rates: rates,
+            },
+            baker_stake_pending_change: PoolPendingChange::NoChange,
+            current_payday_status:      None,
+            all_pool_total_capital:     Amount::from_ccd(1000),
+        };
+        let (client, _) = FakeNode::start(State {
+            account_infos,
+            chain_parameters: Some(chain_parameters(Vec::new())?),
+            epoch_duration: chrono::Duration::hours(1),
+            pools: vec![pool],
+            ..State::default()
+        })
+        .await?;
+        let generator = StakingGenerator::instantiate(client, common_args(None), StakingArgs {
+            accounts: dir.clone(),
+            bakers:   1,
+            amount:   Amount::from_ccd(1),
+        })
+        .await;
+        std::fs::remove_dir_all(&dir)?;
+        let mut generator = generator?;
+        let mut next = || -> anyhow::Result<(AccountAddress, Nonce, Payload)> {
+            match generator.generate()? {
+                BlockItem::AccountTransaction(tx) => {
+                    Ok((tx.header.sender, tx.header.nonce, tx.payload.decode()?))
+                }
+                _ => anyhow::bail!("Expected an account transaction."),
+            }
+        };
+
+        // The baker is added with the minimum equity capital plus the amount, and
+        // with the maximal commissions.
+        let max_commission = Some(AmountFraction::new_unchecked(10_000));
+        match next()? {
+            (sender, Nonce { nonce: 1 }, Payload::ConfigureBaker { data }) if sender == baker => {
+                assert_eq!(data.capital, Some(Amount::from_ccd(101)));
+                assert_eq!(data.restake_earnings, Some(true));
+                assert_eq!(data.open_for_delegation, Some(OpenStatus::OpenForAll));
+                assert!(data.keys_with_proofs.is_some());
+                assert_eq!(data.transaction_fee_commission, max_commission);
+                assert_eq!(data.baking_reward_commission, max_commission);
+                assert_eq!(data.finalization_reward_commission, max_commission);
+            }
+            transaction => panic!("Expected the baker to be added, got {transaction:?}."),
+        }
+        // The targets are passive delegation and the open pool, and the new pool is
+        // not open yet.
+        match next()? {
+            (sender, Nonce { nonce: 1 }, Payload::ConfigureDelegation { data })
+                if sender == delegator =>
+            {
+                assert_eq!(data.capital, Some(Amount::from_ccd(1)));
+                assert_eq!(data.restake_earnings, Some(true));
+                assert_eq!(data.delegation_target, Some(DelegationTarget::Passive));
+            }
+            transaction => panic!("Expected a delegation, got {transaction:?}."),
+        }
+        match next()? {
+            (sender, Nonce { nonce: 2 }, Payload::ConfigureBaker { data }) if sender == baker => {
+                assert_eq!(data.capital, Some(Amount::from_ccd(100)));
+                assert_eq!(data.restake_earnings, None);
+            }
+            transaction => panic!("Expected the stake to be reduced, got {transaction:?}."),
+        }
+        match next()? {
+            (sender, Nonce { nonce: 2 }, Payload::ConfigureDelegation { data })
+                if sender == delegator =>
+            {
+                assert_eq!(data.capital, None);
+                assert_eq!(data.restake_earnings, Some(false));
+            }
+            transaction => panic!("Expected restaking to be toggled, got {transaction:?}."),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn create_accounts_generator_skips_used_credentials() -> anyhow::Result<()> {
+        let global = GlobalContext::<ArCurve>::generate("genesis".into());
+        let mut rng = StdRng::seed_from_u64(0);
+        let ip_secret_key = ps_sig::SecretKey::<IpPairing>::generate(10, &mut rng);
+        let description = |name: &str| Description {
+            name:        name.into(),
+            url:         format!("https://{name}.example.com"),
+            description: name.into(),
+        };
+        let ip_info = IpInfo {
+            ip_identity:       IpIdentity(0),
+            ip_description:    description("ip"),
+            ip_verify_key:     ps_sig::PublicKey::from(&ip_secret_key),
+            ip_cdi_verify_key: KeyPair::generate(&mut rng).public(),
+        };
+        let ar_info = ArInfo {
+            ar_identity:    ArIdentity::try_from(1).expect("The identity is not zero."),
+            ar_description: description("ar"),
+            ar_public_key:  elgamal::PublicKey::from(&elgamal::SecretKey::generate(
+                global.elgamal_generator(),
+                &mut rng,
+            )),
+        };
+        let ars = [(ar_info.ar_identity, ar_info.clone())].into();
+
+        // Issue an identity from the seed phrase, as a wallet would.
+        let seed_phrase = [["abandon"; 11].as_slice(), &["about"]].concat().join(" ");
+        let wallet = ConcordiumHdWallet {
+            seed: words_to_seed(&seed_phrase),
+            net:  Net::Testnet,
+        };
+        let id_use_data = IdObjectUseData {
+            aci:        AccCredentialInfo {
+                cred_holder_info: CredentialHolderInfo {
+                    id_cred: IdCredentials {
+                        id_cred_sec: PedersenValue::new(wallet.get_id_cred_sec(0, 0)?),
+                    },
+                },
+                prf_key:          wallet.get_prf_key(0, 0)?,
+            },
+            randomness: wallet.get_blinding_randomness(0, 0)?,
+        };
+        let context = IpContext::new(&ip_info, &ars, &global);
+        let (pio, _) = generate_pio_v1(&context, Threshold(1), &id_use_data)
+            .context("Could not generate the identity request.")?;
+        let alist = AttributeList {
+            valid_to:     YearMonth::new(2100, 1).context("Invalid month.")?,
+            created_at:   YearMonth::new(2024, 1).context("Invalid month.")?,
+            max_accounts: 3,
+            alist:        BTreeMap::new(),
+            _phantom:     Default::default(),
+        };
+        let signature = verify_credentials_v1(&pio, context, &alist, &ip_secret_key)
+            .map_err(|reason| anyhow::anyhow!("Could not sign the identity: {reason:?}"))?;
+        let id_object: IdentityObjectV1<IpPairing, ArCurve, AttributeKind> = IdentityObjectV1 {
+            pre_identity_object: pio,
+            alist,
+            signature,
+        };
+
+        // The account of the first credential already exists.
+        let cred_id = |index: u8| -> anyhow::Result<CredentialRegistrationID> {
+            let exponent = id_use_data.aci.prf_key.prf_exponent(index)?;
+            Ok(CredentialRegistrationID::from_exponent(&global, exponent))
+        };
+        let existing = AccountAddress([5; 32]);
+        let secret_key = elgamal::SecretKey::generate(global.elgamal_generator(), &mut rng);
+        let (client, _) = FakeNode::start(State {
+            account_infos: [(existing, account_info(&global, existing, 5, &secret_key))].into(),
+            credentials: vec![(cred_id(0)?, existing)],
+            cryptographic_parameters: Some(global.clone()),
+            identity_providers: vec![ip_info.clone()],
+            anonymity_revokers: vec![ar_info],
+            ..State::default()
+        })
+        .await?;
+        let (identity, seed_phrase_file) = (temp_file("identity.json"), temp_file("seed-phrase"));
+        std::fs::write(
+            &identity,
+            serde_json::to_string(&Versioned::new(VERSION_0, id_object))?,
+        )?;
+        std::fs::write(&seed_phrase_file, &seed_phrase)?;
+        let generator = CreateAccountsGenerator::instantiate(
+            client,
+            CreateAccountsArgs {
+                identity:       identity.clone(),
+                seed_phrase:    seed_phrase_file.clone(),
+                ip_index:       0,
+                identity_index: 0,
+                mainnet:        false,
+            },
+            100,
+        )
+        .await;
+        std::fs::remove_file(&identity)?;
+        std::fs::remove_file(&seed_phrase_file)?;
+        let mut generator = generator?;
+
+        for index in 1..3 {
+            let BlockItem::CredentialDeployment(message) = generator.generate()? else {
+                anyhow::bail!("Expected a credential deployment.");
+            };
+            let AccountCredential::Normal { cdi } = &message.credential else {
+                anyhow::bail!("Expected a normal credential.");
+            };
+            assert_eq!(
+                CredentialRegistrationID::new(cdi.values.cred_id),
+                cred_id(index)?
+            );
+            verify_cdi(
+                &global,
+                &ip_info,
+                &ars,
+                cdi,
+                &Either::Left(message.message_expiry),
+            )
+            .map_err(|e| anyhow::anyhow!("Invalid credential: {e:?}"))?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn chain_updates_generator_alternates_updates() -> anyhow::Result<()> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let keys: Vec<_> = (0..2).map(|_| UpdateKeyPair::generate(&mut rng)).collect();
+        let dir = temp_file("chain-updates");
+        std::fs::create_dir_all(&dir)?;
+        // Only the second key is given, which meets the threshold on its own.
+        std::fs::write(
+            dir.join("level2-key-1.json"),
+            serde_json::to_string(&keys[1])?,
+        )?;
+        let parameters = chain_parameters(keys.iter().map(UpdatePublicKey::from).collect())?;
+        let seq = |number| UpdateSequenceNumber { number };
+        let update_sequence_numbers = NextUpdateSequenceNumbers {
+            root_keys: seq(1),
+            level_1_keys: seq(1),
+            level_2_keys: seq(1),
+            protocol: seq(1),
+            election_difficulty: seq(1),
+            euro_per_energy: seq(7),
+            micro_ccd_per_euro: seq(3),
+            foundation_account: seq(1),
+            mint_distribution: seq(1),
+            transaction_fee_distribution: seq(1),
+            gas_rewards: seq(1),
+            pool_parameters: seq(1),
+            add_anonymity_revoker: seq(1),
+            add_identity_provider: seq(1),
+            cooldown_parameters: seq(1),
+            time_parameters: seq(1),
+            timeout_parameters: seq(1),
+            min_block_time: seq(1),
+            block_energy_limit: seq(1),
+            finalization_committee_parameters: seq(1),
+        };
+        let (client, _) = FakeNode::start(State {
+            chain_parameters: Some(parameters.clone()),
+            update_sequence_numbers: Some(update_sequence_numbers),
+            ..State::default()
+        })
+        .await?;
+        let generator = ChainUpdatesGenerator::instantiate(
+            client,
+            ChainUpdatesArgs {
+                keys:    dir.clone(),
+                updates: vec![UpdateKind::MicroCcdPerEuro, UpdateKind::EuroPerEnergy],
+            },
+            100,
+        )
+        .await;
+        std::fs::remove_dir_all(&dir)?;
+        let mut generator = generator?;
+
+        for (i, number) in [3, 7, 4, 8].into_iter().enumerate() {
+            let BlockItem::UpdateInstruction(instruction) = generator.generate()? else {
+                anyhow::bail!("Expected an update instruction.");
+            };
+            assert_eq!(instruction.header.seq_number, seq(number));
+            assert_eq!(
+                instruction
+                    .signatures
+                    .signatures
+                    .keys()
+                    .map(|index| index.index)
+                    .collect::<Vec<_>>(),
+                [1]
+            );
+            match (i % 2, instruction.payload) {
+                (0, UpdatePayload::MicroGTUPerEuro(rate)) => {
+                    assert_eq!(rate, parameters.micro_ccd_per_euro)
+                }
+                (1, UpdatePayload::EuroPerEnergy(rate)) => {
+                    assert_eq!(rate, parameters.euro_per_energy)
+                }
+                (_, payload) => panic!("Unexpected update {payload:?} at position {i}."),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn staking_pools_follow_own_changes_and_bounds() {
+        let now = chrono::Utc::now();
+        let ccd = Amount::from_ccd;
+        let baker_id = |index| BakerId {
+            id: AccountIndex { index },
+        };
+        let (other, own) = (baker_id(1), baker_id(7));
+        let mut pools = Pools {
+            pools:          [(other, Pool {
+                equity:    ccd(1000),
+                delegated: Amount::zero(),
+                open_from: Some(now),
+            })]
+            .into(),
+            // Including stake that is not in the tracked pools.
+            total:          ccd(4000),
+            leverage_bound: LeverageFactor {
+                numerator:   3,
+                denominator: 1,
+            },
+            capital_bound:  CapitalBound {
+                bound: AmountFraction::new_unchecked(25_000),
+            },
+            delay:          chrono::Duration::seconds(100),
+            moving:         Vec::new(),
+        };
+        let later = now + chrono::Duration::seconds(100);
+
+        // A new pool is a target once the transaction adding it has expired.
+        let baker = Stake::Baker {
+            stake:   ccd(100),
+            restake: true,
+        };
+        pools.update(own, &Stake::None, &baker, now);
+        assert_eq!(pools.targets(ccd(1), now), [
+            DelegationTarget::Passive,
+            DelegationTarget::from(other)
+        ]);
+        assert_eq!(pools.targets(ccd(1), later), [
+            DelegationTarget::Passive,
+            DelegationTarget::from(other),
+            DelegationTarget::from(own)
+        ]);
+        // The leverage bound allows 200 CCD for the new pool, which is less than
+        // allowed by the capital bound. For the other pool the capital bound allows
+        // 3100 / 3 - 1000 CCD.
+        assert_eq!(pools.room(&pools.pools[&own]), ccd(200));
+        assert_eq!(
+            pools.room(&pools.pools[&other]),
+            Amount::from_micro_ccd(33_333_333)
+        );
+        assert_eq!(pools.targets(ccd(100), later), [
+            DelegationTarget::Passive,
+            DelegationTarget::from(own)
+        ]);
+
+        let delegator = Stake::Delegator {
+            stake:   ccd(150),
+            restake: true,
+            target:  DelegationTarget::from(own),
+        };
+        pools.update(baker_id(8), &Stake::None, &delegator, now);
+        assert!(pools.has_room(&DelegationTarget::from(own), ccd(50)));
+        assert!(!pools.has_room(&DelegationTarget::from(own), ccd(51)));
+
+        // A delegation that moves to passive delegation is still counted against
+        // the pool it left until the next payday.
+        let moved = Stake::Delegator {
+            stake:   ccd(150),
+            restake: true,
+            target:  DelegationTarget::Passive,
+        };
+        pools.move_delegation(&DelegationTarget::from(own), ccd(150), later);
+        pools.update(baker_id(8), &delegator, &moved, now);
+        assert!(!pools.has_room(&DelegationTarget::from(own), ccd(51)));
+        pools.settle(later);
+        assert!(pools.has_room(&DelegationTarget::from(own), ccd(200)));
+        pools.update(baker_id(8), &moved, &delegator, now);
+
+        // A pool whose removal is pending is not a target, and it is removed once
+        // the removal takes effect.
+        pools.close(own);
+        assert_eq!(pools.targets(ccd(1), later), [
+            DelegationTarget::Passive,
+            DelegationTarget::from(other)
+        ]);
+        pools.update(own, &baker, &Stake::None, later);
+        assert!(!pools.pools.contains_key(&own));
+        assert_eq!(pools.total, ccd(4150));
+    }
+
+    #[tokio::test]
+    async fn pipeline_does_not_wait_for_previous_submission_of_sender() -> anyhow::Result<()> {
+        let (client, state) = FakeNode::start(State {
+            accounts: vec![AccountAddress([2; 32])],
+            send_delay: std::time::Duration::from_millis(200),
+            ..State::default()
+        })
+        .await?;
+        let generator =
+            CcdGenerator::instantiate(client.clone(), common_args(None), ccd_args(None, None))
+                .await?;
+        let submitter = Submitter::new(vec![client], Distribution::RoundRobin)?;
+        generate_transactions(submitter, generator, 1000, 4, None, Some(Limit::Count(8))).await?;
+        let state = state.lock().expect("Lock is not poisoned.");
+        assert_eq!(state.submitted, 8);
+        assert_eq!(state.max_concurrent_sends, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn chain_update_keys_meet_threshold() -> anyhow::Result<()> {
+        let dir = temp_file("update-keys");
+        std::fs::create_dir_all(&dir)?;
+        let mut rng = StdRng::seed_from_u64(0);
+        for index in [0, 2, 3] {
+            let key = UpdateKeyPair::generate(&mut rng);
+            std::fs::write(
+                dir.join(format!("level2-key-{index}.json")),
+                serde_json::to_string(&key)?,
+            )?;
+        }
+        std::fs::write(dir.join("governance-keys.json"), "{}")?;
+        let keys = read_level2_keys(&dir);
+        std::fs::remove_dir_all(&dir)?;
+        let keys = keys?;
+        assert_eq!(keys.keys().map(|i| i.index).collect::<Vec<_>>(), [0, 2, 3]);
+
+        let access_structure = |indices: &[u16], threshold: u16| AccessStructure {
+            authorized_keys: indices
+                .iter()
+                .map(|&index| UpdateKeysIndex { index })
+                .collect(),
+            threshold:       threshold.try_into().expect("The threshold is not zero."),
+        };
+        let signer = signer_for(
+            &keys,
+            &access_structure(&[1, 2, 3], 2),
+            UpdateKind::GasRewards,
+        )?;
+        assert_eq!(signer.keys().map(|i| i.index).collect::<Vec<_>>(), [2, 3]);
+        assert!(signer_for(&keys, &access_structure(&[0, 1], 2), UpdateKind::GasRewards).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dry_run_writes_replayable_file() -> anyhow::Result<()> {
+        let client = fake_node().await?;
+        let mut generator =
+            CcdGenerator::instantiate(client, common_args(None), ccd_args(None, None)).await?;
+        let path = temp_file("dry-run.bin");
+        let mut submitter = Submitter::dry_run(Some(&path), OutputFormat::Binary)?;
+        let mut hashes = Vec::new();
+        for _ in 0..3 {
+            hashes.push(submitter.send(&generator.generate()?).await?);
+        }
+        let items = read_signed_transactions(&path)?;
+        std::fs::remove_file(&path)?;
+        let read_hashes: Vec<_> = items.iter().map(|item| item.hash()).collect();
+        assert_eq!(read_hashes, hashes);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dry_run_writes_json_lines() -> anyhow::Result<()> {
+        let client = fake_node().await?;
+        let mut generator =
+            RegisterDataGenerator::instantiate(client, common_args(None), RegisterDataArgs {
+                size: 4,
+            })
+            .await?;
+        let path = temp_file("dry-run.json");
+        let mut submitter = Submitter::dry_run(Some(&path), OutputFormat::Json)?;
+        let mut hashes = Vec::new();
+        for _ in 0..2 {
+            hashes.push(submitter.send(&generator.generate()?).await?);
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+        assert_eq!(lines.len(), 2);
+        for (line, hash) in lines.iter().zip(hashes) {
+            assert_eq!(line["type"], "accountTransaction");
+            assert_eq!(line["hash"], hash.to_string());
+            assert!(line["payload"].get("registerData").is_some(), "Got {line}.");
+        }
+        Ok(())
     }
 }
@@ -1,13 +1,24 @@
 use anyhow::Context;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use concordium_rust_sdk::{endpoints::Endpoint, types::WalletAccount, v2};
 use generator::{
-    generate_transactions, CcdGenerator, CommonArgs, MintCis2Generator,
-    RegisterCredentialsGenerator, TransferCis2Generator, WccdGenerator,
+    bootstrap, generate_transactions, invoke_instances, replay_transactions, sign_transactions,
+    CcdGenerator, ChainUpdatesGenerator, CommonArgs, ContractGenerator, CreateAccountsGenerator,
+    EncryptedTransferGenerator, Generate, Limit, MintCis2Generator, RegisterCredentialsGenerator,
+    RegisterDataGenerator, StakingGenerator, StateGrowthGenerator, TransferCis2Generator,
+    WccdGenerator,
 };
+use metrics::Metrics;
+use scenario::{Scenario, ScenarioRunner};
 use std::path::PathBuf;
+use submitter::{Distribution, OutputFormat, Submitter};
 
+#[cfg(test)]
+mod fake_node;
 mod generator;
+mod metrics;
+mod scenario;
+mod submitter;
 
 #[derive(clap::Parser, Debug)]
 #[clap(author, version, about)]
@@ -15,24 +26,89 @@ mod generator;
 struct App {
     #[clap(
         long = "node",
-        help = "GRPC interface of the node.",
+        help = "GRPC interface of the node. Can be given multiple times to submit transactions to \
+                several nodes, in which case the first node is used for all queries.",
         default_value = "http://localhost:20000"
     )]
-    endpoint: Endpoint,
-    #[clap(long = "sender", help = "Path to file containing sender keys.")]
-    account:  PathBuf,
+    endpoints:           Vec<Endpoint>,
+    #[clap(
+        long = "distribution",
+        help = "How transactions are distributed between the nodes given by `--node`. One of \
+                `round-robin`, `hash` (by transaction hash), `sender` (by sender account), or \
+                `broadcast` (every transaction is sent to all nodes).",
+        default_value = "round-robin"
+    )]
+    distribution:        Distribution,
+    #[clap(
+        long = "sender",
+        help = "Path to file containing sender keys. Required by all commands except `replay`, \
+                `invoke`, `create-accounts`, `chain-updates`, and `bootstrap` without `--fund`."
+    )]
+    account:             Option<PathBuf>,
     #[clap(
         long = "tps",
         help = "Transactions to send per second.",
         default_value = "1"
     )]
-    tps:      u16,
+    tps:                 u16,
+    #[clap(
+        long = "max-in-flight",
+        help = "Maximum number of submissions that are in flight at the same time. Transactions \
+                of the same sender are sent in nonce order.",
+        default_value = "16"
+    )]
+    max_in_flight:       usize,
     #[clap(
         long = "expiry",
         help = "Expiry of transactions in seconds.",
         default_value = "7200"
     )]
-    expiry:   u32,
+    expiry:              u32,
+    #[clap(
+        long = "energy-margin",
+        help = "Safety margin in percent that is added to the energy of contract transactions \
+                estimated by dry runs.",
+        default_value = "20"
+    )]
+    energy_margin:       u64,
+    #[clap(
+        long = "reestimate-interval",
+        help = "Interval in seconds after which the energy of contract updates is estimated again.",
+        default_value = "60"
+    )]
+    reestimate_interval: u64,
+    #[clap(
+        long = "seed",
+        help = "Seed for all randomness of the generator, such as the choice of receivers and \
+                generated keys. Given the same chain state, runs with the same seed generate the \
+                same payloads."
+    )]
+    seed:                Option<u64>,
+    #[clap(
+        long = "metrics-address",
+        help = "If set, serve Prometheus metrics of the submitted transactions at `/metrics` on \
+                the given address."
+    )]
+    metrics_address:     Option<std::net::SocketAddr>,
+    #[clap(
+        long = "dry-run",
+        help = "Do not submit the generated transactions to the node. Setup transactions of the \
+                generators are still sent."
+    )]
+    dry_run:             bool,
+    #[clap(
+        long = "output",
+        help = "With `--dry-run`, write the transactions to the given file.",
+        requires = "dry_run"
+    )]
+    output:              Option<PathBuf>,
+    #[clap(
+        long = "output-format",
+        help = "Format of the file given by `--output`. Either `binary`, the format written by \
+                `sign` that can be submitted using `replay`, or `json`, one JSON object per line.",
+        default_value = "binary"
+    )]
+    output_format:       OutputFormat,
 
     #[command(subcommand)]
     command: Command,
@@ -40,74 +116,434 @@ struct App {
 
 #[derive(Debug, Subcommand)]
 enum Command {
-    /// Send CCD to a list of receivers.
+    #[command(flatten)]
+    Generate(GeneratorCommand),
+    /// Generate and sign transactions using one of the generators, and write
+    /// them to a file instead of sending them to the node. The file can later
+    /// be submitted using `replay`.
+    Sign {
+        #[arg(
+            long = "out",
+            help = "Path of the file to write the signed transactions to."
+        )]
+        out:       PathBuf,
+        #[arg(long = "count", help = "Number of transactions to sign.")]
+        count:     u64,
+        #[command(subcommand)]
+        generator: GeneratorCommand,
+    },
+    /// Submit transactions that were pre-signed using `sign`.
+    Replay(generator::ReplayArgs),
+    /// Invoke view entrypoints of a CIS-2 contract at the given rate, without
+    /// submitting transactions, and report the latency of the invocations.
+    Invoke(generator::InvokeArgs),
+    /// Create accounts from an identity to be used as receivers and senders by
+    /// later runs, and optionally fund them from the sender.
+    Bootstrap(generator::BootstrapArgs),
+    /// Run a scenario of setup steps, load phases using the generators, and
+    /// assertions about the resulting state, described in a JSON file.
+    Scenario {
+        #[arg(
+            long = "file",
+            help = "Path to the scenario file. See the README for the format."
+        )]
+        file: PathBuf,
+    },
+}
+
+/// The generator of a phase of a scenario, parsed from the arguments given in
+/// the scenario file.
+#[derive(clap::Parser, Debug)]
+struct PhaseCommand {
+    #[command(subcommand)]
+    generator: GeneratorCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum GeneratorCommand {
+    /// Send CCD to a list of receivers, optionally with a memo or a release
+    /// schedule.
     Ccd(generator::CcdArgs),
+    /// Register data of a given size.
+    RegisterData(generator::RegisterDataArgs),
+    /// Move CCD between the public and encrypted balance of the sender, and
+    /// make encrypted transfers to a list of receivers.
+    EncryptedTransfer(generator::EncryptedTransferArgs),
+    /// Add, change and remove stake of a pool of accounts, as bakers and as
+    /// delegators.
+    Staking(generator::StakingArgs),
+    /// Create accounts by deploying credentials derived from an identity.
+    CreateAccounts(generator::CreateAccountsArgs),
     /// Mint CIS-2 NFT tokens.
-    MintNfts,
+    MintNfts(generator::InstanceArgs),
     /// Transfer CIS-2 tokens to a list of receivers.
     TransferCis2(generator::TransferCis2Args),
     /// Wrap, unwrap, and transfer WCCD tokens. First, wCCD are minted for every
     /// account on the chain and then 1 wCCD is alternately wrapped,
     /// transferred, and unwrapped.
-    Wccd,
+    Wccd(generator::InstanceArgs),
     /// Register Web3 ID credentials.
-    RegisterCredentials,
+    RegisterCredentials(generator::RegisterCredentialsArgs),
+    /// Deploy a user-supplied contract module and call its entrypoints with
+    /// parameters generated from templates.
+    Contract(generator::ContractArgs),
+    /// Make level 2 chain updates, such as exchange rate, GAS reward and pool
+    /// parameter updates, signed with the given update keys.
+    ChainUpdates(generator::ChainUpdatesArgs),
+    /// Grow the state of a contract without bound, and periodically report its
+    /// size and the cost of an update.
+    StateGrowth(generator::StateGrowthArgs),
+}
+
+impl GeneratorCommand {
+    /// The name of the generator, used to label its metrics.
+    fn name(&self) -> &'static str {
+        match self {
+            GeneratorCommand::Ccd(_) => "ccd",
+            GeneratorCommand::RegisterData(_) => "register-data",
+            GeneratorCommand::EncryptedTransfer(_) => "encrypted-transfer",
+            GeneratorCommand::Staking(_) => "staking",
+            GeneratorCommand::CreateAccounts(_) => "create-accounts",
+            GeneratorCommand::MintNfts(_) => "mint-nfts",
+            GeneratorCommand::TransferCis2(_) => "transfer-cis2",
+            GeneratorCommand::Wccd(_) => "wccd",
+            GeneratorCommand::RegisterCredentials(_) => "register-credentials",
+            GeneratorCommand::Contract(_) => "contract",
+            GeneratorCommand::ChainUpdates(_) => "chain-updates",
+            GeneratorCommand::StateGrowth(_) => "state-growth",
+        }
+    }
+}
+
+/// What to do with the transactions produced by a generator.
+enum Output {
+    /// Send the transactions to the node at the given rate, optionally only
+    /// the given number of transactions.
+    Submit {
+        tps:           u16,
+        max_in_flight: usize,
+        metrics:       Option<Metrics>,
+        limit:         Option<Limit>,
+    },
+    /// Write the given number of transactions to a file.
+    Sign { out: PathBuf, count: u64 },
+}
+
+async fn run(
+    submitter: Submitter,
+    generator: impl Generate + Send + 'static,
+    output: Output,
+) -> anyhow::Result<()> {
+    match output {
+        Output::Submit {
+            tps,
+            max_in_flight,
+            metrics,
+            limit,
+        } => generate_transactions(submitter, generator, tps, max_in_flight, metrics, limit).await,
+        Output::Sign { out, count } => sign_transactions(generator, &out, count),
+    }
+}
+
+/// Connect to the node at the given endpoint.
+async fn connect(endpoint: Endpoint) -> anyhow::Result<v2::Client> {
+    // Use TLS if the URI scheme is HTTPS.
+    // This uses whatever system certificates have been installed as trusted roots.
+    let endpoint = if endpoint
+        .uri()
+        .scheme()
+        .map_or(false, |x| x == &http::uri::Scheme::HTTPS)
+    {
+        endpoint
+            .tls_config(tonic::transport::channel::ClientTlsConfig::new())
+            .context("Unable to construct TLS configuration for the Concordium API.")?
+    } else {
+        endpoint
+    };
+    let ep = endpoint.connect_timeout(std::time::Duration::from_secs(10));
+    v2::Client::new(ep)
+        .await
+        .context("Unable to connect Concordium node.")
 }
 
 #[tokio::main(flavor = "multi_thread")]
 async fn main() -> anyhow::Result<()> {
     let app = App::parse();
 
-    let client = {
-        // Use TLS if the URI scheme is HTTPS.
-        // This uses whatever system certificates have been installed as trusted roots.
-        let endpoint = if app
-            .endpoint
-            .uri()
-            .scheme()
-            .map_or(false, |x| x == &http::uri::Scheme::HTTPS)
-        {
-            app.endpoint
-                .tls_config(tonic::transport::channel::ClientTlsConfig::new())
-                .context("Unable to construct TLS configuration for the Concordium API.")?
-        } else {
-            app.endpoint
+    let mut clients = Vec::with_capacity(app.endpoints.len());
+    for endpoint in app.endpoints {
+        clients.push(connect(endpoint).await?);
+    }
+    let client = clients
+        .first()
+        .context("At least one node must be given.")?
+        .clone();
+    let submitter = if app.dry_run {
+        Submitter::dry_run(app.output.as_deref(), app.output_format)?
+    } else {
+        Submitter::new(clients.clone(), app.distribution)?
+    };
+
+    // Start serving the metrics of the generator with the given name, if
+    // enabled.
+    let start_metrics = |name: &str| -> anyhow::Result<Option<Metrics>> {
+        let Some(address) = app.metrics_address else {
+            return Ok(None);
         };
-        let ep = endpoint.connect_timeout(std::time::Duration::from_secs(10));
-        v2::Client::new(ep)
-            .await
-            .context("Unable to connect Concordium node.")?
+        let metrics = Metrics::new(name)?;
+        metrics.serve(client.clone(), address)?;
+        Ok(Some(metrics))
+    };
+
+    let settings = Settings {
+        account:             app.account,
+        expiry:              app.expiry,
+        energy_margin:       app.energy_margin,
+        reestimate_interval: app.reestimate_interval,
+        seed:                app.seed,
     };
 
-    let keys: WalletAccount =
-        WalletAccount::from_json_file(app.account).context("Could not parse the keys file.")?;
-    let args = CommonArgs {
-        keys,
-        expiry: app.expiry,
+    let (generator_command, output) = match app.command {
+        Command::Generate(generator_command) => {
+            let metrics = start_metrics(generator_command.name())?;
+            (generator_command, Output::Submit {
+                tps: app.tps,
+                max_in_flight: app.max_in_flight,
+                metrics,
+                limit: None,
+            })
+        }
+        Command::Sign {
+            out,
+            count,
+            generator,
+        } => (generator, Output::Sign { out, count }),
+        Command::Replay(replay_args) => {
+            let metrics = start_metrics("replay")?;
+            return replay_transactions(
+                submitter,
+                replay_args,
+                app.tps,
+                app.max_in_flight,
+                metrics,
+            )
+            .await;
+        }
+        Command::Invoke(invoke_args) => {
+            return invoke_instances(clients, invoke_args, app.tps, app.max_in_flight, app.seed)
+                .await;
+        }
+        Command::Bootstrap(bootstrap_args) => {
+            let keys = settings
+                .account
+                .map(WalletAccount::from_json_file)
+                .transpose()
+                .context("Could not parse the keys file.")?;
+            return bootstrap(client, bootstrap_args, keys, app.expiry).await;
+        }
+        Command::Scenario { file } => {
+            return run_scenario(client, submitter, &settings, &file, app.max_in_flight).await;
+        }
     };
+    run_generator(&client, submitter, &settings, generator_command, output).await
+}
+
+/// Settings given on the command line that are used to instantiate the
+/// generators.
+struct Settings {
+    account:             Option<PathBuf>,
+    expiry:              u32,
+    energy_margin:       u64,
+    reestimate_interval: u64,
+    seed:                Option<u64>,
+}
+
+impl Settings {
+    /// The arguments of generators that need a sender, reading the keys of the
+    /// sender from the file given by `--sender`.
+    fn common_args(&self) -> anyhow::Result<CommonArgs> {
+        let keys: WalletAccount = WalletAccount::from_json_file(
+            self.account
+                .as_ref()
+                .context("The `--sender` option is required for this command.")?,
+        )
+        .context("Could not parse the keys file.")?;
+        Ok(CommonArgs {
+            keys,
+            expiry: self.expiry,
+            energy_margin: self.energy_margin,
+            reestimate_interval: self.reestimate_interval,
+            seed: self.seed,
+        })
+    }
+}
 
-    match app.command {
-        Command::Ccd(ccd_args) => {
-            let generator = CcdGenerator::instantiate(client.clone(), args, ccd_args).await?;
-            generate_transactions(client, generator, app.tps).await
+/// Instantiate the generator given by the command and run it.
+async fn run_generator(
+    client: &v2::Client,
+    submitter: Submitter,
+    settings: &Settings,
+    generator_command: GeneratorCommand,
+    output: Output,
+) -> anyhow::Result<()> {
+    match generator_command {
+        // Account creation and chain updates do not need a sender.
+        GeneratorCommand::CreateAccounts(create_accounts_args) => {
+            let generator = CreateAccountsGenerator::instantiate(
+                client.clone(),
+                create_accounts_args,
+                settings.expiry,
+            )
+            .await?;
+            run(submitter, generator, output).await
+        }
+        GeneratorCommand::ChainUpdates(chain_updates_args) => {
+            let generator = ChainUpdatesGenerator::instantiate(
+                client.clone(),
+                chain_updates_args,
+                settings.expiry,
+            )
+            .await?;
+            run(submitter, generator, output).await
+        }
+        GeneratorCommand::Ccd(ccd_args) => {
+            let generator =
+                CcdGenerator::instantiate(client.clone(), settings.common_args()?, ccd_args)
+                    .await?;
+            run(submitter, generator, output).await
+        }
+        GeneratorCommand::RegisterData(register_data_args) => {
+            let generator = RegisterDataGenerator::instantiate(
+                client.clone(),
+                settings.common_args()?,
+                register_data_args,
+            )
+            .await?;
+            run(submitter, generator, output).await
+        }
+        GeneratorCommand::EncryptedTransfer(encrypted_args) => {
+            let generator = EncryptedTransferGenerator::instantiate(
+                client.clone(),
+                settings.common_args()?,
+                encrypted_args,
+            )
+            .await?;
+            run(submitter, generator, output).await
+        }
+        GeneratorCommand::Staking(staking_args) => {
+            let generator = StakingGenerator::instantiate(
+                client.clone(),
+                settings.common_args()?,
+                staking_args,
+            )
+            .await?;
+            run(submitter, generator, output).await
+        }
+        GeneratorCommand::MintNfts(instance_args) => {
+            let generator = MintCis2Generator::instantiate(
+                client.clone(),
+                settings.common_args()?,
+                instance_args,
+            )
+            .await?;
+            run(submitter, generator, output).await
         }
-        Command::MintNfts => {
-            let generator = MintCis2Generator::instantiate(client.clone(), args).await?;
-            generate_transactions(client, generator, app.tps).await
+        GeneratorCommand::TransferCis2(transfer_cis2_args) => {
+            let generator = TransferCis2Generator::instantiate(
+                client.clone(),
+                settings.common_args()?,
+                transfer_cis2_args,
+            )
+            .await?;
+            run(submitter, generator, output).await
         }
-        Command::TransferCis2(transfer_cis2_args) => {
+        GeneratorCommand::Wccd(instance_args) => {
             let generator =
-                TransferCis2Generator::instantiate(client.clone(), args, transfer_cis2_args)
+                WccdGenerator::instantiate(client.clone(), settings.common_args()?, instance_args)
                     .await?;
-            generate_transactions(client, generator, app.tps).await
+            run(submitter, generator, output).await
         }
-        Command::Wccd => {
-            let generator = WccdGenerator::instantiate(client.clone(), args).await?;
-            generate_transactions(client, generator, app.tps).await
+        GeneratorCommand::RegisterCredentials(register_credentials_args) => {
+            let generator = RegisterCredentialsGenerator::instantiate(
+                client.clone(),
+                settings.common_args()?,
+                register_credentials_args,
+            )
+            .await?;
+            run(submitter, generator, output).await
         }
-        Command::RegisterCredentials => {
-            let generator = RegisterCredentialsGenerator::instantiate(client.clone(), args).await?;
-            generate_transactions(client, generator, app.tps).await
+        GeneratorCommand::StateGrowth(state_growth_args) => {
+            let generator = StateGrowthGenerator::instantiate(
+                client.clone(),
+                settings.common_args()?,
+                state_growth_args,
+            )
+            .await?;
+            run(submitter, generator, output).await
         }
+        GeneratorCommand::Contract(contract_args) => {
+            let generator = ContractGenerator::instantiate(
+                client.clone(),
+                settings.common_args()?,
+                contract_args,
+            )
+            .await?;
+            run(submitter, generator, output).await
+        }
+    }
+}
+
+/// Run the scenario in the given file: the setup steps, the load phases one
+/// after the other, and the assertions once the transactions of the phases are
+/// finalized. The teardown steps are run at the end, also if a phase or an
+/// assertion failed.
+async fn run_scenario(
+    client: v2::Client,
+    submitter: Submitter,
+    settings: &Settings,
+    file: &std::path::Path,
+    max_in_flight: usize,
+) -> anyhow::Result<()> {
+    let scenario = Scenario::load(file)?;
+    let mut runner = ScenarioRunner::new(client.clone(), settings.common_args()?, file);
+    runner
+        .run_steps(&scenario.setup)
+        .await
+        .context("Setup failed.")?;
+
+    let result = async {
+        for phase in &scenario.phases {
+            let args = runner.phase_args(phase, &PhaseCommand::command())?;
+            let command =
+                PhaseCommand::try_parse_from(std::iter::once(phase.name.clone()).chain(args))
+                    .with_context(|| format!("Invalid generator in phase `{}`.", phase.name))?;
+            println!("Starting phase `{}`.", phase.name);
+            run_generator(
+                &client,
+                submitter.clone(),
+                settings,
+                command.generator,
+                Output::Submit {
+                    tps: phase.tps,
+                    max_in_flight,
+                    metrics: None,
+                    limit: Some(phase.limit()),
+                },
+            )
+            .await
+            .with_context(|| format!("Phase `{}` failed.", phase.name))?;
+        }
+        println!("Waiting for the transactions to be finalized.");
+        runner.wait_for_finalization().await?;
+        runner.check_assertions(&scenario.assertions).await
+    }
+    .await;
+
+    let teardown = runner.run_steps(&scenario.teardown).await;
+    match (result, teardown) {
+        (Ok(()), teardown) => teardown.context("Teardown failed."),
+        (Err(e), Ok(())) => Err(e),
+        // The phase error is the one reported, with the teardown error attached.
+        (Err(e), Err(teardown)) => Err(e.context(format!("Teardown failed as well: {teardown:#}"))),
     }
 }
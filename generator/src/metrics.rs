@@ -0,0 +1,261 @@
+//! Prometheus metrics of the transactions sent by the generator.
+use anyhow::Context;
+use axum::{routing::get, Router};
+use concordium_rust_sdk::{
+    common::types::TransactionTime,
+    types::{hashes::TransactionHash, transactions::BlockItem},
+    v2::{self, BlockIdentifier},
+};
+use futures::{StreamExt, TryStreamExt};
+use prometheus::{
+    Gauge, Histogram, HistogramOpts, IntCounter, IntGauge, Opts, Registry, TextEncoder,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// How long after its expiry a transaction is kept waiting for finalization.
+/// A transaction can be in a block with a slot time up to its expiry, and that
+/// block may be finalized some time later.
+const EXPIRY_GRACE_SECONDS: u64 = 600;
+
+/// The longest wait before reconnecting to the stream of finalized blocks.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Metrics of the transactions produced by one generator. Cloning the metrics
+/// gives a handle to the same counters.
+#[derive(Clone)]
+pub struct Metrics {
+    registry:         Registry,
+    /// Number of transactions produced by the generator.
+    pub generated:    IntCounter,
+    /// Number of transactions accepted by the node.
+    pub submitted:    IntCounter,
+    /// Number of transactions rejected by the node, or that failed when
+    /// executed.
+    pub failed:       IntCounter,
+    /// Number of submitted transactions that were finalized.
+    pub finalized:    IntCounter,
+    /// The rate the transactions are submitted at.
+    pub target_tps:   IntGauge,
+    /// The rate achieved in the last second.
+    pub achieved_tps: Gauge,
+    /// Number of generated transactions waiting to be submitted.
+    pub backlog:      IntGauge,
+    /// Time it takes for the node to respond to a submission.
+    pub latency:      Histogram,
+    /// Submitted transactions that are not yet finalized, with their expiry.
+    /// Transactions are dropped once they have been expired for
+    /// [`EXPIRY_GRACE_SECONDS`], so that the ones that are never seen
+    /// finalized do not accumulate.
+    pending:          Arc<Mutex<HashMap<TransactionHash, TransactionTime>>>,
+}
+
+impl Metrics {
+    /// Create the metrics for the generator with the given name. The name is
+    /// added as the `generator` label to all metrics.
+    pub fn new(generator: &str) -> anyhow::Result<Self> {
+        let registry = Registry::new();
+        let counter = |name: &str, help: &str| -> anyhow::Result<IntCounter> {
+            let counter =
+                IntCounter::with_opts(Opts::new(name, help).const_label("generator", generator))?;
+            registry.register(Box::new(counter.clone()))?;
+            Ok(counter)
+        };
+        let generated = counter(
+            "generator_transactions_generated",
+            "Number of transactions generated.",
+        )?;
+        let submitted = counter(
+            "generator_transactions_submitted",
+            "Number of transactions accepted by the node.",
+        )?;
+        let failed = counter(
+            "generator_transactions_failed",
+            "Number of transactions rejected by the node or failed on chain.",
+        )?;
+        let finalized = counter(
+            "generator_transactions_finalized",
+            "Number of submitted transactions that were finalized.",
+        )?;
+        let target_tps = IntGauge::with_opts(
+            Opts::new("generator_target_tps", "Target transactions per second.")
+                .const_label("generator", generator),
+        )?;
+        registry.register(Box::new(target_tps.clone()))?;
+        let achieved_tps = Gauge::with_opts(
+            Opts::new(
+                "generator_achieved_tps",
+                "Transactions submitted in the last second.",
+            )
+            .const_label("generator", generator),
+        )?;
+        registry.register(Box::new(achieved_tps.clone()))?;
+        let backlog = IntGauge::with_opts(
+            Opts::new(
+                "generator_backlog",
+                "Number of generated transactions waiting to be submitted.",
+            )
+            .const_label("generator", generator),
+        )?;
+        registry.register(Box::new(backlog.clone()))?;
+        let latency = Histogram::with_opts(
+            HistogramOpts::new(
+                "generator_submission_latency_seconds",
+                "Time for the node to respond to a transaction submission.",
+            )
+            .const_label("generator", generator)
+            .buckets(prometheus::exponential_buckets(0.001, 2.0, 14)?),
+        )?;
+        registry.register(Box::new(latency.clone()))?;
+        Ok(Self {
+            registry,
+            generated,
+            submitted,
+            failed,
+            finalized,
+            target_tps,
+            achieved_tps,
+            backlog,
+            latency,
+            pending: Arc::default(),
+        })
+    }
+
+    /// Record that a transaction was accepted by the node, so that it is
+    /// counted when it is finalized.
+    pub fn track<P>(&self, item: &BlockItem<P>, transaction_hash: TransactionHash) {
+        self.submitted.inc();
+        let expiry = match item {
+            BlockItem::AccountTransaction(tx) => tx.header.expiry,
+            BlockItem::CredentialDeployment(credential) => credential.message_expiry,
+            BlockItem::UpdateInstruction(update) => update.header.timeout,
+        };
+        self.pending
+            .lock()
+            .expect("Lock is not poisoned.")
+            .insert(transaction_hash, expiry);
+    }
+
+    /// Drop the pending transactions that expired more than
+    /// [`EXPIRY_GRACE_SECONDS`] ago. They were either never included in a
+    /// block, or finalized while the finalized blocks were not followed.
+    fn evict_expired(&self) {
+        let cutoff = (chrono::Utc::now().timestamp() as u64).saturating_sub(EXPIRY_GRACE_SECONDS);
+        self.pending
+            .lock()
+            .expect("Lock is not poisoned.")
+            .retain(|_, expiry| expiry.seconds >= cutoff);
+    }
+
+    /// Serve the metrics at `/metrics` on the given address, and follow the
+    /// finalized blocks to count the finalized and failed transactions. The
+    /// tasks are spawned in the background.
+    pub fn serve(&self, client: v2::Client, address: std::net::SocketAddr) -> anyhow::Result<()> {
+        let registry = self.registry.clone();
+        let server = Router::new().route(
+            "/metrics",
+            get(move || async move {
+                TextEncoder::new()
+                    .encode_to_string(&registry.gather())
+                    .unwrap_or_else(|e| format!("Could not encode metrics: {e}"))
+            }),
+        );
+        let server = axum::Server::try_bind(&address)
+            .context("Could not bind the metrics address.")?
+            .serve(server.into_make_service());
+        println!("Serving metrics at http://{address}/metrics.");
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                eprintln!("Metrics server stopped: {e}");
+            }
+        });
+
+        let metrics = self.clone();
+        tokio::spawn(async move {
+            // The stream is reconnected with an exponential backoff, which is reset
+            // once blocks are received again.
+            let mut delay = Duration::from_secs(1);
+            loop {
+                let mut received = false;
+                match metrics
+                    .follow_finalized(client.clone(), &mut received)
+                    .await
+                {
+                    Ok(()) => eprintln!("The stream of finalized blocks ended."),
+                    Err(e) => eprintln!("Error following the finalized blocks: {e:#}"),
+                }
+                metrics.evict_expired();
+                if received {
+                    delay = Duration::from_secs(1);
+                }
+                eprintln!("Reconnecting in {}s.", delay.as_secs());
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+        });
+        Ok(())
+    }
+
+    /// Count the pending transactions that appear in finalized blocks, until
+    /// the stream of finalized blocks ends or fails. `received` is set once a
+    /// block has been processed.
+    async fn follow_finalized(
+        &self,
+        mut client: v2::Client,
+        received: &mut bool,
+    ) -> anyhow::Result<()> {
+        let mut blocks = client.get_finalized_blocks().await?;
+        while let Some(block) = blocks.next().await.transpose()? {
+            let summaries: Vec<_> = client
+                .get_block_transaction_events(BlockIdentifier::Given(block.block_hash))
+                .await?
+                .response
+                .try_collect()
+                .await?;
+            {
+                let mut pending = self.pending.lock().expect("Lock is not poisoned.");
+                for summary in summaries {
+                    if pending.remove(&summary.hash).is_some() {
+                        self.finalized.inc();
+                        if !summary.is_success() {
+                            self.failed.inc();
+                        }
+                    }
+                }
+            }
+            self.evict_expired();
+            *received = true;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expired_transactions_are_evicted() {
+        let metrics = Metrics::new("test").unwrap();
+        let now = chrono::Utc::now().timestamp() as u64;
+        let expired = TransactionHash::new([1; 32]);
+        let recently_expired = TransactionHash::new([2; 32]);
+        let valid = TransactionHash::new([3; 32]);
+        {
+            let mut pending = metrics.pending.lock().unwrap();
+            pending.insert(expired, TransactionTime {
+                seconds: now - EXPIRY_GRACE_SECONDS - 10,
+            });
+            pending.insert(recently_expired, TransactionTime { seconds: now - 10 });
+            pending.insert(valid, TransactionTime { seconds: now + 100 });
+        }
+        metrics.evict_expired();
+        let pending = metrics.pending.lock().unwrap();
+        assert!(!pending.contains_key(&expired));
+        assert!(pending.contains_key(&recently_expired));
+        assert!(pending.contains_key(&valid));
+    }
+}
@@ -0,0 +1,581 @@
+//! Scenarios combining setup transactions, load phases of the generators, and
+//! assertions about the resulting state of the chain, described in a JSON
+//! file. See the README for the format.
+use crate::generator::{
+    embedded_schema, get_receivers, mint_nft_param, next_unused_token_id, parse_contract_address,
+    serial_parameter, submit_batch, CommonArgs, ContractDeploymentInfo, EnergyEstimator, Limit,
+};
+use anyhow::Context;
+use concordium_rust_sdk::{
+    cis2::{Cis2Contract, TokenAmount, TokenId},
+    common::{
+        types::{Amount, TransactionTime},
+        Deserial,
+    },
+    id::types::AccountAddress,
+    types::{
+        smart_contracts::{ModuleReference, OwnedContractName, OwnedParameter, WasmModule},
+        transactions::{send, BlockItem},
+        Address, ContractAddress, Energy,
+    },
+    v2::{self, BlockIdentifier},
+};
+use futures::TryStreamExt;
+use std::{
+    collections::BTreeMap,
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+/// Number of tokens minted by each transaction of a `mint` step.
+const MINT_BATCH_SIZE: u32 = 100;
+
+/// A scenario as given in the file passed to `scenario --file`.
+#[derive(Debug, serde::Deserialize)]
+pub struct Scenario {
+    /// Steps run before the load phases.
+    #[serde(default)]
+    pub setup:      Vec<Step>,
+    /// The load phases, run one after the other.
+    pub phases:     Vec<Phase>,
+    /// Checks of the state of the chain after all phases are finalized.
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    /// Steps run at the end, also if a phase or an assertion failed.
+    #[serde(default)]
+    pub teardown:   Vec<Step>,
+}
+
+/// A setup or teardown step. The transactions of each step are finalized
+/// before the next step starts.
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Step {
+    /// Deploy a module, unless it is already deployed.
+    Deploy { module: PathBuf },
+    /// Deploy a module and initialize an instance of a contract in it. The
+    /// instance can be referred to by its label in later steps, phases and
+    /// assertions.
+    #[serde(rename_all = "camelCase")]
+    Init {
+        label:       String,
+        module:      PathBuf,
+        /// The name of the init function, e.g. `init_cis2_nft`.
+        init_name:   String,
+        /// The parameter in the JSON format of the schema embedded in the
+        /// module. If not given, the empty parameter is used.
+        #[serde(default)]
+        parameter:   Option<serde_json::Value>,
+        init_energy: Option<Energy>,
+    },
+    /// Transfer an amount of CCD from the sender to each of the receivers.
+    Fund {
+        /// Path to a file containing the receivers. If not given, all
+        /// accounts on the chain are funded.
+        receivers: Option<PathBuf>,
+        amount:    Amount,
+    },
+    /// Mint the next unused token ids of a `cis2_nft` instance, such as the
+    /// one used by `mint-nfts`.
+    Mint {
+        instance: String,
+        tokens:   u32,
+        /// The owner of the minted tokens. Defaults to the sender.
+        owner:    Option<AccountAddress>,
+    },
+}
+
+/// A load phase running one of the generators.
+#[derive(Debug, serde::Deserialize)]
+pub struct Phase {
+    pub name:      String,
+    /// The generator and its options as given on the command line, e.g.
+    /// `["transfer-cis2", "--contract", "$nft"]`. Arguments of the form
+    /// `$label` are replaced by the address of the instance with that label.
+    pub generator: Vec<String>,
+    pub tps:       u16,
+    /// Number of transactions to submit.
+    count:         Option<u64>,
+    /// Duration of the phase in seconds, as an alternative to `count`.
+    duration:      Option<u64>,
+}
+
+impl Phase {
+    /// When the phase ends. A phase with a duration stops dispatching
+    /// transactions once the duration has passed, even if fewer than
+    /// `duration * tps` transactions were submitted.
+    pub fn limit(&self) -> Limit {
+        match self.count {
+            Some(count) => Limit::Count(count),
+            None => Limit::Duration(std::time::Duration::from_secs(self.duration.unwrap_or(0))),
+        }
+    }
+}
+
+/// A check of the state of the chain at the end of the scenario.
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Assertion {
+    /// The CIS-2 balance of an account.
+    #[serde(rename_all = "camelCase")]
+    TokenBalance {
+        instance: String,
+        token_id: TokenId,
+        account:  AccountAddress,
+        expected: TokenAmount,
+    },
+    /// The number of contract instances on the chain, optionally only counting
+    /// instances of the given module.
+    InstanceCount {
+        module:   Option<PathBuf>,
+        expected: u64,
+    },
+}
+
+impl Scenario {
+    /// Read a scenario from a file and check that it is well-formed.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let scenario: Scenario = serde_json::from_str(
+            &std::fs::read_to_string(path).context("Could not read the scenario file.")?,
+        )
+        .context("Could not parse the scenario file.")?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !self.phases.is_empty(),
+            "The scenario must have at least one phase."
+        );
+        for phase in &self.phases {
+            anyhow::ensure!(
+                !phase.generator.is_empty(),
+                "Phase `{}` must name a generator.",
+                phase.name
+            );
+            anyhow::ensure!(
+                phase.tps > 0,
+                "Phase `{}` must have a positive tps.",
+                phase.name
+            );
+            anyhow::ensure!(
+                phase.count.is_some() != phase.duration.is_some(),
+                "Phase `{}` must have exactly one of `count` and `duration`.",
+                phase.name
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Replace the arguments of the form `$label` by the address of the instance
+/// with that label.
+fn substitute_labels(
+    args: &[String],
+    instances: &BTreeMap<String, ContractAddress>,
+) -> anyhow::Result<Vec<String>> {
+    args.iter()
+        .map(|arg| match arg.strip_prefix('$') {
+            Some(label) => instances
+                .get(label)
+                .map(|address| address.to_string())
+                .with_context(|| format!("No instance is labelled `{label}`.")),
+            None => Ok(arg.clone()),
+        })
+        .collect()
+}
+
+/// Resolve the values of the path options of the generator named by the first
+/// argument against `base`. Absolute paths are kept as they are.
+fn resolve_paths(mut args: Vec<String>, command: &clap::Command, base: &Path) -> Vec<String> {
+    let Some(generator) = args.first().and_then(|name| command.find_subcommand(name)) else {
+        return args;
+    };
+    let is_path = |flag: &str| {
+        generator.get_arguments().any(|arg| {
+            let matches = match flag.strip_prefix("--") {
+                Some(long) => arg.get_long() == Some(long),
+                None => flag
+                    .strip_prefix('-')
+                    .and_then(|short| short.parse::<char>().ok())
+                    .is_some_and(|short| arg.get_short() == Some(short)),
+            };
+            matches && arg.get_value_hint() == clap::ValueHint::AnyPath
+        })
+    };
+    let resolve = |value: &str| base.join(value).to_string_lossy().into_owned();
+    let mut i = 1;
+    while i < args.len() {
+        if let Some((flag, value)) = args[i].split_once('=') {
+            if is_path(flag) {
+                args[i] = format!("{flag}={}", resolve(value));
+            }
+        } else if is_path(&args[i]) && i + 1 < args.len() {
+            args[i + 1] = resolve(&args[i + 1]);
+            i += 1;
+        }
+        i += 1;
+    }
+    args
+}
+
+/// Runs the steps and assertions of a scenario, keeping track of the
+/// instances created by the steps.
+pub struct ScenarioRunner {
+    client:    v2::Client,
+    args:      CommonArgs,
+    /// The directory of the scenario file, which relative paths are resolved
+    /// against.
+    base:      PathBuf,
+    instances: BTreeMap<String, ContractAddress>,
+}
+
+impl ScenarioRunner {
+    pub fn new(client: v2::Client, args: CommonArgs, scenario_file: &Path) -> Self {
+        Self {
+            client,
+            args,
+            base: scenario_file
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+            instances: BTreeMap::new(),
+        }
+    }
+
+    /// The arguments of the generator of a phase, with instance labels
+    /// replaced by addresses, and relative paths resolved against the
+    /// directory of the scenario file. The generators are the subcommands of
+    /// `command`, whose path options are those with a path value hint.
+    pub fn phase_args(
+        &self,
+        phase: &Phase,
+        command: &clap::Command,
+    ) -> anyhow::Result<Vec<String>> {
+        let args = substitute_labels(&phase.generator, &self.instances)?;
+        Ok(resolve_paths(args, command, &self.base))
+    }
+
+    fn instance(&self, instance: &str) -> anyhow::Result<ContractAddress> {
+        match self.instances.get(instance) {
+            Some(address) => Ok(*address),
+            None => parse_contract_address(instance)
+                .with_context(|| format!("`{instance}` is neither a label nor an address.")),
+        }
+    }
+
+    fn read_module(&self, path: &Path) -> anyhow::Result<(Vec<u8>, WasmModule)> {
+        let path = self.base.join(path);
+        let bytes = std::fs::read(&path)
+            .with_context(|| format!("Could not read the module {}.", path.display()))?;
+        let module = WasmModule::deserial(&mut Cursor::new(&bytes))
+            .with_context(|| format!("Could not parse the module {}.", path.display()))?;
+        Ok((bytes, module))
+    }
+
+    /// Run the steps in order.
+    pub async fn run_steps(&mut self, steps: &[Step]) -> anyhow::Result<()> {
+        for step in steps {
+            self.run_step(step)
+                .await
+                .with_context(|| format!("Step {step:?} failed."))?;
+        }
+        Ok(())
+    }
+
+    async fn run_step(&mut self, step: &Step) -> anyhow::Result<()> {
+        let sender = self.args.keys.address;
+        let mut nonce = self
+            .client
+            .get_next_account_sequence_number(&sender)
+            .await?
+            .nonce;
+        let expiry = TransactionTime::seconds_after(self.args.expiry);
+        match step {
+            Step::Deploy { module } => {
+                let (_, module) = self.read_module(module)?;
+                let mod_ref = module.get_module_ref();
+                if self
+                    .client
+                    .get_module_source(&mod_ref, BlockIdentifier::LastFinal)
+                    .await
+                    .is_ok()
+                {
+                    println!("Module {mod_ref} is already deployed.");
+                    return Ok(());
+                }
+                let tx = send::deploy_module(&self.args.keys, sender, nonce, expiry, module);
+                submit_batch(&mut self.client, &[BlockItem::AccountTransaction(tx)]).await?;
+                println!("Deployed module {mod_ref}.");
+            }
+            Step::Init {
+                label,
+                module,
+                init_name,
+                parameter,
+                init_energy,
+            } => {
+                let (bytes, module) = self.read_module(module)?;
+                let init_name = OwnedContractName::new(init_name.clone())
+                    .context("Invalid name of the init function.")?;
+                let param = match parameter {
+                    None => OwnedParameter::empty(),
+                    Some(value) => {
+                        let param_schema = embedded_schema(&module)
+                            .context("The module has no embedded schema.")?
+                            .get_init_param_schema(init_name.as_contract_name().contract_name())
+                            .context("The schema has no type for the init parameter.")?;
+                        serial_parameter(&param_schema, value).context("Invalid init parameter.")?
+                    }
+                };
+                let info = ContractDeploymentInfo {
+                    module:      &bytes,
+                    name:        init_name.as_contract_name().get_chain_name(),
+                    init_energy: *init_energy,
+                    owner_only:  false,
+                };
+                let address = info
+                    .deploy_and_init_contract(&mut self.client, &self.args, param, &mut nonce)
+                    .await?;
+                println!("Instance `{label}` is {address}.");
+                self.instances.insert(label.clone(), address);
+            }
+            Step::Fund { receivers, amount } => {
+                let receivers = receivers.as_ref().map(|path| self.base.join(path));
+                let accounts = get_receivers(&mut self.client, receivers).await?;
+                let batch: Vec<_> = accounts
+                    .iter()
+                    .map(|receiver| {
+                        let tx = send::transfer(
+                            &self.args.keys,
+                            sender,
+                            nonce,
+                            expiry,
+                            *receiver,
+                            *amount,
+                        );
+                        nonce.next_mut();
+                        BlockItem::AccountTransaction(tx)
+                    })
+                    .collect();
+                submit_batch(&mut self.client, &batch).await?;
+                println!("Funded {} accounts with {amount} CCD.", accounts.len());
+            }
+            Step::Mint {
+                instance,
+                tokens,
+                owner,
+            } => {
+                let address = self.instance(instance)?;
+                let contract = Cis2Contract::create(self.client.clone(), address).await?;
+                let mut next_id = next_unused_token_id(&contract).await?;
+                let end = next_id + tokens;
+                let mut estimator = EnergyEstimator::new(contract, &self.args);
+                while next_id < end {
+                    let batch_end = end.min(next_id + MINT_BATCH_SIZE);
+                    let param = mint_nft_param(owner.unwrap_or(sender), next_id..batch_end)?;
+                    let tx = estimator
+                        .make_estimated_update(&self.args, nonce, "mint", Amount::zero(), param)
+                        .await?;
+                    nonce.next_mut();
+                    // Wait for each batch, so that the energy of the next one can be estimated.
+                    submit_batch(&mut self.client, &[BlockItem::AccountTransaction(tx)]).await?;
+                    next_id = batch_end;
+                }
+                println!("Minted {tokens} tokens in {address}.");
+            }
+        }
+        Ok(())
+    }
+
+    /// Wait until all transactions of the sender are finalized.
+    pub async fn wait_for_finalization(&mut self) -> anyhow::Result<()> {
+        loop {
+            let next = self
+                .client
+                .get_next_account_sequence_number(&self.args.keys.address)
+                .await?;
+            if next.all_final {
+                return Ok(());
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        }
+    }
+
+    /// Check all the assertions and print their outcome. Fails if any of them
+    /// does not hold.
+    pub async fn check_assertions(&mut self, assertions: &[Assertion]) -> anyhow::Result<()> {
+        let mut failed = 0;
+        for assertion in assertions {
+            let (description, holds) = self.check_assertion(assertion).await?;
+            if holds {
+                println!("Assertion passed: {description}.");
+            } else {
+                println!("Assertion failed: {description}.");
+                failed += 1;
+            }
+        }
+        anyhow::ensure!(
+            failed == 0,
+            "{failed} of {} assertions failed.",
+            assertions.len()
+        );
+        Ok(())
+    }
+
+    /// Check an assertion, returning a description of the outcome and whether
+    /// it holds.
+    async fn check_assertion(&mut self, assertion: &Assertion) -> anyhow::Result<(String, bool)> {
+        match assertion {
+            Assertion::TokenBalance {
+                instance,
+                token_id,
+                account,
+                expected,
+            } => {
+                let address = self.instance(instance)?;
+                let balance = Cis2Contract::create(self.client.clone(), address)
+                    .await?
+                    .balance_of_single(
+                        BlockIdentifier::LastFinal,
+                        token_id.clone(),
+                        Address::Account(*account),
+                    )
+                    .await
+                    .with_context(|| format!("Could not query the balance of {account}."))?;
+                Ok((
+                    format!(
+                        "balance of token {token_id} of {account} in {address} is {balance}, \
+                         expected {expected}"
+                    ),
+                    &balance == expected,
+                ))
+            }
+            Assertion::InstanceCount { module, expected } => {
+                let mod_ref = match module {
+                    Some(module) => Some(self.read_module(module)?.1.get_module_ref()),
+                    None => None,
+                };
+                let count = self.count_instances(mod_ref).await?;
+                let of_module = mod_ref.map_or_else(String::new, |m| format!(" of module {m}"));
+                Ok((
+                    format!("{count} instances{of_module}, expected {expected}"),
+                    count == *expected,
+                ))
+            }
+        }
+    }
+
+    /// Count the instances on the chain, only counting instances of the given
+    /// module if any.
+    async fn count_instances(&mut self, module: Option<ModuleReference>) -> anyhow::Result<u64> {
+        let instances: Vec<ContractAddress> = self
+            .client
+            .get_instance_list(BlockIdentifier::LastFinal)
+            .await?
+            .response
+            .try_collect()
+            .await?;
+        let Some(module) = module else {
+            return Ok(instances.len() as u64);
+        };
+        let mut count = 0;
+        for address in instances {
+            let info = self
+                .client
+                .get_instance_info(address, BlockIdentifier::LastFinal)
+                .await?
+                .response;
+            if info.source_module() == module {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenario_parses_and_validates() {
+        let scenario: Scenario = serde_json::from_value(serde_json::json!({
+            "setup": [
+                { "type": "deploy", "module": "cis2_nft.wasm.v1" },
+                { "type": "init", "label": "nft", "module": "cis2_nft.wasm.v1", "initName": "init_cis2_nft" },
+                { "type": "fund", "amount": "1000000" },
+                { "type": "mint", "instance": "nft", "tokens": 10 }
+            ],
+            "phases": [
+                { "name": "mint", "generator": ["mint-nfts", "--contract", "$nft"], "tps": 5, "duration": 60 },
+                { "name": "transfer", "generator": ["ccd"], "tps": 10, "count": 100 }
+            ],
+            "assertions": [
+                { "type": "tokenBalance", "instance": "nft", "tokenId": "00000000", "account": "3kBx2h5Y2veb4hZgAJWPrr8RyQESKm5TjzF3ti1QQ4VSYLwK1G", "expected": "1" },
+                { "type": "instanceCount", "expected": 1 }
+            ]
+        }))
+        .expect("The scenario is well-formed.");
+        scenario.validate().expect("The scenario is valid.");
+        assert_eq!(scenario.setup.len(), 4);
+        assert!(scenario.teardown.is_empty());
+        assert_eq!(
+            scenario.phases[0].limit(),
+            Limit::Duration(std::time::Duration::from_secs(60))
+        );
+        assert_eq!(scenario.phases[1].limit(), Limit::Count(100));
+    }
+
+    #[test]
+    fn phase_paths_resolve_against_scenario_directory() {
+        use clap::CommandFactory;
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let command = crate::PhaseCommand::command();
+        let base = Path::new("scenarios");
+        assert_eq!(
+            resolve_paths(
+                args(&["ccd", "--receivers", "receivers.json", "--amount", "1"]),
+                &command,
+                base
+            ),
+            args(&[
+                "ccd",
+                "--receivers",
+                "scenarios/receivers.json",
+                "--amount",
+                "1"
+            ])
+        );
+        assert_eq!(
+            resolve_paths(
+                args(&["ccd", "--receivers=/tmp/receivers.json"]),
+                &command,
+                base
+            ),
+            args(&["ccd", "--receivers=/tmp/receivers.json"])
+        );
+    }
+
+    #[test]
+    fn phase_needs_count_or_duration() {
+        let scenario: Scenario = serde_json::from_value(serde_json::json!({
+            "phases": [{ "name": "transfer", "generator": ["ccd"], "tps": 10 }]
+        }))
+        .expect("The scenario is well-formed.");
+        assert!(scenario.validate().is_err());
+    }
+
+    #[test]
+    fn labels_are_substituted() {
+        let instances = BTreeMap::from([("nft".to_string(), ContractAddress::new(7, 0))]);
+        let args = ["transfer-cis2", "--contract", "$nft"].map(String::from);
+        assert_eq!(substitute_labels(&args, &instances).unwrap(), [
+            "transfer-cis2",
+            "--contract",
+            "<7,0>"
+        ]);
+        assert!(substitute_labels(&["$wccd".to_string()], &instances).is_err());
+    }
+}
@@ -0,0 +1,319 @@
+//! Submission of transactions to one or more nodes, or to a file when doing a
+//! dry run.
+use anyhow::Context;
+use concordium_rust_sdk::{
+    common::Serial,
+    types::{
+        hashes::TransactionHash,
+        transactions::{BlockItem, EncodedPayload},
+    },
+    v2,
+};
+use std::{
+    io::Write,
+    path::Path,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// How transactions are distributed between the nodes.
+#[derive(Debug, Clone, Copy)]
+pub enum Distribution {
+    /// Send each transaction to the next node in turn.
+    RoundRobin,
+    /// Choose the node based on the hash of the transaction.
+    Hash,
+    /// Choose the node based on the sender of the transaction, so that all
+    /// transactions of a sender go to the same node.
+    Sender,
+    /// Send each transaction to all nodes.
+    Broadcast,
+}
+
+impl FromStr for Distribution {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "round-robin" => Ok(Self::RoundRobin),
+            "hash" => Ok(Self::Hash),
+            "sender" => Ok(Self::Sender),
+            "broadcast" => Ok(Self::Broadcast),
+            _ => anyhow::bail!(
+                "Unknown distribution `{s}`. Expected one of `round-robin`, `hash`, `sender` or \
+                 `broadcast`."
+            ),
+        }
+    }
+}
+
+/// The format of the transactions written by a dry run.
+#[derive(Debug, Clone, Copy)]
+pub enum OutputFormat {
+    /// Serialized block items, each prefixed by its length as a big-endian
+    /// `u32`. This is the format written by `sign` and read by `replay`.
+    Binary,
+    /// One JSON object per line, with the decoded payload of the transaction.
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "binary" => Ok(Self::Binary),
+            "json" => Ok(Self::Json),
+            _ => anyhow::bail!("Unknown output format `{s}`. Expected `binary` or `json`."),
+        }
+    }
+}
+
+/// Write a serialized block item prefixed by its length as a big-endian `u32`.
+pub fn write_signed_transaction(
+    writer: &mut impl Write,
+    item: &BlockItem<EncodedPayload>,
+) -> std::io::Result<()> {
+    let mut bytes = Vec::new();
+    item.serial(&mut bytes);
+    writer.write_all(&(bytes.len() as u32).to_be_bytes())?;
+    writer.write_all(&bytes)
+}
+
+/// The JSON rendering of a block item written by a dry run.
+pub fn block_item_to_json(item: &BlockItem<EncodedPayload>) -> anyhow::Result<serde_json::Value> {
+    let hash = item.hash();
+    let value = match item {
+        BlockItem::AccountTransaction(tx) => serde_json::json!({
+            "type": "accountTransaction",
+            "hash": hash,
+            "header": tx.header,
+            "payload": tx.payload.decode().context("Could not decode the payload.")?,
+        }),
+        BlockItem::CredentialDeployment(credential) => serde_json::json!({
+            "type": "credentialDeployment",
+            "hash": hash,
+            "credential": credential,
+        }),
+        BlockItem::UpdateInstruction(update) => serde_json::json!({
+            "type": "updateInstruction",
+            "hash": hash,
+            "sequenceNumber": update.header.seq_number,
+            "effectiveTime": update.header.effective_time,
+            "timeout": update.header.timeout,
+            "payload": update.payload,
+        }),
+    };
+    Ok(value)
+}
+
+/// Where the transactions are submitted to.
+#[derive(Clone)]
+enum Target {
+    /// Submit the transactions to the nodes using the distribution.
+    Nodes {
+        clients:      Vec<v2::Client>,
+        distribution: Distribution,
+        next:         Arc<AtomicUsize>,
+    },
+    /// Write the transactions to a file instead of submitting them.
+    File {
+        file:   Arc<Mutex<std::fs::File>>,
+        format: OutputFormat,
+    },
+    /// Drop the transactions instead of submitting them.
+    Discard,
+}
+
+/// Submits transactions to one or more nodes, or to a file when doing a dry
+/// run. Clones of a submitter share the connections or the file, and the
+/// position of the round-robin distribution.
+#[derive(Clone)]
+pub struct Submitter {
+    target: Target,
+}
+
+impl Submitter {
+    pub fn new(clients: Vec<v2::Client>, distribution: Distribution) -> anyhow::Result<Self> {
+        anyhow::ensure!(!clients.is_empty(), "At least one node must be given.");
+        Ok(Self {
+            target: Target::Nodes {
+                clients,
+                distribution,
+                next: Arc::default(),
+            },
+        })
+    }
+
+    /// A submitter that does not send anything to the nodes. The transactions
+    /// are written to the given file if any, and are otherwise dropped.
+    pub fn dry_run(output: Option<&Path>, format: OutputFormat) -> anyhow::Result<Self> {
+        let target = match output {
+            Some(output) => Target::File {
+                file: Arc::new(Mutex::new(
+                    std::fs::File::create(output).context("Could not create the output file.")?,
+                )),
+                format,
+            },
+            None => Target::Discard,
+        };
+        Ok(Self { target })
+    }
+
+    /// Submit the transaction to the node chosen by the distribution. When
+    /// broadcasting, the transaction is submitted to all nodes concurrently,
+    /// and the submission succeeds if any node accepts it, since the other
+    /// nodes may already have received it from their peers.
+    ///
+    /// In a dry run the transaction is written to the output file instead,
+    /// and its hash is returned.
+    pub async fn send(
+        &mut self,
+        item: &BlockItem<EncodedPayload>,
+    ) -> anyhow::Result<TransactionHash> {
+        let (clients, distribution, next) = match &mut self.target {
+            Target::Nodes {
+                clients,
+                distribution,
+                next,
+            } => (clients, *distribution, next),
+            Target::File { file, format } => {
+                // Write each transaction with a single call, so that the file is
+                // complete up to the last transaction if the generator is stopped.
+                let mut bytes = Vec::new();
+                match format {
+                    OutputFormat::Binary => write_signed_transaction(&mut bytes, item)?,
+                    OutputFormat::Json => {
+                        serde_json::to_writer(&mut bytes, &block_item_to_json(item)?)?;
+                        bytes.push(b'\n');
+                    }
+                }
+                file.lock()
+                    .expect("Lock is not poisoned.")
+                    .write_all(&bytes)
+                    .context("Could not write to the output file.")?;
+                return Ok(item.hash());
+            }
+            Target::Discard => return Ok(item.hash()),
+        };
+        // Index of the node chosen by the given bytes.
+        let index_of = |bytes: &[u8]| {
+            let mut prefix = [0u8; 8];
+            prefix.copy_from_slice(&bytes[..8]);
+            (u64::from_le_bytes(prefix) % clients.len() as u64) as usize
+        };
+        let index = match distribution {
+            Distribution::RoundRobin => next.fetch_add(1, Ordering::Relaxed) % clients.len(),
+            Distribution::Hash => index_of(item.hash().as_ref()),
+            Distribution::Sender => match item {
+                BlockItem::AccountTransaction(tx) => index_of(&tx.header.sender.0),
+                // Credential deployments and chain updates have no sender.
+                _ => index_of(item.hash().as_ref()),
+            },
+            Distribution::Broadcast => {
+                let results = futures::future::join_all(
+                    clients
+                        .iter_mut()
+                        .map(|client| client.send_block_item(item)),
+                )
+                .await;
+                let mut error = None;
+                for result in results {
+                    match result {
+                        Ok(transaction_hash) => return Ok(transaction_hash),
+                        Err(e) => {
+                            error.get_or_insert(e);
+                        }
+                    }
+                }
+                return Err(error.expect("There is at least one node.").into());
+            }
+        };
+        Ok(clients[index].send_block_item(item).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake_node::{FakeNode, State};
+    use concordium_rust_sdk::{
+        common::types::TransactionTime,
+        id::types::{AccountAddress, AccountKeys},
+        types::{transactions::send, Nonce, RegisteredData, WalletAccount},
+    };
+    use rand::SeedableRng;
+
+    /// A transaction of the given sender.
+    fn transaction(sender: u8, nonce: u64) -> BlockItem<EncodedPayload> {
+        let keys = WalletAccount {
+            address: AccountAddress([sender; 32]),
+            keys:    AccountKeys::singleton(&mut rand::rngs::StdRng::seed_from_u64(0)),
+        };
+        BlockItem::AccountTransaction(send::register_data(
+            &keys,
+            keys.address,
+            Nonce { nonce },
+            TransactionTime::from_seconds(u64::MAX),
+            RegisteredData::try_from(vec![0u8; 4]).expect("The data is small enough."),
+        ))
+    }
+
+    /// Start two fake nodes and a submitter to them.
+    async fn two_nodes(
+        distribution: Distribution,
+    ) -> anyhow::Result<(Submitter, [Arc<Mutex<State>>; 2])> {
+        let (first, first_state) = FakeNode::start(State::default()).await?;
+        let (second, second_state) = FakeNode::start(State::default()).await?;
+        let submitter = Submitter::new(vec![first, second], distribution)?;
+        Ok((submitter, [first_state, second_state]))
+    }
+
+    fn submitted(states: &[Arc<Mutex<State>>; 2]) -> [usize; 2] {
+        states
+            .each_ref()
+            .map(|state| state.lock().expect("Lock is not poisoned.").submitted)
+    }
+
+    #[tokio::test]
+    async fn round_robin_alternates_nodes() -> anyhow::Result<()> {
+        let (mut submitter, states) = two_nodes(Distribution::RoundRobin).await?;
+        for nonce in 1..=4 {
+            submitter.send(&transaction(1, nonce)).await?;
+        }
+        assert_eq!(submitted(&states), [2, 2]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sender_distribution_uses_one_node_per_sender() -> anyhow::Result<()> {
+        let (mut submitter, states) = two_nodes(Distribution::Sender).await?;
+        for nonce in 1..=3 {
+            submitter.send(&transaction(1, nonce)).await?;
+        }
+        let counts = submitted(&states);
+        assert!(counts == [3, 0] || counts == [0, 3], "Got {counts:?}.");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn broadcast_sends_to_all_nodes() -> anyhow::Result<()> {
+        let (mut submitter, states) = two_nodes(Distribution::Broadcast).await?;
+        for nonce in 1..=3 {
+            submitter.send(&transaction(1, nonce)).await?;
+        }
+        assert_eq!(submitted(&states), [3, 3]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dry_run_returns_hash_of_transaction() -> anyhow::Result<()> {
+        let mut submitter = Submitter::dry_run(None, OutputFormat::Binary)?;
+        let item = transaction(1, 1);
+        assert_eq!(submitter.send(&item).await?, item.hash());
+        Ok(())
+    }
+}
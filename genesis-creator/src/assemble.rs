@@ -8,6 +8,9 @@ use crate::config::ProtocolConfig;
 /// parameters, the foundation account and where to find genesis accounts,
 /// anonymity revokers, identity providers, cryptographic parameters and where
 /// to output the genesis data file.
+///
+/// As for [`Config`](crate::config::Config), unknown top-level keys are
+/// rejected by the flattened protocol configuration.
 #[derive(SerdeDeserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct AssembleGenesisConfig {
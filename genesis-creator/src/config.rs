@@ -1,6 +1,6 @@
 //! Input configuration structures and parsing.
 use crate::genesis::{GenesisParametersConfigV0, GenesisParametersConfigV1};
-use anyhow::ensure;
+use anyhow::{ensure, Context};
 
 use concordium_rust_sdk::{
     common::{types::Amount, SerdeDeserialize},
@@ -10,13 +10,19 @@ use concordium_rust_sdk::{
         AccessStructure, ProtocolVersion, UpdateKeysIndex, UpdateKeysThreshold, UpdatePublicKey,
     },
 };
-use std::{collections::BTreeSet, path::PathBuf};
+use serde::{
+    de::{DeserializeOwned, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use serde_path_to_error::Segment;
+use std::{collections::BTreeSet, ops::Range, path::PathBuf};
+use toml::Spanned;
 
 /// Struct for specifying the cryptographic parameters. Either a path to a file
 /// with existing gryptographic parameters, or the genesis string from which
 /// theg5 cryptographic parameters should be generated.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(tag = "kind", rename_all = "camelCase")]
+#[serde(tag = "kind", rename_all = "camelCase", deny_unknown_fields)]
 pub enum CryptoParamsConfig {
     #[serde(rename_all = "camelCase")]
     Existing { source: PathBuf },
@@ -30,7 +36,7 @@ pub enum CryptoParamsConfig {
 /// it specifies that `n` anonymity revokers should be generated freshly,
 /// starting from the given id.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(tag = "kind", rename_all = "camelCase")]
+#[serde(tag = "kind", rename_all = "camelCase", deny_unknown_fields)]
 pub enum AnonymityRevokerConfig {
     #[serde(rename_all = "camelCase")]
     Existing { source: PathBuf },
@@ -47,7 +53,7 @@ pub enum AnonymityRevokerConfig {
 /// specifies that `n` identity providers should be generated freshly, starting
 /// from the given id.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(tag = "kind", rename_all = "camelCase")]
+#[serde(tag = "kind", rename_all = "camelCase", deny_unknown_fields)]
 pub enum IdentityProviderConfig {
     #[serde(rename_all = "camelCase")]
     Existing { source: PathBuf },
@@ -62,7 +68,7 @@ pub enum IdentityProviderConfig {
 /// with an existing account is given, or a fresh account should be generated
 /// freshly.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(tag = "kind", rename_all = "camelCase")]
+#[serde(tag = "kind", rename_all = "camelCase", deny_unknown_fields)]
 pub enum AccountConfig {
     #[serde(rename_all = "camelCase")]
     Existing {
@@ -94,11 +100,21 @@ pub enum AccountConfig {
     },
 }
 
+impl AccountConfig {
+    /// The number of genesis accounts specified by the entry.
+    pub fn num_accounts(&self) -> u32 {
+        match self {
+            AccountConfig::Existing { .. } => 1,
+            AccountConfig::Fresh { repeat, .. } => repeat.unwrap_or(1),
+        }
+    }
+}
+
 /// Struct for specifying which level 2 keys can authorize a concrete level 2
 /// chain update, together with a threshold specifying how many of the given
 /// keys are needed.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Level2UpdateConfig {
     pub authorized_keys: Vec<UpdateKeysIndex>,
     pub threshold:       UpdateKeysThreshold,
@@ -134,7 +150,7 @@ impl Level2UpdateConfig {
 /// keys `Level2UpdateConfig` determining the keys that can authorize the
 /// update.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Level2KeysConfig {
     pub keys: Vec<HigherLevelKey>,
     pub emergency: Level2UpdateConfig,
@@ -157,7 +173,7 @@ pub struct Level2KeysConfig {
 
 /// Struct holding the root or the level 1 keys, together with a threshold.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct HigherLevelKeysConfig {
     pub threshold: UpdateKeysThreshold,
     pub keys:      Vec<HigherLevelKey>,
@@ -166,7 +182,7 @@ pub struct HigherLevelKeysConfig {
 /// Struct for specifying a key. Either a path to an existing key, or a `u32`
 /// specifying how many keys should be generated freshly.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(tag = "kind", rename_all = "camelCase")]
+#[serde(tag = "kind", rename_all = "camelCase", deny_unknown_fields)]
 pub enum HigherLevelKey {
     Existing { source: PathBuf },
     Fresh { repeat: u32 },
@@ -174,6 +190,7 @@ pub enum HigherLevelKey {
 
 /// Struct holding all the root, level 1 and level 2 keys.
 #[derive(SerdeDeserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateKeysConfig {
     pub root:   HigherLevelKeysConfig,
     pub level1: HigherLevelKeysConfig,
@@ -185,7 +202,7 @@ pub struct UpdateKeysConfig {
 /// `genesis.dat` file. The `delete_existing` field specifies whether to delete
 /// existing files before generation.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct OutputConfig {
     pub update_keys:              Option<PathBuf>,
     pub account_keys:             PathBuf,
@@ -201,6 +218,9 @@ pub struct OutputConfig {
 
 /// Struct representing the configuration specified by the input TOML file for
 /// every protocol version.
+///
+/// Unknown fields cannot be denied here because of the flattened protocol
+/// configuration, which instead rejects any top-level keys not consumed here.
 #[derive(SerdeDeserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
@@ -224,7 +244,7 @@ pub struct Config {
 
 /// Protocol specific configurations, tagged by the protocol version.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(tag = "protocolVersion")]
+#[serde(tag = "protocolVersion", deny_unknown_fields)]
 pub enum ProtocolConfig {
     #[serde(rename = "1")]
     P1 {
@@ -269,3 +289,471 @@ impl ProtocolConfig {
         }
     }
 }
+
+/// Parse a TOML configuration file.
+///
+/// All configuration structures reject unknown keys. For such errors, and for
+/// unknown values of tags such as `kind`, the error points at the line and
+/// column of the offending entry, gives its full key path, and suggests the
+/// closest expected name. A missing field points at a similarly named key if
+/// there is one. Other errors give the key path and location of the value
+/// that could not be parsed.
+pub fn parse_config<T: DeserializeOwned>(source: &[u8]) -> anyhow::Result<T> {
+    let source =
+        std::str::from_utf8(source).context("The configuration file is not valid UTF-8.")?;
+    serde_path_to_error::deserialize(toml::Deserializer::new(source))
+        .map_err(|e| explain_error(source, e))
+}
+
+/// A TOML value that keeps the location of its keys and values, used to
+/// point at entries of the configuration file in error messages.
+enum SourceValue {
+    Table(Vec<(Spanned<String>, Spanned<SourceValue>)>),
+    Array(Vec<Spanned<SourceValue>>),
+    String(String),
+    Other,
+}
+
+impl<'de> Deserialize<'de> for SourceValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SourceValueVisitor;
+
+        impl<'de> Visitor<'de> for SourceValueVisitor {
+            type Value = SourceValue;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a TOML value")
+            }
+
+            fn visit_bool<E>(self, _: bool) -> Result<SourceValue, E> { Ok(SourceValue::Other) }
+
+            fn visit_i64<E>(self, _: i64) -> Result<SourceValue, E> { Ok(SourceValue::Other) }
+
+            fn visit_u64<E>(self, _: u64) -> Result<SourceValue, E> { Ok(SourceValue::Other) }
+
+            fn visit_f64<E>(self, _: f64) -> Result<SourceValue, E> { Ok(SourceValue::Other) }
+
+            fn visit_str<E>(self, value: &str) -> Result<SourceValue, E> {
+                Ok(SourceValue::String(value.to_string()))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<SourceValue, A::Error> {
+                let mut values = Vec::new();
+                while let Some(value) = seq.next_element()? {
+                    values.push(value);
+                }
+                Ok(SourceValue::Array(values))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<SourceValue, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(SourceValue::Table(entries))
+            }
+        }
+
+        deserializer.deserialize_any(SourceValueVisitor)
+    }
+}
+
+/// An entry `key = value` of a TOML file, including entries of inline tables.
+struct SourceEntry<'a> {
+    /// Full path of the entry, e.g., `accounts[2].restakeEarnings`.
+    path:      String,
+    key:       &'a Spanned<String>,
+    value:     &'a Spanned<SourceValue>,
+    /// The keys of the table the entry belongs to.
+    siblings:  Vec<&'a str>,
+    /// Whether the entry is a top-level key of the file.
+    top_level: bool,
+}
+
+/// Collect the entries of `value` and of all values nested in it, in the
+/// order they appear in the file.
+fn source_entries<'a>(path: &str, value: &'a SourceValue, entries: &mut Vec<SourceEntry<'a>>) {
+    match value {
+        SourceValue::Table(table) => {
+            let siblings: Vec<&str> = table.iter().map(|(k, _)| k.get_ref().as_str()).collect();
+            for (key, value) in table {
+                let entry_path = join_path(path, key.get_ref());
+                entries.push(SourceEntry {
+                    path: entry_path.clone(),
+                    key,
+                    value,
+                    siblings: siblings.clone(),
+                    top_level: path.is_empty(),
+                });
+                source_entries(&entry_path, value.get_ref(), entries);
+            }
+        }
+        SourceValue::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                source_entries(&format!("{}[{}]", path, i), value.get_ref(), entries);
+            }
+        }
+        SourceValue::String(_) | SourceValue::Other => {}
+    }
+}
+
+/// Append a key to a key path, which is empty at the top level.
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// The location of a span in the source, as ` at line _, column _`.
+fn location(source: &str, span: Range<usize>) -> String {
+    let before = &source[..span.start];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    format!(
+        " at line {}, column {}",
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1
+    )
+}
+
+/// Rewrite errors about unknown fields and variants to point at the entry in
+/// the source, and errors about missing fields to point at a similar key.
+///
+/// The key path of the error stops at values that serde buffers, such as the
+/// internally tagged `kind` enums and the flattened protocol configuration,
+/// so the entry is looked up among the values nested in the last value of
+/// the path. Keys of tables that are only implicitly defined by a header such
+/// as `[a.b]` have no location, so if there are any, the error only gives the
+/// key path.
+fn explain_error(
+    source: &str,
+    error: serde_path_to_error::Error<toml::de::Error>,
+) -> anyhow::Error {
+    let root: Option<SourceValue> = toml::from_str(source).ok();
+    // The values along the key path of the error, with their paths.
+    let mut ancestors: Vec<(String, &SourceValue)> =
+        root.iter().map(|root| (String::new(), root)).collect();
+    for segment in error.path().iter() {
+        let (path, value) = match ancestors.last() {
+            Some(last) => last,
+            None => break,
+        };
+        let child = match (segment, value) {
+            (Segment::Map { key } | Segment::Enum { variant: key }, SourceValue::Table(table)) => {
+                table
+                    .iter()
+                    .find(|(k, _)| k.get_ref() == key)
+                    .map(|(_, v)| (join_path(path, key), v.get_ref()))
+            }
+            (Segment::Seq { index }, SourceValue::Array(values)) => values
+                .get(*index)
+                .map(|v| (format!("{}[{}]", path, index), v.get_ref())),
+            _ => None,
+        };
+        match child {
+            Some(child) => ancestors.push(child),
+            None => break,
+        }
+    }
+    let error_path = match error.path().to_string() {
+        path if path == "." => String::new(),
+        path => path,
+    };
+    let last_key = match error.path().iter().next_back() {
+        Some(Segment::Map { key }) => Some(key.clone()),
+        _ => None,
+    };
+    let error = error.into_inner();
+    let description = error.message();
+    let names = backticked(description);
+    // Look up the first entry satisfying `accept` nested in the innermost
+    // value on the path that has any.
+    let locate = |accept: &dyn Fn(&SourceEntry) -> bool| {
+        ancestors.iter().rev().find_map(|(path, value)| {
+            let mut entries = Vec::new();
+            source_entries(path, value, &mut entries);
+            entries.into_iter().find(|e| accept(e))
+        })
+    };
+
+    let explanation = match names.split_first() {
+        Some((name, expected)) if description.starts_with("unknown field `") => {
+            // The table containing an unknown field should contain some of
+            // the expected fields, which tells apart keys of the same name in
+            // different tables.
+            let entry = locate(&|e| {
+                e.key.get_ref() == name && e.siblings.iter().any(|s| expected.contains(s))
+            })
+            .or_else(|| locate(&|e| e.key.get_ref() == name));
+            let (path, location, top_level) = match entry {
+                Some(e) => (e.path, location(source, e.key.span()), e.top_level),
+                None if last_key.as_deref() == Some(*name) => {
+                    (error_path.clone(), String::new(), error_path == *name)
+                }
+                None => (
+                    join_path(&error_path, name),
+                    String::new(),
+                    error_path.is_empty(),
+                ),
+            };
+            // Top-level keys are split between the configuration and the
+            // flattened protocol configuration, and serde only reports the
+            // latter as expected, so do not list them.
+            let listed = if top_level { &[] } else { expected };
+            Some(
+                format!("Unknown field `{}`{}.", path, location)
+                    + &suggestion(name, expected, listed),
+            )
+        }
+        Some((name, expected)) if description.starts_with("unknown variant `") => {
+            let entry =
+                locate(&|e| matches!(e.value.get_ref(), SourceValue::String(s) if s == name));
+            let (path, location) = match entry {
+                Some(e) => (e.path, location(source, e.value.span())),
+                None => (error_path.clone(), String::new()),
+            };
+            if path.is_empty() {
+                None
+            } else {
+                Some(
+                    format!("Unknown value `{}` for `{}`{}.", name, path, location)
+                        + &suggestion(name, expected, expected),
+                )
+            }
+        }
+        Some((name, _)) if description.starts_with("missing field `") => {
+            // A missing field is often a misspelled one, which is otherwise
+            // ignored in structures that allow unknown fields.
+            ancestors.last().and_then(|(path, value)| {
+                let mut entries = Vec::new();
+                source_entries(path, value, &mut entries);
+                let lower = name.to_lowercase();
+                let entry = entries
+                    .into_iter()
+                    .filter(|e| {
+                        e.key.get_ref() != name && closest(e.key.get_ref(), &[name]).is_some()
+                    })
+                    .min_by_key(|e| edit_distance(&e.key.get_ref().to_lowercase(), &lower))?;
+                Some(format!(
+                    "Missing field `{}`, but found `{}`{}. Did you mean `{}`?",
+                    join_path(path, name),
+                    entry.path,
+                    location(source, entry.key.span()),
+                    name
+                ))
+            })
+        }
+        _ => None,
+    };
+    match (explanation, error.span()) {
+        (Some(explanation), _) => anyhow::anyhow!(explanation),
+        (None, _) if error_path.is_empty() => error.into(),
+        (None, span) => anyhow::anyhow!(
+            "Error in `{}`{}: {}.",
+            error_path,
+            span.map(|span| location(source, span)).unwrap_or_default(),
+            description
+        ),
+    }
+}
+
+/// Suggest the expected name closest to `name`, or list the names in `listed`
+/// if none is close.
+fn suggestion(name: &str, expected: &[&str], listed: &[&str]) -> String {
+    if let Some(closest) = closest(name, expected) {
+        format!(" Did you mean `{}`?", closest)
+    } else if listed.is_empty() {
+        String::new()
+    } else {
+        let expected = listed
+            .iter()
+            .map(|e| format!("`{}`", e))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(" Expected one of {}.", expected)
+    }
+}
+
+/// The names enclosed in backticks in an error message.
+fn backticked(message: &str) -> Vec<&str> { message.split('`').skip(1).step_by(2).collect() }
+
+/// The candidate closest to `name` in edit distance, ignoring case, if it is
+/// close enough to likely be what was meant.
+fn closest<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    let max_distance = std::cmp::max(2, name.chars().count() / 3);
+    candidates
+        .iter()
+        .map(|c| (edit_distance(&name, &c.to_lowercase()), *c))
+        // Exclude candidates that share hardly anything with the name, which
+        // happens for short names.
+        .filter(|(d, c)| *d <= max_distance && *d < name.len().min(c.len()))
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+/// Edit distance between two strings, counting insertions, deletions and
+/// substitutions of characters and transpositions of adjacent characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // Distances from the current and the two previous prefixes of `a` to all
+    // prefixes of `b`.
+    let mut before_previous = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut current = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            current[j] = (previous[j - 1] + usize::from(a[i - 1] != b[j - 1]))
+                .min(previous[j] + 1)
+                .min(current[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before_previous[j - 2] + 1);
+            }
+        }
+        before_previous = std::mem::replace(&mut previous, current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// The error message of parsing the `genesis6.toml` example with the only
+    /// occurrence of `from` replaced by `to`.
+    fn error(from: &str, to: &str) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("examples")
+            .join("genesis6.toml");
+        let source = std::fs::read_to_string(path).unwrap();
+        assert_eq!(source.matches(from).count(), 1, "`{}` is not unique", from);
+        parse_config::<Config>(source.replace(from, to).as_bytes())
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn example_parses() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("examples")
+            .join("genesis6.toml");
+        let config: Config = parse_config(&std::fs::read(path).unwrap()).unwrap();
+        assert_eq!(config.protocol.protocol_version(), ProtocolVersion::P6);
+    }
+
+    #[test]
+    fn unknown_account_field() {
+        assert_eq!(
+            error(
+                "foundation = true",
+                "foundation = true\nrestakeEarning = true"
+            ),
+            "Unknown field `accounts[1].restakeEarning` at line 54, column 1. Did you mean \
+             `restakeEarnings`?"
+        );
+        assert_eq!(
+            error("repeat = 5", "repeat = 5\nstake_amount = 1"),
+            "Unknown field `accounts[0].stake_amount` at line 44, column 1. Expected one of \
+             `repeat`, `stake`, `balance`, `template`, `identityProvider`, `numKeys`, \
+             `threshold`, `restakeEarnings`, `foundation`."
+        );
+    }
+
+    #[test]
+    fn unknown_output_field() {
+        assert_eq!(
+            error("deleteExisting", "deleteExisitng"),
+            "Unknown field `out.deleteExisitng` at line 18, column 1. Did you mean \
+             `deleteExisting`?"
+        );
+    }
+
+    #[test]
+    fn unknown_inline_table_field() {
+        assert_eq!(
+            error("repeat = 7", "repaet = 7"),
+            "Unknown field `updates.root.keys[0].repaet` at line 56, column 50. Did you mean \
+             `repeat`?"
+        );
+        assert_eq!(
+            error("{max = 1.0, min = 1.0}", "{max = 1.0, mni = 1.0}"),
+            "Unknown field `parameters.chain.poolParameters.finalizationCommissionRange.mni` at \
+             line 107, column 43. Did you mean `min`?"
+        );
+    }
+
+    #[test]
+    fn unknown_chain_parameter_field() {
+        assert_eq!(
+            error("capitalBound = 0.1", "capitalBund = 0.1"),
+            "Unknown field `parameters.chain.poolParameters.capitalBund` at line 111, column 1. \
+             Did you mean `capitalBound`?"
+        );
+        assert_eq!(
+            error("gasAccount = 0.45", "gasAcount = 0.45"),
+            "Unknown field \
+             `parameters.chain.rewardParameters.transactionFeeDistribution.gasAcount` at line \
+             120, column 46. Did you mean `gasAccount`?"
+        );
+        assert_eq!(
+            error("delegatorCooldown", "delegatorCoolDown"),
+            "Unknown field `parameters.chain.cooldownParameters.delegatorCoolDown` at line 116, \
+             column 1. Did you mean `delegatorCooldown`?"
+        );
+    }
+
+    #[test]
+    fn unknown_top_level_field() {
+        assert_eq!(
+            error(
+                "protocolVersion = \"6\"",
+                "protocolVersion = \"6\"\nparameter = 1"
+            ),
+            "Unknown field `parameter` at line 9, column 1. Did you mean `parameters`?"
+        );
+        // The `parameters` table is then only defined implicitly by the
+        // headers of its subtables, so the keys have no location.
+        assert_eq!(
+            error("[parameters]", "[paramters]"),
+            "Unknown field `paramters`. Did you mean `parameters`?"
+        );
+    }
+
+    #[test]
+    fn unknown_variant() {
+        assert_eq!(
+            error("kind = \"fresh\"\nid = 0", "kind = \"frehs\"\nid = 0"),
+            "Unknown value `frehs` for `identityProviders[0].kind` at line 31, column 8. Did you \
+             mean `fresh`?"
+        );
+        assert_eq!(
+            error(
+                "{kind = \"fresh\", repeat = 7}",
+                "{kind = \"new\", repeat = 7}"
+            ),
+            "Unknown value `new` for `updates.root.keys[0].kind` at line 56, column 41. Expected \
+             one of `existing`, `fresh`."
+        );
+    }
+
+    #[test]
+    fn misspelled_missing_field() {
+        assert_eq!(
+            error("[cryptographicParameters]", "[cryptographicParameter]"),
+            "Missing field `cryptographicParameters`, but found `cryptographicParameter` at line \
+             21, column 2. Did you mean `cryptographicParameters`?"
+        );
+    }
+
+    #[test]
+    fn invalid_value() {
+        assert_eq!(
+            error("repeat = 5", "repeat = \"5\""),
+            "Error in `accounts[0]` at line 35, column 1: invalid type: string \"5\", expected \
+             u32."
+        );
+    }
+}
@@ -3,7 +3,8 @@ use concordium_rust_sdk::{
     base as concordium_base,
     common::{
         types::{Amount, CredentialIndex, Ratio, Timestamp},
-        Buffer, SerdeDeserialize, SerdeSerialize, Serial, Serialize, Versioned,
+        Buffer, Deserial, Get, ParseResult, ReadBytesExt, SerdeDeserialize, SerdeSerialize, Serial,
+        Serialize, Versioned, VERSION_0,
     },
     id,
     id::{
@@ -16,12 +17,15 @@ use concordium_rust_sdk::{
     smart_contracts::common::Duration,
     types::{
         hashes::{BlockHash, LeadershipElectionNonce},
-        AccountIndex, AccountThreshold, BakerAggregationVerifyKey, BakerElectionVerifyKey, BakerId,
-        BakerSignatureVerifyKey, BlockHeight, ChainParameterVersion0, ChainParameterVersion1,
-        ChainParameterVersion2, ChainParameters, ChainParametersV0, ChainParametersV1,
-        ChainParametersV2, CooldownParameters, ElectionDifficulty, Energy, Epoch, ExchangeRate,
-        PartsPerHundredThousands, PoolParameters, ProtocolVersion, RewardParameters, Slot,
-        SlotDuration, TimeParameters, TimeoutParameters, UpdateKeysCollection,
+        AccountIndex, AccountThreshold, AmountFraction, BakerAggregationVerifyKey,
+        BakerElectionVerifyKey, BakerId, BakerSignatureVerifyKey, BlockHeight, CapitalBound,
+        ChainParameterVersion0, ChainParameterVersion1, ChainParameterVersion2, ChainParameters,
+        ChainParametersV0, ChainParametersV1, ChainParametersV2, CommissionRanges,
+        CooldownParameters, DurationSeconds, ElectionDifficulty, Energy, Epoch, ExchangeRate,
+        GASRewards, GASRewardsV1, InclusiveRange, LeverageFactor, MintDistributionV0,
+        MintDistributionV1, MintRate, PartsPerHundredThousands, PoolParameters, ProtocolVersion,
+        RewardParameters, RewardParametersSkeleton, RewardPeriodLength, Slot, SlotDuration,
+        TimeParameters, TimeoutParameters, TransactionFeeDistribution, UpdateKeysCollection,
     },
 };
 use serde::de;
@@ -75,7 +79,10 @@ pub struct GenesisAccountPublic {
     pub address:           AccountAddress,
     pub account_threshold: AccountThreshold,
     #[map_size_length = 8]
-    #[serde(deserialize_with = "deserialize_versioned_public_account")]
+    #[serde(
+        deserialize_with = "deserialize_versioned_public_account",
+        serialize_with = "serialize_versioned_public_account"
+    )]
     pub credentials:       GenesisCredentials,
     pub balance:           Amount,
     pub baker:             Option<GenesisBakerPublic>,
@@ -89,10 +96,17 @@ fn deserialize_versioned_public_account<'de, D: de::Deserializer<'de>>(
     Ok(versioned.value)
 }
 
+fn serialize_versioned_public_account<S: serde::Serializer>(
+    credentials: &GenesisCredentials,
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    Versioned::new(VERSION_0, credentials).serialize(ser)
+}
+
 /// The finalization parameters. Corresponds to the Haskell type
 /// `FinalizationParameters` in haskell-src/Concordium/Types/Parameters.hs.
-#[derive(SerdeDeserialize, Serialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
+#[derive(SerdeDeserialize, SerdeSerialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct FinalizationParameters {
     /// Number of levels to skip between finalizations.
     minimum_skip:        BlockHeight,
@@ -119,17 +133,27 @@ pub struct FinalizationParameters {
     allow_zero_delay:    bool,
 }
 
+impl FinalizationParameters {
+    /// The maximum size of the finalization committee.
+    pub fn committee_max_size(&self) -> u32 { self.committee_max_size }
+}
+
 /// Genesis chain parameters version 0. Contains all version 0 chain paramters
 /// except for the foundation account index.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct GenesisChainParametersV0 {
     election_difficulty:          ElectionDifficulty,
+    #[serde(deserialize_with = "deserialize_exchange_rate")]
     euro_per_energy:              ExchangeRate,
-    #[serde(rename = "microCCDPerEuro")]
+    #[serde(
+        rename = "microCCDPerEuro",
+        deserialize_with = "deserialize_exchange_rate"
+    )]
     micro_ccd_per_euro:           ExchangeRate,
     account_creation_limit:       u16,
     baker_cooldown_epochs:        Epoch,
+    #[serde(deserialize_with = "deserialize_strict")]
     reward_parameters:            RewardParameters<ChainParameterVersion0>,
     minimum_threshold_for_baking: Amount,
 }
@@ -161,16 +185,24 @@ impl GenesisChainParametersV0 {
 /// Genesis chain parameters version 1. Contains all version 1 chain parameters
 /// except for the foundation account index.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct GenesisChainParametersV1 {
     election_difficulty:    ElectionDifficulty,
+    #[serde(deserialize_with = "deserialize_exchange_rate")]
     euro_per_energy:        ExchangeRate,
-    #[serde(rename = "microCCDPerEuro")]
+    #[serde(
+        rename = "microCCDPerEuro",
+        deserialize_with = "deserialize_exchange_rate"
+    )]
     micro_ccd_per_euro:     ExchangeRate,
     account_creation_limit: u16,
+    #[serde(deserialize_with = "deserialize_strict")]
     reward_parameters:      RewardParameters<ChainParameterVersion1>,
+    #[serde(deserialize_with = "deserialize_strict")]
     time_parameters:        TimeParameters,
+    #[serde(deserialize_with = "deserialize_strict")]
     pool_parameters:        PoolParameters,
+    #[serde(deserialize_with = "deserialize_strict")]
     cooldown_parameters:    CooldownParameters,
 }
 
@@ -202,21 +234,30 @@ impl GenesisChainParametersV1 {
 
 /// Genesis chain parameters version 2.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct GenesisChainParametersV2 {
     /// Consensus protocol version 2 timeout parameters.
+    #[serde(deserialize_with = "deserialize_strict")]
     pub timeout_parameters:                TimeoutParameters,
     /// Minimum time interval between blocks.
     pub min_block_time:                    Duration,
     /// Maximum energy allowed per block.
     pub block_energy_limit:                Energy,
+    #[serde(deserialize_with = "deserialize_exchange_rate")]
     pub euro_per_energy:                   ExchangeRate,
-    #[serde(rename = "microCCDPerEuro")]
+    #[serde(
+        rename = "microCCDPerEuro",
+        deserialize_with = "deserialize_exchange_rate"
+    )]
     pub micro_ccd_per_euro:                ExchangeRate,
     pub account_creation_limit:            u16,
+    #[serde(deserialize_with = "deserialize_strict")]
     pub reward_parameters:                 RewardParameters<ChainParameterVersion2>,
+    #[serde(deserialize_with = "deserialize_strict")]
     pub time_parameters:                   TimeParameters,
+    #[serde(deserialize_with = "deserialize_strict")]
     pub pool_parameters:                   PoolParameters,
+    #[serde(deserialize_with = "deserialize_strict")]
     pub cooldown_parameters:               CooldownParameters,
     pub finalization_committee_parameters: FinalizationCommitteeParametersConfig,
 }
@@ -244,7 +285,7 @@ impl GenesisChainParametersV2 {
 }
 
 #[derive(SerdeDeserialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct FinalizationCommitteeParametersConfig {
     /// Minimum number of bakers to include in the finalization committee before
     /// the '_fcpFinalizerRelativeStakeThreshold' takes effect.
@@ -297,7 +338,7 @@ impl GenesisChainParameters {
 ///
 /// Used to derive parsing for the genesis parameter section of the TOML config.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct GenesisParametersConfigV0 {
     /// Time at which the genesis will occur. If `None` then the tool will use
     /// "current" time as genesis time.
@@ -342,7 +383,8 @@ impl GenesisParametersConfigV0 {
 
 /// The core genesis parameters. This corresponds to the Haskell type in
 /// haskell-src/Concordium/Genesis/Data/Base.hs in concordium-base.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, SerdeSerialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CoreGenesisParametersV0 {
     /// Nominal time of the genesis block.
     pub time:                    Timestamp,
@@ -361,39 +403,354 @@ pub struct CoreGenesisParametersV0 {
 ///
 /// Used to derive parsing for the genesis parameter section of the TOML config.
 #[derive(SerdeDeserialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct GenesisParametersConfigV1 {
     /// Leadership election nonce.
     pub leadership_election_nonce: LeadershipElectionNonce,
-    #[serde(flatten)]
-    pub core: CoreGenesisParametersConfigV1,
-    pub chain: GenesisChainParametersV2,
-}
-
-/// The core genesis parameters. This corresponds to the Haskell type in
-/// haskell-src/Concordium/Genesis/Data/BaseV1.hs in concordium-base.
-#[derive(Debug, SerdeDeserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct CoreGenesisParametersConfigV1 {
-    /// Nominal time of the genesis block.
-    pub genesis_time:        Option<chrono::DateTime<chrono::Utc>>,
+    /// Nominal time of the genesis block. If `None` then the tool will use
+    /// "current" time as genesis time.
+    pub genesis_time:              Option<chrono::DateTime<chrono::Utc>>,
     /// Duration of an epoch.
-    pub epoch_duration:      Duration,
+    pub epoch_duration:            Duration,
     /// Fractional weight of signatures required for a quorum certificate or
     /// timeout certificate. This must be in the range [2/3, 1], and should
     /// generally be set to 2/3.
-    pub signature_threshold: RatioNumDenomSerde,
+    pub signature_threshold:       RatioNumDenomSerde,
+    pub chain:                     GenesisChainParametersV2,
 }
 
 /// Type for deriving serde deserialization for ratio which uses numerator and
 /// denominator instead of decimals.
-#[derive(Debug, SerdeDeserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Copy, SerdeDeserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct RatioNumDenomSerde {
+    #[serde(deserialize_with = "deserialize_large_u64")]
     pub numerator:   u64,
+    #[serde(deserialize_with = "deserialize_large_u64")]
     pub denominator: u64,
 }
 
+/// Deserialize a `u64` that is given either as an integer or as a string.
+/// Integers in TOML are signed 64-bit integers, so values above `i64::MAX`
+/// have to be given as strings.
+fn deserialize_large_u64<'de, D: de::Deserializer<'de>>(des: D) -> Result<u64, D::Error> {
+    #[derive(SerdeDeserialize)]
+    #[serde(untagged)]
+    enum LargeU64 {
+        Integer(u64),
+        String(String),
+    }
+    match LargeU64::deserialize(des)? {
+        LargeU64::Integer(value) => Ok(value),
+        LargeU64::String(value) => value.parse().map_err(|_| {
+            de::Error::custom(format!("Invalid unsigned 64-bit integer \"{value}\"."))
+        }),
+    }
+}
+
+/// Deserialize an exchange rate, where the numerator and denominator can also
+/// be given as strings, see [`deserialize_large_u64`].
+fn deserialize_exchange_rate<'de, D: de::Deserializer<'de>>(
+    des: D,
+) -> Result<ExchangeRate, D::Error> {
+    #[derive(SerdeDeserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Parts {
+        #[serde(deserialize_with = "deserialize_large_u64")]
+        numerator:   u64,
+        #[serde(deserialize_with = "deserialize_large_u64")]
+        denominator: u64,
+    }
+    #[derive(SerdeDeserialize)]
+    #[serde(untagged)]
+    enum ExchangeRateConfig {
+        Rate(ExchangeRate),
+        Parts(Parts),
+    }
+    match ExchangeRateConfig::deserialize(des)? {
+        ExchangeRateConfig::Rate(rate) => Ok(rate),
+        ExchangeRateConfig::Parts(Parts {
+            numerator,
+            denominator,
+        }) => ExchangeRate::new(numerator, denominator).ok_or_else(|| {
+            de::Error::custom(
+                "The numerator and denominator of an exchange rate must be non-zero and in \
+                 reduced form.",
+            )
+        }),
+    }
+}
+
+/// SDK types whose serde deserialization silently ignores unknown fields. In
+/// the configuration they are deserialized through a mirror that denies
+/// unknown fields, so that a misspelled key is reported instead of ignored.
+trait StrictDeserialize: Sized {
+    type Mirror: de::DeserializeOwned;
+
+    fn from_mirror(mirror: Self::Mirror) -> anyhow::Result<Self>;
+}
+
+/// Deserialize a value through its mirror, see [`StrictDeserialize`].
+fn deserialize_strict<'de, D: de::Deserializer<'de>, T: StrictDeserialize>(
+    des: D,
+) -> Result<T, D::Error> {
+    T::from_mirror(T::Mirror::deserialize(des)?).map_err(de::Error::custom)
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct RewardParametersMirror<MintDistribution, GasRewards> {
+    mint_distribution:            MintDistribution,
+    #[serde(deserialize_with = "deserialize_strict")]
+    transaction_fee_distribution: TransactionFeeDistribution,
+    #[serde(rename = "gASRewards")]
+    gas_rewards:                  GasRewards,
+}
+
+impl<MD: StrictDeserialize, GR: StrictDeserialize> StrictDeserialize
+    for RewardParametersSkeleton<MD, GR>
+{
+    type Mirror = RewardParametersMirror<MD::Mirror, GR::Mirror>;
+
+    fn from_mirror(mirror: Self::Mirror) -> anyhow::Result<Self> {
+        Ok(Self {
+            mint_distribution:            MD::from_mirror(mirror.mint_distribution)?,
+            transaction_fee_distribution: mirror.transaction_fee_distribution,
+            gas_rewards:                  GR::from_mirror(mirror.gas_rewards)?,
+        })
+    }
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct MintDistributionV0Mirror {
+    mint_per_slot:       MintRate,
+    baking_reward:       AmountFraction,
+    finalization_reward: AmountFraction,
+}
+
+impl StrictDeserialize for MintDistributionV0 {
+    type Mirror = MintDistributionV0Mirror;
+
+    fn from_mirror(mirror: Self::Mirror) -> anyhow::Result<Self> {
+        Ok(Self {
+            mint_per_slot:       mirror.mint_per_slot,
+            baking_reward:       mirror.baking_reward,
+            finalization_reward: mirror.finalization_reward,
+        })
+    }
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct MintDistributionV1Mirror {
+    baking_reward:       AmountFraction,
+    finalization_reward: AmountFraction,
+}
+
+impl StrictDeserialize for MintDistributionV1 {
+    type Mirror = MintDistributionV1Mirror;
+
+    fn from_mirror(mirror: Self::Mirror) -> anyhow::Result<Self> {
+        Ok(Self {
+            baking_reward:       mirror.baking_reward,
+            finalization_reward: mirror.finalization_reward,
+        })
+    }
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct GasRewardsV0Mirror {
+    baker:              AmountFraction,
+    finalization_proof: AmountFraction,
+    account_creation:   AmountFraction,
+    chain_update:       AmountFraction,
+}
+
+impl StrictDeserialize for GASRewards {
+    type Mirror = GasRewardsV0Mirror;
+
+    fn from_mirror(mirror: Self::Mirror) -> anyhow::Result<Self> {
+        Ok(Self {
+            baker:              mirror.baker,
+            finalization_proof: mirror.finalization_proof,
+            account_creation:   mirror.account_creation,
+            chain_update:       mirror.chain_update,
+        })
+    }
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct GasRewardsV1Mirror {
+    baker:            AmountFraction,
+    account_creation: AmountFraction,
+    chain_update:     AmountFraction,
+}
+
+impl StrictDeserialize for GASRewardsV1 {
+    type Mirror = GasRewardsV1Mirror;
+
+    fn from_mirror(mirror: Self::Mirror) -> anyhow::Result<Self> {
+        Ok(Self {
+            baker:            mirror.baker,
+            account_creation: mirror.account_creation,
+            chain_update:     mirror.chain_update,
+        })
+    }
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct TransactionFeeDistributionMirror {
+    baker:       AmountFraction,
+    gas_account: AmountFraction,
+}
+
+impl StrictDeserialize for TransactionFeeDistribution {
+    type Mirror = TransactionFeeDistributionMirror;
+
+    fn from_mirror(mirror: Self::Mirror) -> anyhow::Result<Self> {
+        ensure!(
+            (mirror.baker + mirror.gas_account).is_some(),
+            "Transaction fee fractions exceed 100%."
+        );
+        Ok(Self {
+            baker:       mirror.baker,
+            gas_account: mirror.gas_account,
+        })
+    }
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct TimeParametersMirror {
+    reward_period_length: RewardPeriodLength,
+    mint_per_payday:      MintRate,
+}
+
+impl StrictDeserialize for TimeParameters {
+    type Mirror = TimeParametersMirror;
+
+    fn from_mirror(mirror: Self::Mirror) -> anyhow::Result<Self> {
+        Ok(Self {
+            reward_period_length: mirror.reward_period_length,
+            mint_per_payday:      mirror.mint_per_payday,
+        })
+    }
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct PoolParametersMirror {
+    passive_finalization_commission: AmountFraction,
+    passive_baking_commission:       AmountFraction,
+    passive_transaction_commission:  AmountFraction,
+    #[serde(deserialize_with = "deserialize_strict")]
+    finalization_commission_range:   InclusiveRange<AmountFraction>,
+    #[serde(deserialize_with = "deserialize_strict")]
+    baking_commission_range:         InclusiveRange<AmountFraction>,
+    #[serde(deserialize_with = "deserialize_strict")]
+    transaction_commission_range:    InclusiveRange<AmountFraction>,
+    minimum_equity_capital:          Amount,
+    capital_bound:                   CapitalBound,
+    #[serde(deserialize_with = "deserialize_strict")]
+    leverage_bound:                  LeverageFactor,
+}
+
+impl StrictDeserialize for PoolParameters {
+    type Mirror = PoolParametersMirror;
+
+    fn from_mirror(mirror: Self::Mirror) -> anyhow::Result<Self> {
+        Ok(Self {
+            passive_finalization_commission: mirror.passive_finalization_commission,
+            passive_baking_commission:       mirror.passive_baking_commission,
+            passive_transaction_commission:  mirror.passive_transaction_commission,
+            commission_bounds:               CommissionRanges {
+                finalization: mirror.finalization_commission_range,
+                baking:       mirror.baking_commission_range,
+                transaction:  mirror.transaction_commission_range,
+            },
+            minimum_equity_capital:          mirror.minimum_equity_capital,
+            capital_bound:                   mirror.capital_bound,
+            leverage_bound:                  mirror.leverage_bound,
+        })
+    }
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(deny_unknown_fields)]
+struct InclusiveRangeMirror {
+    min: AmountFraction,
+    max: AmountFraction,
+}
+
+impl StrictDeserialize for InclusiveRange<AmountFraction> {
+    type Mirror = InclusiveRangeMirror;
+
+    fn from_mirror(mirror: Self::Mirror) -> anyhow::Result<Self> {
+        Ok(Self {
+            min: mirror.min,
+            max: mirror.max,
+        })
+    }
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(deny_unknown_fields)]
+struct LeverageFactorMirror {
+    numerator:   u64,
+    denominator: u64,
+}
+
+impl StrictDeserialize for LeverageFactor {
+    type Mirror = LeverageFactorMirror;
+
+    fn from_mirror(mirror: Self::Mirror) -> anyhow::Result<Self> {
+        LeverageFactor::new(mirror.numerator, mirror.denominator)
+            .context("Invalid leverage factor.")
+    }
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct CooldownParametersMirror {
+    pool_owner_cooldown: DurationSeconds,
+    delegator_cooldown:  DurationSeconds,
+}
+
+impl StrictDeserialize for CooldownParameters {
+    type Mirror = CooldownParametersMirror;
+
+    fn from_mirror(mirror: Self::Mirror) -> anyhow::Result<Self> {
+        Ok(Self {
+            pool_owner_cooldown: mirror.pool_owner_cooldown,
+            delegator_cooldown:  mirror.delegator_cooldown,
+        })
+    }
+}
+
+#[derive(SerdeDeserialize)]
+#[serde(deny_unknown_fields)]
+struct TimeoutParametersMirror {
+    base:     Duration,
+    increase: Ratio,
+    decrease: Ratio,
+}
+
+impl StrictDeserialize for TimeoutParameters {
+    type Mirror = TimeoutParametersMirror;
+
+    fn from_mirror(mirror: Self::Mirror) -> anyhow::Result<Self> {
+        Ok(Self {
+            base:     mirror.base,
+            increase: mirror.increase,
+            decrease: mirror.decrease,
+        })
+    }
+}
+
 impl TryFrom<RatioNumDenomSerde> for Ratio {
     type Error = concordium_rust_sdk::common::types::NewRatioError;
 
@@ -402,11 +759,12 @@ impl TryFrom<RatioNumDenomSerde> for Ratio {
     }
 }
 
-impl TryFrom<CoreGenesisParametersConfigV1> for CoreGenesisParametersV1 {
-    type Error = anyhow::Error;
-
-    fn try_from(config: CoreGenesisParametersConfigV1) -> Result<Self, Self::Error> {
-        let time = if let Some(date) = config.genesis_time {
+impl GenesisParametersConfigV1 {
+    /// Convert genesis parameters to [`CoreGenesisParametersV1`]. Note that
+    /// this function is effectful in that, if the genesis time is not provided
+    /// it will use the current time as genesis time.
+    pub fn to_core(&self) -> anyhow::Result<CoreGenesisParametersV1> {
+        let time = if let Some(date) = self.genesis_time {
             date.timestamp_millis()
         } else {
             chrono::Utc::now().timestamp_millis()
@@ -419,7 +777,7 @@ impl TryFrom<CoreGenesisParametersConfigV1> for CoreGenesisParametersV1 {
             millis: time as u64,
         };
 
-        let threshold = Ratio::try_from(config.signature_threshold)?;
+        let threshold = Ratio::try_from(self.signature_threshold)?;
         let threshold_decimal = rust_decimal::Decimal::from(threshold);
         let min_threshold = rust_decimal::Decimal::from(2) / rust_decimal::Decimal::from(3);
         anyhow::ensure!(
@@ -431,9 +789,9 @@ impl TryFrom<CoreGenesisParametersConfigV1> for CoreGenesisParametersV1 {
             "Signature threshold must be 1 or less."
         );
 
-        Ok(Self {
+        Ok(CoreGenesisParametersV1 {
             genesis_time,
-            epoch_duration: config.epoch_duration,
+            epoch_duration: self.epoch_duration,
             signature_threshold: threshold,
         })
     }
@@ -441,7 +799,8 @@ impl TryFrom<CoreGenesisParametersConfigV1> for CoreGenesisParametersV1 {
 
 /// The core genesis parameters. This corresponds to the Haskell type in
 /// haskell-src/Concordium/Genesis/Data/BaseV1.hs in concordium-base.
-#[derive(Debug, Serialize, SerdeDeserialize)]
+#[derive(Debug, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CoreGenesisParametersV1 {
     /// Nominal time of the genesis block.
     pub genesis_time:        Timestamp,
@@ -474,6 +833,50 @@ fn serialize_with_length_header(data: &impl Serial, buf: &mut Vec<u8>, out: &mut
     buf.clear();
 }
 
+/// Inverse of [`serialize_with_length_header`]. Fails if the value does not
+/// use exactly the number of bytes given by the header.
+fn deserialize_with_length_header<T: Deserial>(source: &mut impl ReadBytesExt) -> ParseResult<T> {
+    let len: u32 = source.get()?;
+    let mut limited = std::io::Read::take(source, len.into());
+    let data = T::deserial(&mut limited)?;
+    ensure!(
+        limited.limit() == 0,
+        "{} bytes left after the value.",
+        limited.limit()
+    );
+    Ok(data)
+}
+
+/// Deserialize the identity providers in the format written by the
+/// [`Serial`] instances of the genesis states.
+fn deserialize_identity_providers(
+    source: &mut impl ReadBytesExt,
+) -> ParseResult<BTreeMap<IpIdentity, IpInfo<IpPairing>>> {
+    let len: u32 = source.get()?;
+    let mut identity_providers = BTreeMap::new();
+    for _ in 0..len {
+        let k = source.get()?;
+        let v = deserialize_with_length_header(source)?;
+        identity_providers.insert(k, v);
+    }
+    Ok(identity_providers)
+}
+
+/// Deserialize the anonymity revokers in the format written by the
+/// [`Serial`] instances of the genesis states.
+fn deserialize_anonymity_revokers(
+    source: &mut impl ReadBytesExt,
+) -> ParseResult<BTreeMap<ArIdentity, ArInfo<ArCurve>>> {
+    let len: u32 = source.get()?;
+    let mut anonymity_revokers = BTreeMap::new();
+    for _ in 0..len {
+        let k = source.get()?;
+        let v = deserialize_with_length_header(source)?;
+        anonymity_revokers.insert(k, v);
+    }
+    Ok(anonymity_revokers)
+}
+
 impl Serial for GenesisStateCPV0 {
     fn serial<B: Buffer>(&self, out: &mut B) {
         let mut tmp = Vec::new();
@@ -567,6 +970,36 @@ impl Serial for GenesisStateCPV2 {
     }
 }
 
+/// Implement [`Deserial`] for the genesis states as the inverse of their
+/// [`Serial`] instances. The states only differ in the versions of the update
+/// keys and the chain parameters.
+macro_rules! deserial_genesis_state {
+    ($($state:ty),*) => {$(
+        impl Deserial for $state {
+            fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+                let cryptographic_parameters = deserialize_with_length_header(source)?;
+                let identity_providers = deserialize_identity_providers(source)?;
+                let anonymity_revokers = deserialize_anonymity_revokers(source)?;
+                let update_keys = source.get()?;
+                let chain_parameters = source.get()?;
+                let leadership_election_nonce = source.get()?;
+                let accounts = source.get()?;
+                Ok(Self {
+                    cryptographic_parameters,
+                    identity_providers,
+                    anonymity_revokers,
+                    update_keys,
+                    chain_parameters,
+                    leadership_election_nonce,
+                    accounts,
+                })
+            }
+        }
+    )*};
+}
+
+deserial_genesis_state!(GenesisStateCPV0, GenesisStateCPV1, GenesisStateCPV2);
+
 /// The genesis data containing the core genesis parameters and the initial
 /// genesis state.
 pub enum GenesisData {
@@ -601,6 +1034,19 @@ pub enum GenesisData {
 }
 
 impl GenesisData {
+    /// The protocol version the chain starts in.
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        match self {
+            GenesisData::P1 { .. } => ProtocolVersion::P1,
+            GenesisData::P2 { .. } => ProtocolVersion::P2,
+            GenesisData::P3 { .. } => ProtocolVersion::P3,
+            GenesisData::P4 { .. } => ProtocolVersion::P4,
+            GenesisData::P5 { .. } => ProtocolVersion::P5,
+            GenesisData::P6 { .. } => ProtocolVersion::P6,
+            GenesisData::P7 { .. } => ProtocolVersion::P7,
+        }
+    }
+
     pub fn hash(&self) -> BlockHash {
         let mut hasher = sha2::Sha256::new();
         Slot::from(0u64).serial(&mut hasher);
@@ -783,3 +1229,46 @@ impl Serial for GenesisData {
         }
     }
 }
+
+impl Deserial for GenesisData {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let version: u8 = source.get()?;
+        let tag: u8 = source.get()?;
+        ensure!(
+            tag == 0,
+            "Only initial genesis data is supported, but the genesis has tag {tag}."
+        );
+        let genesis = match version {
+            3 => GenesisData::P1 {
+                core:          source.get()?,
+                initial_state: source.get()?,
+            },
+            4 => GenesisData::P2 {
+                core:          source.get()?,
+                initial_state: source.get()?,
+            },
+            5 => GenesisData::P3 {
+                core:          source.get()?,
+                initial_state: source.get()?,
+            },
+            6 => GenesisData::P4 {
+                core:          source.get()?,
+                initial_state: source.get()?,
+            },
+            7 => GenesisData::P5 {
+                core:          source.get()?,
+                initial_state: source.get()?,
+            },
+            8 => GenesisData::P6 {
+                core:          source.get()?,
+                initial_state: source.get()?,
+            },
+            9 => GenesisData::P7 {
+                core:          source.get()?,
+                initial_state: source.get()?,
+            },
+            _ => anyhow::bail!("Unsupported genesis data version {version}."),
+        };
+        Ok(genesis)
+    }
+}
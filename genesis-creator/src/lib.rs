@@ -1,8 +1,14 @@
 pub mod assemble;
 pub mod config;
 pub mod genesis;
+pub mod validate;
 
-use crate::{assemble::AssembleGenesisConfig, config::*, genesis::*};
+use crate::{
+    assemble::AssembleGenesisConfig,
+    config::*,
+    genesis::*,
+    validate::{validate_genesis, AccountOrigin, Severity},
+};
 /// A command line tool for generating genesis files.
 ///
 /// The tool has two modes: `generate` that can generate a new genesis,
@@ -15,8 +21,8 @@ use crate::{assemble::AssembleGenesisConfig, config::*, genesis::*};
 use anyhow::{anyhow, bail, ensure, Context};
 use concordium_rust_sdk::{
     common::{
-        types::{CredentialIndex, KeyIndex, KeyPair},
-        Serial, Versioned, VERSION_0,
+        types::{Amount, CredentialIndex, KeyIndex, KeyPair, Timestamp},
+        Deserial, Serial, Versioned, VERSION_0,
     },
     id,
     id::{
@@ -32,10 +38,11 @@ use concordium_rust_sdk::{
         },
     },
     types::{
+        hashes::{BlockHash, LeadershipElectionNonce},
         AccountIndex, AuthorizationsV0, AuthorizationsV1, BakerCredentials, BakerId, BakerKeyPairs,
-        ChainParameterVersion0, ChainParameterVersion1, HigherLevelAccessStructure,
-        ProtocolVersion, UpdateKeyPair, UpdateKeysCollection, UpdateKeysCollectionSkeleton,
-        UpdatePublicKey,
+        ChainParameterVersion0, ChainParameterVersion1, ChainParametersV0, ChainParametersV1,
+        ChainParametersV2, HigherLevelAccessStructure, ProtocolVersion, UpdateKeyPair,
+        UpdateKeysCollection, UpdateKeysCollectionSkeleton, UpdatePublicKey,
     },
 };
 use rayon::prelude::*;
@@ -845,6 +852,27 @@ fn accounts(
     }
 }
 
+/// Validate the genesis data and print all problems found, with the accounts
+/// referred to as configured in `origin`. Fails if any of the problems is an
+/// error.
+fn check_genesis(genesis: &GenesisData, origin: AccountOrigin) -> anyhow::Result<()> {
+    let problems = validate_genesis(genesis, Timestamp::now());
+    for problem in &problems {
+        println!("{}", problem.display(origin));
+    }
+    let errors = problems
+        .iter()
+        .filter(|problem| problem.severity == Severity::Error)
+        .count();
+    ensure!(
+        errors == 0,
+        "The genesis is invalid: found {} errors and {} warnings.",
+        errors,
+        problems.len() - errors
+    );
+    Ok(())
+}
+
 fn read_json<S: DeserializeOwned>(path: &Path) -> anyhow::Result<S> {
     let data_value: serde_json::Value = serde_json::from_slice(
         &std::fs::read(path).context("Could not read existing account file.")?,
@@ -885,7 +913,36 @@ pub fn handle_assemble(config_path: &Path, verbose: bool) -> anyhow::Result<()>
     let config_source =
         std::fs::read(config_path).context("Unable to read the configuration file.")?;
     let config: AssembleGenesisConfig =
-        toml::from_slice(&config_source).context("Unable to parse the configuration file.")?;
+        parse_config(&config_source).context("Unable to parse the configuration file.")?;
+
+    println!(
+        "The genesis data will be stored in {}",
+        config.genesis_out.display()
+    );
+    println!(
+        "The genesis hash will be written to {}",
+        config.genesis_hash_out.display()
+    );
+    let genesis_out = make_relative(config_path, &config.genesis_out)?;
+    let genesis_hash_out = make_relative(config_path, &config.genesis_hash_out)?;
+    let accounts = config.accounts.clone();
+
+    let genesis = assemble_genesis(config_path, config, verbose)?;
+    check_genesis(&genesis, AccountOrigin::AccountsFile(&accounts))?;
+    write_genesis(&genesis_out, &genesis_hash_out, &genesis)?;
+
+    println!("DONE");
+    Ok(())
+}
+
+/// Assemble the genesis data described by an `AssembleGenesisConfig` read from
+/// the file at `config_path`. Paths in the configuration are relative to the
+/// configuration file.
+fn assemble_genesis(
+    config_path: &Path,
+    config: AssembleGenesisConfig,
+    verbose: bool,
+) -> anyhow::Result<GenesisData> {
     let accounts: Vec<GenesisAccountPublic> =
         read_json(&make_relative(config_path, &config.accounts)?)?;
     let global = read_json::<Versioned<_>>(&make_relative(config_path, &config.global)?)?;
@@ -909,15 +966,6 @@ pub fn handle_assemble(config_path: &Path, verbose: bool) -> anyhow::Result<()>
         })
         .context("Cannot find foundation account.")?;
 
-    println!(
-        "The genesis data will be stored in {}",
-        config.genesis_out.display()
-    );
-    println!(
-        "The genesis hash will be written to {}",
-        config.genesis_hash_out.display()
-    );
-
     let protocol_version = config.protocol.protocol_version();
 
     let genesis = match config.protocol {
@@ -972,6 +1020,7 @@ pub fn handle_assemble(config_path: &Path, verbose: bool) -> anyhow::Result<()>
             }
         }
         ProtocolConfig::P6 { parameters } | ProtocolConfig::P7 { parameters } => {
+            let core = parameters.to_core()?;
             let update_keys = read_json(&make_relative(config_path, &config.governance_keys)?)?;
 
             let initial_state = GenesisStateCPV2 {
@@ -983,7 +1032,6 @@ pub fn handle_assemble(config_path: &Path, verbose: bool) -> anyhow::Result<()>
                 leadership_election_nonce: parameters.leadership_election_nonce,
                 accounts,
             };
-            let core = parameters.core.try_into()?;
             match protocol_version {
                 ProtocolVersion::P6 => GenesisData::P6 {
                     core,
@@ -997,15 +1045,7 @@ pub fn handle_assemble(config_path: &Path, verbose: bool) -> anyhow::Result<()>
             }
         }
     };
-
-    write_genesis(
-        &make_relative(config_path, &config.genesis_out)?,
-        &make_relative(config_path, &config.genesis_hash_out)?,
-        &genesis,
-    )?;
-
-    println!("DONE");
-    Ok(())
+    Ok(genesis)
 }
 
 /// Function for generating a new genesis data file given a path to TOML file
@@ -1016,7 +1056,7 @@ pub fn handle_generate(config_path: &Path, verbose: bool) -> anyhow::Result<()>
     let config_source =
         std::fs::read(config_path).context("Unable to read the configuration file.")?;
     let config: Config =
-        toml::from_slice(&config_source).context("Unable to parse the configuration file.")?;
+        parse_config(&config_source).context("Unable to parse the configuration file.")?;
     if verbose {
         println!("Using the following configuration structure for generating genesis.");
         println!("{:#?}", config);
@@ -1096,6 +1136,11 @@ pub fn handle_generate(config_path: &Path, verbose: bool) -> anyhow::Result<()>
         config.anonymity_revokers,
     )?;
 
+    let entries: Vec<u32> = config
+        .accounts
+        .iter()
+        .map(AccountConfig::num_accounts)
+        .collect();
     let (foundation_idx, num_bakers, accounts) = accounts(
         config.out.baker_keys,
         config.out.account_keys,
@@ -1198,6 +1243,7 @@ pub fn handle_generate(config_path: &Path, verbose: bool) -> anyhow::Result<()>
             }
         }
         ProtocolConfig::P6 { parameters } | ProtocolConfig::P7 { parameters } => {
+            let core = parameters.to_core()?;
             let update_keys = updates_v1(config.out.update_keys, config.updates)?;
 
             let initial_state = GenesisStateCPV2 {
@@ -1209,7 +1255,6 @@ pub fn handle_generate(config_path: &Path, verbose: bool) -> anyhow::Result<()>
                 leadership_election_nonce: parameters.leadership_election_nonce,
                 accounts,
             };
-            let core = parameters.core.try_into()?;
             match protocol_version {
                 ProtocolVersion::P6 => GenesisData::P6 {
                     core,
@@ -1223,6 +1268,7 @@ pub fn handle_generate(config_path: &Path, verbose: bool) -> anyhow::Result<()>
             }
         }
     };
+    check_genesis(&genesis, AccountOrigin::Entries(&entries))?;
     write_genesis(
         config.out.genesis.as_path(),
         config.out.genesis_hash.as_path(),
@@ -1246,3 +1292,687 @@ fn write_genesis(data_path: &Path, hash_path: &Path, genesis: &GenesisData) -> a
     .context("Unable to write the genesis hash.")?;
     Ok(())
 }
+
+/// Read a genesis data file.
+pub fn read_genesis(path: &Path) -> anyhow::Result<GenesisData> {
+    let bytes = std::fs::read(path).context("Unable to read the genesis data file.")?;
+    let mut source = std::io::Cursor::new(&bytes);
+    let genesis =
+        GenesisData::deserial(&mut source).context("Unable to parse the genesis data file.")?;
+    ensure!(
+        source.position() == bytes.len() as u64,
+        "The genesis data file has {} bytes left after the genesis data.",
+        bytes.len() as u64 - source.position()
+    );
+    Ok(genesis)
+}
+
+/// JSON representation of chain parameters version 2, which have no
+/// `serde::Serialize` implementation.
+fn chain_parameters_v2_json(params: &ChainParametersV2) -> serde_json::Value {
+    serde_json::json!({
+        "timeoutParameters": params.timeout_parameters,
+        "minBlockTime": params.min_block_time,
+        "blockEnergyLimit": params.block_energy_limit,
+        "euroPerEnergy": params.euro_per_energy,
+        "microCCDPerEuro": params.micro_ccd_per_euro,
+        "cooldownParameters": params.cooldown_parameters,
+        "timeParameters": params.time_parameters,
+        "accountCreationLimit": params.account_creation_limit,
+        "rewardParameters": params.reward_parameters,
+        "foundationAccountIndex": params.foundation_account_index,
+        "poolParameters": params.pool_parameters,
+        "finalizationCommitteeParameters": params.finalization_committee_parameters,
+    })
+}
+
+/// Convert the contents of genesis data to JSON, in the structure printed by
+/// `inspect`.
+fn genesis_json(genesis: &GenesisData) -> anyhow::Result<serde_json::Value> {
+    /// The parts of the genesis state that are the same in all chain
+    /// parameter versions.
+    macro_rules! state_json {
+        ($core:expr, $state:expr, $chain_parameters:expr) => {
+            serde_json::json!({
+                "coreParameters": $core,
+                "chainParameters": $chain_parameters,
+                "updateKeys": $state.update_keys,
+                "leadershipElectionNonce": $state.leadership_election_nonce,
+                "cryptographicParameters": $state.cryptographic_parameters,
+                "identityProviders": $state.identity_providers.values().collect::<Vec<_>>(),
+                "anonymityRevokers": $state.anonymity_revokers.values().collect::<Vec<_>>(),
+                "accounts": $state.accounts.iter().map(|account| serde_json::json!({
+                    "address": account.address,
+                    "accountThreshold": account.account_threshold,
+                    "credentials": account.credentials.len(),
+                    "balance": account.balance,
+                    "baker": account.baker,
+                })).collect::<Vec<_>>(),
+            })
+        };
+    }
+    let mut json = match genesis {
+        GenesisData::P1 {
+            core,
+            initial_state,
+        }
+        | GenesisData::P2 {
+            core,
+            initial_state,
+        }
+        | GenesisData::P3 {
+            core,
+            initial_state,
+        } => state_json!(core, initial_state, initial_state.chain_parameters),
+        GenesisData::P4 {
+            core,
+            initial_state,
+        }
+        | GenesisData::P5 {
+            core,
+            initial_state,
+        } => state_json!(core, initial_state, initial_state.chain_parameters),
+        GenesisData::P6 {
+            core,
+            initial_state,
+        }
+        | GenesisData::P7 {
+            core,
+            initial_state,
+        } => state_json!(
+            core,
+            initial_state,
+            chain_parameters_v2_json(&initial_state.chain_parameters)
+        ),
+    };
+    let fields = json
+        .as_object_mut()
+        .context("The genesis data is a JSON object.")?;
+    fields.insert(
+        "protocolVersion".into(),
+        serde_json::to_value(genesis.protocol_version())?,
+    );
+    fields.insert("genesisHash".into(), serde_json::to_value(genesis.hash())?);
+    Ok(json)
+}
+
+/// Print a human readable summary of genesis data given as JSON by
+/// [`genesis_json`].
+fn print_genesis_summary(json: &serde_json::Value) -> anyhow::Result<()> {
+    let pretty = |value: &serde_json::Value| {
+        serde_json::to_string_pretty(value).expect("JSON serialization of values does not fail.")
+    };
+    // Strings are shown without the quotes of their JSON representation.
+    let text = |value: &serde_json::Value| match value.as_str() {
+        Some(s) => s.to_string(),
+        None => value.to_string(),
+    };
+    println!("Protocol version: {}", json["protocolVersion"]);
+    println!("Genesis hash: {}", text(&json["genesisHash"]));
+    println!("Core parameters: {}", pretty(&json["coreParameters"]));
+    println!("Chain parameters: {}", pretty(&json["chainParameters"]));
+
+    println!("Update keys:");
+    let update_keys = &json["updateKeys"];
+    for (name, keys) in [
+        ("root", &update_keys["rootKeys"]),
+        ("level 1", &update_keys["level1Keys"]),
+    ] {
+        println!(
+            "  {name}: {} keys, threshold {}",
+            keys["keys"].as_array().map_or(0, Vec::len),
+            keys["threshold"]
+        );
+    }
+    let level2 = &update_keys["level2Keys"];
+    println!(
+        "  level 2: {} keys",
+        level2["keys"].as_array().map_or(0, Vec::len)
+    );
+    for (name, access) in level2.as_object().into_iter().flatten() {
+        if name != "keys" {
+            println!(
+                "    {name}: keys {}, threshold {}",
+                access["authorizedKeys"], access["threshold"]
+            );
+        }
+    }
+
+    println!("Identity providers:");
+    for ip in json["identityProviders"].as_array().into_iter().flatten() {
+        println!(
+            "  {}: {}",
+            ip["ipIdentity"],
+            text(&ip["ipDescription"]["name"])
+        );
+    }
+    println!("Anonymity revokers:");
+    for ar in json["anonymityRevokers"].as_array().into_iter().flatten() {
+        println!(
+            "  {}: {}",
+            ar["arIdentity"],
+            text(&ar["arDescription"]["name"])
+        );
+    }
+
+    let accounts = json["accounts"]
+        .as_array()
+        .context("Accounts are a JSON array.")?;
+    let mut total_balance = Amount::zero();
+    let mut total_stake = Amount::zero();
+    println!("Accounts:");
+    for account in accounts {
+        let balance: Amount = serde_json::from_value(account["balance"].clone())?;
+        total_balance += balance;
+        print!("  {}: {balance} CCD", text(&account["address"]));
+        let baker = &account["baker"];
+        if !baker.is_null() {
+            let stake: Amount = serde_json::from_value(baker["stake"].clone())?;
+            total_stake += stake;
+            print!(
+                ", baker {} with stake {stake} CCD (restake earnings: {})",
+                baker["bakerId"], baker["restakeEarnings"]
+            );
+        }
+        println!();
+    }
+    println!(
+        "{} accounts with a total balance of {total_balance} CCD, of which {total_stake} CCD is \
+         staked.",
+        accounts.len()
+    );
+    Ok(())
+}
+
+/// Function for inspecting an existing genesis data file. It prints the
+/// contents of the file and the recomputed genesis hash, either as a summary or
+/// as JSON.
+pub fn handle_inspect(genesis_path: &Path, json: bool) -> anyhow::Result<()> {
+    let genesis = read_genesis(genesis_path)?;
+    let value = genesis_json(&genesis)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        print_genesis_summary(&value)?;
+    }
+    Ok(())
+}
+
+/// Function for validating a genesis without writing it. The genesis is either
+/// read from an existing genesis data file, or assembled as described by an
+/// `AssembleGenesisConfig`. All problems found are printed, and the function
+/// fails if any of them is an error.
+pub fn handle_validate(
+    genesis_path: Option<&Path>,
+    config_path: Option<&Path>,
+) -> anyhow::Result<()> {
+    let genesis = match (genesis_path, config_path) {
+        (Some(genesis_path), None) => {
+            let genesis = read_genesis(genesis_path)?;
+            check_genesis(&genesis, AccountOrigin::Genesis)?;
+            genesis
+        }
+        (None, Some(config_path)) => {
+            let config_source =
+                std::fs::read(config_path).context("Unable to read the configuration file.")?;
+            let config: AssembleGenesisConfig =
+                parse_config(&config_source).context("Unable to parse the configuration file.")?;
+            let accounts = config.accounts.clone();
+            let genesis = assemble_genesis(config_path, config, false)?;
+            check_genesis(&genesis, AccountOrigin::AccountsFile(&accounts))?;
+            genesis
+        }
+        _ => bail!("Exactly one of a genesis data file and a configuration file must be given."),
+    };
+    println!("The genesis with hash {} is valid.", genesis.hash());
+    Ok(())
+}
+
+/// Write a value as pretty-printed JSON to a file.
+fn write_json(path: &Path, value: &impl serde::Serialize) -> anyhow::Result<()> {
+    std::fs::write(path, serde_json::to_vec_pretty(value)?)
+        .with_context(|| format!("Unable to write {}.", path.display()))
+}
+
+/// Convert a JSON value to TOML. Numbers are converted explicitly, since with
+/// arbitrary precision enabled they do not serialize as numbers to other
+/// formats than JSON.
+fn json_to_toml(value: serde_json::Value) -> anyhow::Result<toml::Value> {
+    use serde_json::Value;
+    let value = match value {
+        Value::Null => bail!("TOML does not support null values."),
+        Value::Bool(b) => toml::Value::Boolean(b),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => toml::Value::Integer(i),
+            // TOML integers are signed, so larger integers are written as strings,
+            // which the configuration accepts where such integers occur.
+            (None, Some(u)) => toml::Value::String(u.to_string()),
+            (None, None) => toml::Value::Float(
+                n.to_string()
+                    .parse()
+                    .with_context(|| format!("Unable to represent {n} in TOML."))?,
+            ),
+        },
+        Value::String(s) => toml::Value::String(s),
+        Value::Array(values) => toml::Value::Array(
+            values
+                .into_iter()
+                .map(json_to_toml)
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        Value::Object(fields) => toml::Value::Table(
+            fields
+                .into_iter()
+                .map(|(k, v)| Ok((k, json_to_toml(v)?)))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+    };
+    Ok(value)
+}
+
+/// The `parameters` section of an `AssembleGenesisConfig` for protocol
+/// versions 1 to 5, given the JSON of the `chain` subsection.
+fn parameters_config_v0(
+    core: &CoreGenesisParametersV0,
+    leadership_election_nonce: &LeadershipElectionNonce,
+    chain: serde_json::Value,
+) -> anyhow::Result<serde_json::Value> {
+    let genesis_time =
+        chrono::DateTime::<chrono::Utc>::from_timestamp_millis(core.time.millis.try_into()?)
+            .context("Genesis time is out of range.")?;
+    Ok(serde_json::json!({
+        "genesisTime": genesis_time,
+        "slotDuration": core.slot_duration,
+        "leadershipElectionNonce": leadership_election_nonce,
+        "epochLength": core.epoch_length,
+        "maxBlockEnergy": core.max_block_energy,
+        "finalization": core.finalization_parameters,
+        "chain": chain,
+    }))
+}
+
+/// The `chain` subsection of the genesis parameters for chain parameters
+/// version 0.
+fn chain_parameters_config_v0(params: &ChainParametersV0) -> serde_json::Value {
+    serde_json::json!({
+        "version": "v0",
+        "electionDifficulty": params.election_difficulty,
+        "euroPerEnergy": params.euro_per_energy,
+        "microCCDPerEuro": params.micro_gtu_per_euro,
+        "accountCreationLimit": params.account_creation_limit,
+        "bakerCooldownEpochs": params.baker_cooldown_epochs,
+        "rewardParameters": params.reward_parameters,
+        "minimumThresholdForBaking": params.minimum_threshold_for_baking,
+    })
+}
+
+/// The `chain` subsection of the genesis parameters for chain parameters
+/// version 1.
+fn chain_parameters_config_v1(params: &ChainParametersV1) -> serde_json::Value {
+    serde_json::json!({
+        "version": "v1",
+        "electionDifficulty": params.election_difficulty,
+        "euroPerEnergy": params.euro_per_energy,
+        "microCCDPerEuro": params.micro_gtu_per_euro,
+        "accountCreationLimit": params.account_creation_limit,
+        "rewardParameters": params.reward_parameters,
+        "timeParameters": params.time_parameters,
+        "poolParameters": params.pool_parameters,
+        "cooldownParameters": params.cooldown_parameters,
+    })
+}
+
+/// The `parameters` section of an `AssembleGenesisConfig` for protocol
+/// versions 6 and 7.
+fn parameters_config_v1(
+    core: &CoreGenesisParametersV1,
+    leadership_election_nonce: &LeadershipElectionNonce,
+    params: &ChainParametersV2,
+) -> anyhow::Result<serde_json::Value> {
+    let genesis_time = chrono::DateTime::<chrono::Utc>::from_timestamp_millis(
+        core.genesis_time.millis.try_into()?,
+    )
+    .context("Genesis time is out of range.")?;
+    let finalization_committee = &params.finalization_committee_parameters;
+    Ok(serde_json::json!({
+        "genesisTime": genesis_time,
+        "leadershipElectionNonce": leadership_election_nonce,
+        "epochDuration": core.epoch_duration,
+        "signatureThreshold": {
+            "numerator": core.signature_threshold.numerator(),
+            "denominator": core.signature_threshold.denominator(),
+        },
+        "chain": {
+            "timeoutParameters": params.timeout_parameters,
+            "minBlockTime": params.min_block_time,
+            "blockEnergyLimit": params.block_energy_limit,
+            "euroPerEnergy": params.euro_per_energy,
+            "microCCDPerEuro": params.micro_ccd_per_euro,
+            "accountCreationLimit": params.account_creation_limit,
+            "rewardParameters": params.reward_parameters,
+            "timeParameters": params.time_parameters,
+            "poolParameters": params.pool_parameters,
+            "cooldownParameters": params.cooldown_parameters,
+            "finalizationCommitteeParameters": {
+                "minFinalizers": finalization_committee.min_finalizers,
+                "maxFinalizers": finalization_committee.max_finalizers,
+                "finalizersRelativeStakeThreshold":
+                    u32::from(finalization_committee.finalizers_relative_stake_threshold),
+            },
+        },
+    }))
+}
+
+/// Write the files read by `assemble`, except for the configuration, and
+/// return the address of the foundation account.
+fn write_assemble_inputs(
+    out: &Path,
+    cryptographic_parameters: &GlobalContext<ArCurve>,
+    identity_providers: &BTreeMap<IpIdentity, IpInfo<IpPairing>>,
+    anonymity_revokers: &BTreeMap<ArIdentity, ArInfo<ArCurve>>,
+    update_keys: &impl serde::Serialize,
+    accounts: &[GenesisAccountPublic],
+    foundation_account_index: AccountIndex,
+) -> anyhow::Result<id::types::AccountAddress> {
+    write_json(&out.join("accounts.json"), &accounts)?;
+    write_json(
+        &out.join("identity-providers.json"),
+        &Versioned::new(VERSION_0, identity_providers),
+    )?;
+    write_json(
+        &out.join("anonymity-revokers.json"),
+        &Versioned::new(VERSION_0, anonymity_revokers),
+    )?;
+    write_json(
+        &out.join("cryptographic-parameters.json"),
+        &Versioned::new(VERSION_0, cryptographic_parameters),
+    )?;
+    write_json(&out.join("governance-keys.json"), update_keys)?;
+    let foundation_account = usize::try_from(foundation_account_index.index)
+        .ok()
+        .and_then(|index| accounts.get(index))
+        .context("The foundation account index does not refer to a genesis account.")?;
+    Ok(foundation_account.address)
+}
+
+/// Function for extracting the input files of `assemble` from an existing
+/// genesis data file. It writes the accounts, identity providers, anonymity
+/// revokers, cryptographic parameters, governance keys and an
+/// `AssembleGenesisConfig` to the directory `out`. The files are only written
+/// once assembling them reproduces the genesis data file exactly.
+pub fn handle_extract(
+    genesis_path: &Path,
+    out: &Path,
+    delete_existing: bool,
+) -> anyhow::Result<()> {
+    let bytes = std::fs::read(genesis_path).context("Unable to read the genesis data file.")?;
+    let genesis = read_genesis(genesis_path)?;
+    if out.exists() && !delete_existing {
+        bail!("Supplied output path {} already exists.", out.display());
+    }
+
+    // The files are written to a temporary directory, and only moved to `out`
+    // once they are checked to assemble to the same genesis.
+    static EXTRACTIONS: AtomicU64 = AtomicU64::new(0);
+    let staging = std::env::temp_dir().join(format!(
+        "genesis-creator-extract-{}-{}",
+        std::process::id(),
+        EXTRACTIONS.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    ));
+    check_and_create_dir(true, &staging, false)?;
+    let result = extract_assemble_inputs(&genesis, &bytes, &staging).and_then(|hash| {
+        check_and_create_dir(delete_existing, out, false)?;
+        for entry in std::fs::read_dir(&staging)? {
+            let entry = entry?;
+            std::fs::copy(entry.path(), out.join(entry.file_name()))
+                .context("Unable to write the extracted files.")?;
+        }
+        Ok(hash)
+    });
+    let cleanup = std::fs::remove_dir_all(&staging);
+    let hash = result?;
+    cleanup.context("Unable to remove the temporary directory.")?;
+    println!(
+        "The input files of `assemble` are written to {}, with the configuration in {}",
+        out.display(),
+        out.join("assemble-config.toml").display()
+    );
+    println!("Assembling the extracted files reproduces the genesis with hash {hash}.");
+    Ok(())
+}
+
+/// Write the input files of `assemble` for the genesis to `out`, and check that
+/// they assemble to the given genesis data. Returns the hash of the genesis.
+fn extract_assemble_inputs(
+    genesis: &GenesisData,
+    bytes: &[u8],
+    out: &Path,
+) -> anyhow::Result<BlockHash> {
+    /// Write the input files for a genesis state, and return the address of
+    /// the foundation account.
+    macro_rules! write_state {
+        ($state:expr) => {
+            write_assemble_inputs(
+                out,
+                &$state.cryptographic_parameters,
+                &$state.identity_providers,
+                &$state.anonymity_revokers,
+                &$state.update_keys,
+                &$state.accounts,
+                $state.chain_parameters.foundation_account_index,
+            )?
+        };
+    }
+    let (foundation_account, parameters) = match genesis {
+        GenesisData::P1 {
+            core,
+            initial_state,
+        }
+        | GenesisData::P2 {
+            core,
+            initial_state,
+        }
+        | GenesisData::P3 {
+            core,
+            initial_state,
+        } => (
+            write_state!(initial_state),
+            parameters_config_v0(
+                core,
+                &initial_state.leadership_election_nonce,
+                chain_parameters_config_v0(&initial_state.chain_parameters),
+            )?,
+        ),
+        GenesisData::P4 {
+            core,
+            initial_state,
+        }
+        | GenesisData::P5 {
+            core,
+            initial_state,
+        } => (
+            write_state!(initial_state),
+            parameters_config_v0(
+                core,
+                &initial_state.leadership_election_nonce,
+                chain_parameters_config_v1(&initial_state.chain_parameters),
+            )?,
+        ),
+        GenesisData::P6 {
+            core,
+            initial_state,
+        }
+        | GenesisData::P7 {
+            core,
+            initial_state,
+        } => (
+            write_state!(initial_state),
+            parameters_config_v1(
+                core,
+                &initial_state.leadership_election_nonce,
+                &initial_state.chain_parameters,
+            )?,
+        ),
+    };
+
+    let config = serde_json::json!({
+        "protocolVersion": u64::from(genesis.protocol_version()).to_string(),
+        "foundationAccount": foundation_account,
+        "accounts": "accounts.json",
+        "idps": "identity-providers.json",
+        "ars": "anonymity-revokers.json",
+        "governanceKeys": "governance-keys.json",
+        "global": "cryptographic-parameters.json",
+        "genesisOut": "genesis.dat",
+        "genesisHashOut": "genesis_hash",
+        "parameters": parameters,
+    });
+    let config_path = out.join("assemble-config.toml");
+    let config_toml =
+        json_to_toml(config).context("Unable to convert the configuration to TOML.")?;
+    std::fs::write(&config_path, toml::to_string(&config_toml)?)
+        .context("Unable to write the configuration file.")?;
+
+    // Check that the extracted files assemble to the same genesis.
+    let config_source = std::fs::read(&config_path)?;
+    let config: AssembleGenesisConfig = parse_config(&config_source)
+        .context("Unable to parse the extracted configuration file.")?;
+    let assembled = assemble_genesis(&config_path, config, false)
+        .context("Unable to assemble the extracted files.")?;
+    let mut assembled_bytes = Vec::new();
+    assembled.serial(&mut assembled_bytes);
+    ensure!(
+        assembled_bytes == bytes,
+        "Assembling the extracted files does not reproduce the genesis data file."
+    );
+    Ok(assembled.hash())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concordium_rust_sdk::{common::types::Ratio, types::ExchangeRate};
+
+    /// An empty directory for the outputs of a test.
+    pub(crate) fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("genesis-creator-{name}-{}", std::process::id()));
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Generate the genesis of an example configuration with all outputs in
+    /// `dir`, and return the path of the genesis data file.
+    pub(crate) fn generate_example(example: &str, dir: &Path) -> PathBuf {
+        let example = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("examples")
+            .join(example);
+        let mut config: toml::Value =
+            toml::from_str(&std::fs::read_to_string(example).unwrap()).unwrap();
+        let out = config
+            .get_mut("out")
+            .and_then(toml::Value::as_table_mut)
+            .unwrap();
+        for (_, value) in out.iter_mut() {
+            if let toml::Value::String(path) = value {
+                *value = toml::Value::String(dir.join(path.as_str()).display().to_string());
+            }
+        }
+        let genesis = PathBuf::from(out["genesis"].as_str().unwrap());
+        let config_path = dir.join("config.toml");
+        std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+        handle_generate(&config_path, false).unwrap();
+        genesis
+    }
+
+    /// Assemble the genesis from the files written by `extract` to `dir`.
+    fn assemble_extracted(dir: &Path) -> Vec<u8> {
+        let config_path = dir.join("assemble-config.toml");
+        let config = parse_config(&std::fs::read(&config_path).unwrap()).unwrap();
+        let genesis = assemble_genesis(&config_path, config, false).unwrap();
+        let mut bytes = Vec::new();
+        genesis.serial(&mut bytes);
+        bytes
+    }
+
+    #[test]
+    fn extract_reproduces_generated_examples() {
+        for example in [
+            "genesis1.toml",
+            "genesis4.toml",
+            "genesis5.toml",
+            "genesis6.toml",
+        ] {
+            let dir = test_dir(example.trim_end_matches(".toml"));
+            let genesis = generate_example(example, &dir);
+            let extracted = dir.join("extracted");
+            handle_extract(&genesis, &extracted, false).unwrap();
+            assert_eq!(
+                assemble_extracted(&extracted),
+                std::fs::read(&genesis).unwrap(),
+                "Extracting and assembling the genesis of {example} changes it."
+            );
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn extract_keeps_integers_above_i64_max() {
+        let dir = test_dir("large-integers");
+        let genesis_path = generate_example("genesis6.toml", &dir);
+        let GenesisData::P6 {
+            mut core,
+            mut initial_state,
+        } = read_genesis(&genesis_path).unwrap()
+        else {
+            panic!("The example genesis6.toml has protocol version 6.");
+        };
+        core.signature_threshold = Ratio::new(u64::MAX - 1, u64::MAX).unwrap();
+        let chain_parameters = &mut initial_state.chain_parameters;
+        chain_parameters.euro_per_energy = ExchangeRate::new(u64::MAX, 7).unwrap();
+        chain_parameters.micro_ccd_per_euro = ExchangeRate::new(7, u64::MAX).unwrap();
+        let genesis = GenesisData::P6 {
+            core,
+            initial_state,
+        };
+        write_genesis(&genesis_path, &dir.join("genesis_hash"), &genesis).unwrap();
+
+        let extracted = dir.join("extracted");
+        handle_extract(&genesis_path, &extracted, false).unwrap();
+        let config = std::fs::read_to_string(extracted.join("assemble-config.toml")).unwrap();
+        assert!(config.contains(&format!("numerator = \"{}\"", u64::MAX)));
+        assert_eq!(
+            assemble_extracted(&extracted),
+            std::fs::read(&genesis_path).unwrap()
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn failed_extract_writes_nothing() {
+        let dir = test_dir("failed-extract");
+        let genesis_path = generate_example("genesis6.toml", &dir);
+        let GenesisData::P6 {
+            core,
+            mut initial_state,
+        } = read_genesis(&genesis_path).unwrap()
+        else {
+            panic!("The example genesis6.toml has protocol version 6.");
+        };
+        // The files are written before the foundation account is looked up.
+        initial_state.chain_parameters.foundation_account_index = AccountIndex::from(1000);
+        let genesis = GenesisData::P6 {
+            core,
+            initial_state,
+        };
+        write_genesis(&genesis_path, &dir.join("genesis_hash"), &genesis).unwrap();
+
+        let extracted = dir.join("extracted");
+        assert!(handle_extract(&genesis_path, &extracted, false).is_err());
+        assert!(!extracted.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
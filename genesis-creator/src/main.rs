@@ -6,9 +6,13 @@
 /// to regenereate the Mainnet `genesis.dat`).
 ///
 /// In both modes the tool takes a TOML configuration file that specifies the
-/// genesis. For details, see the README.
+/// genesis. For details, see the README. Additionally, `inspect` decodes an
+/// existing genesis data file, `extract` writes the input files of `assemble`
+/// that reproduce it, and `validate` checks a genesis without writing it.
 use clap::Parser;
-use genesis_creator::{handle_assemble, handle_generate};
+use genesis_creator::{
+    handle_assemble, handle_extract, handle_generate, handle_inspect, handle_validate,
+};
 use std::path::PathBuf;
 
 /// Subcommands supported by the tool.
@@ -31,6 +35,45 @@ enum GenesisCreatorCommand {
         /// Whether to output additional data during genesis generation.
         verbose: bool,
     },
+    /// Decode an existing genesis data file and print its contents together
+    /// with the recomputed genesis hash.
+    Inspect {
+        #[clap(long, short)]
+        /// The genesis data file to inspect, e.g. `genesis.dat`.
+        genesis: PathBuf,
+        #[clap(long)]
+        /// Whether to print the contents as JSON instead of a summary.
+        json:    bool,
+    },
+    /// Decode an existing genesis data file and write the files that
+    /// `assemble` needs to reproduce it.
+    Extract {
+        #[clap(long, short)]
+        /// The genesis data file to extract, e.g. `genesis.dat`.
+        genesis:         PathBuf,
+        #[clap(long, short)]
+        /// The directory to write the extracted files to.
+        out:             PathBuf,
+        #[clap(long)]
+        /// Whether to delete the output directory if it already exists.
+        delete_existing: bool,
+    },
+    /// Check a genesis for problems without writing it. The genesis is either
+    /// an existing genesis data file or described by an `assemble`
+    /// configuration.
+    Validate {
+        #[clap(
+            long,
+            short,
+            conflicts_with = "config",
+            required_unless_present = "config"
+        )]
+        /// The genesis data file to validate, e.g. `genesis.dat`.
+        genesis: Option<PathBuf>,
+        #[clap(long, short)]
+        /// The TOML configuration file of `assemble` describing the genesis.
+        config:  Option<PathBuf>,
+    },
 }
 
 #[derive(Parser, Debug)]
@@ -46,8 +89,14 @@ fn main() -> anyhow::Result<()> {
     match &args.action {
         GenesisCreatorCommand::Assemble { config, verbose } => handle_assemble(config, *verbose),
         GenesisCreatorCommand::Generate { config, verbose } => handle_generate(config, *verbose),
+        GenesisCreatorCommand::Inspect { genesis, json } => handle_inspect(genesis, *json),
+        GenesisCreatorCommand::Extract {
+            genesis,
+            out,
+            delete_existing,
+        } => handle_extract(genesis, out, *delete_existing),
+        GenesisCreatorCommand::Validate { genesis, config } => {
+            handle_validate(genesis.as_deref(), config.as_deref())
+        }
     }
 }
-
-// TODO: Deny unused fields.
-// TODO: Output genesis_hash
@@ -0,0 +1,973 @@
+//! Semantic validation of genesis data, run before the genesis data is
+//! written and by the `validate` mode.
+use crate::genesis::*;
+use concordium_rust_sdk::{
+    common::types::{Amount, Ratio, Timestamp},
+    id::{
+        constants::{ArCurve, IpPairing},
+        types::{AccountCredentialWithoutProofs, ArIdentity, ArInfo, IpIdentity, IpInfo},
+    },
+    types::{
+        AccountIndex, AmountFraction, FinalizationCommitteeParameters, MintDistributionV0,
+        MintDistributionV1, TimeoutParameters, TransactionFeeDistribution,
+    },
+};
+use std::collections::BTreeMap;
+
+/// Genesis times older than this, in milliseconds, are reported as a warning,
+/// since the chain has to catch up from the genesis time when it starts.
+const MAX_GENESIS_AGE: u64 = 24 * 60 * 60 * 1000;
+
+/// How serious a [`Problem`] is. Errors make the genesis unusable, while
+/// warnings point out likely mistakes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A field of a genesis account that a [`Problem`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountField {
+    /// The stake of the baker of the account.
+    Stake,
+    /// The baker ID of the baker of the account.
+    BakerId,
+    /// The credential with the given index.
+    Credential(u8),
+}
+
+/// The location of a [`Problem`] in the genesis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    /// A genesis parameter, given by its path in the configuration, e.g.,
+    /// `parameters.chain.poolParameters.minimumEquityCapital`.
+    Parameter(String),
+    /// The genesis accounts as a whole.
+    Accounts,
+    /// The foundation account, given by its index in genesis.
+    FoundationAccount(u64),
+    /// A field of the genesis account with the given index in genesis.
+    Account { index: u64, field: AccountField },
+}
+
+impl From<&str> for Location {
+    fn from(path: &str) -> Self { Location::Parameter(path.into()) }
+}
+
+impl From<String> for Location {
+    fn from(path: String) -> Self { Location::Parameter(path) }
+}
+
+/// Where the genesis accounts are configured. Problems with accounts are
+/// reported as a path to that configuration.
+#[derive(Debug, Clone, Copy)]
+pub enum AccountOrigin<'a> {
+    /// The accounts of a genesis data file. They are referred to by their
+    /// index in genesis, as in the output of `inspect --json`, e.g.,
+    /// `accounts[3].baker.stake`.
+    Genesis,
+    /// The accounts file of an `assemble` configuration, which lists the
+    /// accounts in genesis order, e.g., `accounts.json[3].baker.stake`. The
+    /// foundation account is given by the `foundationAccount` key.
+    AccountsFile(&'a std::path::Path),
+    /// The `[[accounts]]` entries of a `generate` configuration, given by the
+    /// number of genesis accounts each entry specifies. Accounts are referred
+    /// to by their entry, e.g., `accounts[1].stake`, together with their index
+    /// in genesis if the entry specifies more than one account. The foundation
+    /// account is given by the entry with `foundation = true`.
+    Entries(&'a [u32]),
+}
+
+impl AccountOrigin<'_> {
+    /// The entry of the configuration that specifies the genesis account with
+    /// the given index, together with the number of accounts the entry
+    /// specifies.
+    fn entry(entries: &[u32], index: u64) -> Option<(usize, u32)> {
+        let mut first = 0u64;
+        for (entry, &count) in entries.iter().enumerate() {
+            first += u64::from(count);
+            if index < first {
+                return Some((entry, count));
+            }
+        }
+        None
+    }
+
+    /// The path in the configuration of a location, see [`AccountOrigin`].
+    pub fn path(&self, location: &Location) -> String {
+        let (index, field) = match location {
+            Location::Parameter(path) => return path.clone(),
+            Location::Accounts => return "accounts".into(),
+            Location::FoundationAccount(index) => {
+                return match self {
+                    AccountOrigin::Genesis => "chainParameters.foundationAccountIndex".into(),
+                    AccountOrigin::AccountsFile(_) => "foundationAccount".into(),
+                    AccountOrigin::Entries(entries) => match Self::entry(entries, *index) {
+                        Some((entry, _)) => format!("accounts[{entry}].foundation"),
+                        None => "accounts".into(),
+                    },
+                }
+            }
+            Location::Account { index, field } => (*index, *field),
+        };
+        let (account, genesis_index) = match self {
+            AccountOrigin::Genesis => (format!("accounts[{index}]"), None),
+            AccountOrigin::AccountsFile(path) => (format!("{}[{index}]", path.display()), None),
+            AccountOrigin::Entries(entries) => match Self::entry(entries, index) {
+                Some((entry, 1)) => (format!("accounts[{entry}]"), None),
+                Some((entry, _)) => (format!("accounts[{entry}]"), Some(index)),
+                None => (format!("accounts (genesis account {index})"), None),
+            },
+        };
+        let field = match (self, field) {
+            (AccountOrigin::Entries(_), AccountField::Stake) => ".stake".into(),
+            (_, AccountField::Stake) => ".baker.stake".into(),
+            // Baker IDs are assigned from the place of the entry.
+            (AccountOrigin::Entries(_), AccountField::BakerId) => String::new(),
+            (_, AccountField::BakerId) => ".baker.bakerId".into(),
+            // Credentials come from the account file or the template of the entry.
+            (AccountOrigin::Entries(_), AccountField::Credential(_)) => String::new(),
+            (_, AccountField::Credential(index)) => format!(".credentials[{index}]"),
+        };
+        match genesis_index {
+            Some(index) => format!("{account}{field} (genesis account {index})"),
+            None => format!("{account}{field}"),
+        }
+    }
+}
+
+/// A problem found when validating genesis data.
+#[derive(Debug)]
+pub struct Problem {
+    pub severity: Severity,
+    pub location: Location,
+    pub message:  String,
+}
+
+impl Problem {
+    /// Format the problem, with its location as a path in the configuration
+    /// the accounts come from.
+    pub fn display(&self, origin: AccountOrigin) -> String {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        format!(
+            "{severity}: {}: {}",
+            origin.path(&self.location),
+            self.message
+        )
+    }
+}
+
+/// The parts of the genesis state that do not depend on the chain parameters
+/// version.
+struct State<'a> {
+    accounts:                 &'a [GenesisAccountPublic],
+    identity_providers:       &'a BTreeMap<IpIdentity, IpInfo<IpPairing>>,
+    anonymity_revokers:       &'a BTreeMap<ArIdentity, ArInfo<ArCurve>>,
+    foundation_account_index: AccountIndex,
+}
+
+impl<'a> State<'a> {
+    /// The bakers with their index in genesis and their stake.
+    fn pools(&self) -> BTreeMap<u64, u128> {
+        self.accounts
+            .iter()
+            .zip(0u64..)
+            .filter_map(|(account, idx)| Some((idx, micro_ccd(account.baker.as_ref()?.stake))))
+            .collect()
+    }
+}
+
+fn micro_ccd(amount: Amount) -> u128 { amount.micro_ccd.into() }
+
+/// Collects the problems found by the individual checks.
+#[derive(Default)]
+struct Validator {
+    problems: Vec<Problem>,
+}
+
+impl Validator {
+    fn error(&mut self, location: impl Into<Location>, message: impl Into<String>) {
+        self.problems.push(Problem {
+            severity: Severity::Error,
+            location: location.into(),
+            message:  message.into(),
+        })
+    }
+
+    fn warning(&mut self, location: impl Into<Location>, message: impl Into<String>) {
+        self.problems.push(Problem {
+            severity: Severity::Warning,
+            location: location.into(),
+            message:  message.into(),
+        })
+    }
+
+    fn genesis_time(&mut self, genesis_time: Timestamp, now: Timestamp) {
+        if genesis_time.millis.saturating_add(MAX_GENESIS_AGE) < now.millis {
+            let time = chrono::DateTime::from_timestamp_millis(genesis_time.millis as i64)
+                .map_or_else(|| genesis_time.to_string(), |time| time.to_rfc3339());
+            self.warning(
+                "parameters.genesisTime",
+                format!(
+                    "The genesis time {time} is more than a day in the past, so the chain has to \
+                     catch up from it when it starts."
+                ),
+            );
+        }
+    }
+
+    /// Check the accounts: the foundation account, the identity providers and
+    /// anonymity revokers of credentials, and the bakers.
+    fn accounts(&mut self, state: &State) {
+        let foundation_account = usize::try_from(state.foundation_account_index.index)
+            .ok()
+            .and_then(|idx| state.accounts.get(idx));
+        if foundation_account.is_none() {
+            self.error(
+                Location::FoundationAccount(state.foundation_account_index.index),
+                format!(
+                    "The foundation account index {} does not refer to a genesis account.",
+                    state.foundation_account_index
+                ),
+            );
+        }
+        if state.accounts.iter().all(|account| account.baker.is_none()) {
+            self.error(Location::Accounts, "There are no genesis bakers.");
+        } else if state
+            .accounts
+            .iter()
+            .filter_map(|account| account.baker.as_ref())
+            .all(|baker| baker.stake.micro_ccd == 0)
+        {
+            self.error(
+                Location::Accounts,
+                "The total stake of the genesis bakers is zero, so no blocks can be baked.",
+            );
+        }
+        for (account, idx) in state.accounts.iter().zip(0u64..) {
+            self.credentials(state, account, idx);
+            if let Some(baker) = &account.baker {
+                self.baker(account, baker, idx);
+            }
+        }
+    }
+
+    fn credentials(&mut self, state: &State, account: &GenesisAccountPublic, idx: u64) {
+        for (cred_idx, credential) in account.credentials.iter() {
+            let location = Location::Account {
+                index: idx,
+                field: AccountField::Credential(cred_idx.index),
+            };
+            let ip_identity = match credential {
+                AccountCredentialWithoutProofs::Initial { icdv } => icdv.ip_identity,
+                AccountCredentialWithoutProofs::Normal { cdv, .. } => {
+                    for ar in cdv.ar_data.keys() {
+                        if !state.anonymity_revokers.contains_key(ar) {
+                            self.error(
+                                location.clone(),
+                                format!(
+                                    "The credential of account {} uses anonymity revoker {ar}, \
+                                     which is not a genesis anonymity revoker.",
+                                    account.address
+                                ),
+                            );
+                        }
+                    }
+                    if usize::from(cdv.threshold.0) > cdv.ar_data.len() {
+                        self.error(
+                            location.clone(),
+                            format!(
+                                "The anonymity revocation threshold {} of the credential of \
+                                 account {} exceeds its number of anonymity revokers {}.",
+                                cdv.threshold.0,
+                                account.address,
+                                cdv.ar_data.len()
+                            ),
+                        );
+                    }
+                    cdv.ip_identity
+                }
+            };
+            if !state.identity_providers.contains_key(&ip_identity) {
+                self.error(
+                    location,
+                    format!(
+                        "The credential of account {} is issued by identity provider \
+                         {ip_identity}, which is not a genesis identity provider.",
+                        account.address
+                    ),
+                );
+            }
+        }
+    }
+
+    fn baker(&mut self, account: &GenesisAccountPublic, baker: &GenesisBakerPublic, idx: u64) {
+        if baker.stake > account.balance {
+            self.error(
+                Location::Account {
+                    index: idx,
+                    field: AccountField::Stake,
+                },
+                format!(
+                    "The stake {} CCD of baker {} exceeds its balance {} CCD.",
+                    baker.stake, account.address, account.balance
+                ),
+            );
+        }
+        if baker.baker_id.id.index != idx {
+            self.error(
+                Location::Account {
+                    index: idx,
+                    field: AccountField::BakerId,
+                },
+                format!(
+                    "The baker ID {} of account {} does not match its account index {idx}.",
+                    baker.baker_id, account.address
+                ),
+            );
+        }
+    }
+
+    /// Check the stake of each baker against the minimum equity capital of the
+    /// pool parameters.
+    fn minimum_equity_capital(&mut self, state: &State, minimum: Amount) {
+        for (account, idx) in state.accounts.iter().zip(0u64..) {
+            if let Some(baker) = &account.baker {
+                if baker.stake < minimum {
+                    self.error(
+                        Location::Account {
+                            index: idx,
+                            field: AccountField::Stake,
+                        },
+                        format!(
+                            "The stake {} CCD of baker {} is below the minimum equity capital \
+                             {minimum} CCD given by \
+                             parameters.chain.poolParameters.minimumEquityCapital.",
+                            baker.stake, account.address
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Check the finalization parameters of protocol versions 1 to 5, where a
+    /// baker needs a stake of at least the total amount of CCD divided by the
+    /// maximal committee size to be a finalizer.
+    fn finalization_parameters(&mut self, state: &State, parameters: &FinalizationParameters) {
+        let path = "parameters.finalization.committeeMaxSize";
+        let max_size = parameters.committee_max_size();
+        if max_size == 0 {
+            self.error(path, "The finalization committee cannot be empty.");
+            return;
+        }
+        let total: u128 = state.accounts.iter().map(|a| micro_ccd(a.balance)).sum();
+        let pools = state.pools();
+        if !pools.is_empty()
+            && pools
+                .values()
+                .all(|&stake| stake * u128::from(max_size) < total)
+        {
+            self.error(
+                path,
+                format!(
+                    "No genesis baker has the stake of at least 1/{max_size} of the total amount \
+                     of CCD that is required to be a finalizer, so no blocks can be finalized."
+                ),
+            );
+        }
+    }
+
+    /// Check the finalization committee parameters of protocol version 6 and
+    /// later.
+    fn finalization_committee_parameters(
+        &mut self,
+        state: &State,
+        parameters: &FinalizationCommitteeParameters,
+    ) {
+        let path = "parameters.chain.finalizationCommitteeParameters";
+        if parameters.min_finalizers > parameters.max_finalizers {
+            self.error(
+                format!("{path}.minFinalizers"),
+                format!(
+                    "The minimum number of finalizers {} is greater than the maximum number {}.",
+                    parameters.min_finalizers, parameters.max_finalizers
+                ),
+            );
+        }
+        let pools = state.pools();
+        if pools.is_empty() {
+            return;
+        }
+        let total: u128 = pools.values().sum();
+        let threshold: u32 = parameters.finalizers_relative_stake_threshold.into();
+        let eligible = pools
+            .values()
+            .filter(|&&stake| stake * 100_000 >= total * u128::from(threshold))
+            .count();
+        let min_finalizers = usize::try_from(parameters.min_finalizers).unwrap_or(usize::MAX);
+        let max_finalizers = usize::try_from(parameters.max_finalizers).unwrap_or(usize::MAX);
+        let size = eligible
+            .max(min_finalizers.min(pools.len()))
+            .min(max_finalizers);
+        if size == 0 {
+            self.error(
+                path,
+                "The finalization committee would be empty, so no blocks can be finalized.",
+            );
+        } else if pools.len() < min_finalizers {
+            self.warning(
+                format!("{path}.minFinalizers"),
+                format!(
+                    "The minimum number of finalizers {min_finalizers} exceeds the number of \
+                     genesis bakers {}.",
+                    pools.len()
+                ),
+            );
+        }
+    }
+
+    fn fractions_sum(&mut self, path: &str, name: &str, a: AmountFraction, b: AmountFraction) {
+        if (a + b).is_none() {
+            self.error(
+                path,
+                format!("The {name} fractions {a} and {b} sum to more than 1."),
+            );
+        }
+    }
+
+    fn mint_distribution_v0(&mut self, mint_distribution: &MintDistributionV0) {
+        self.fractions_sum(
+            "parameters.chain.rewardParameters.mintDistribution",
+            "baking and finalization reward",
+            mint_distribution.baking_reward,
+            mint_distribution.finalization_reward,
+        );
+    }
+
+    fn mint_distribution_v1(&mut self, mint_distribution: &MintDistributionV1) {
+        self.fractions_sum(
+            "parameters.chain.rewardParameters.mintDistribution",
+            "baking and finalization reward",
+            mint_distribution.baking_reward,
+            mint_distribution.finalization_reward,
+        );
+    }
+
+    fn transaction_fee_distribution(&mut self, distribution: &TransactionFeeDistribution) {
+        self.fractions_sum(
+            "parameters.chain.rewardParameters.transactionFeeDistribution",
+            "baker and GAS account",
+            distribution.baker,
+            distribution.gas_account,
+        );
+    }
+
+    /// Check the consensus parameters of protocol version 6 and later.
+    fn consensus_parameters(
+        &mut self,
+        core: &CoreGenesisParametersV1,
+        timeout_parameters: &TimeoutParameters,
+        min_block_time_millis: u64,
+    ) {
+        let path = "parameters.chain.timeoutParameters";
+        let base = timeout_parameters.base.millis();
+        if base == 0 {
+            self.error(format!("{path}.base"), "The base timeout must be positive.");
+        } else if min_block_time_millis >= base {
+            self.warning(
+                "parameters.chain.minBlockTime",
+                format!(
+                    "The minimum block time of {min_block_time_millis}ms is not below the base \
+                     timeout of {base}ms, so rounds may time out before blocks are baked."
+                ),
+            );
+        }
+        let increase = timeout_parameters.increase;
+        if increase.numerator() <= increase.denominator() {
+            self.error(
+                format!("{path}.increase"),
+                format!(
+                    "The timeout increase {} must be greater than 1.",
+                    show(increase)
+                ),
+            );
+        }
+        let decrease = timeout_parameters.decrease;
+        if decrease.numerator() == 0 || decrease.numerator() >= decrease.denominator() {
+            self.error(
+                format!("{path}.decrease"),
+                format!(
+                    "The timeout decrease {} must be between 0 and 1.",
+                    show(decrease)
+                ),
+            );
+        }
+        let threshold = core.signature_threshold;
+        let (numerator, denominator) = (
+            u128::from(threshold.numerator()),
+            u128::from(threshold.denominator()),
+        );
+        if 3 * numerator < 2 * denominator || numerator > denominator {
+            self.error(
+                "parameters.signatureThreshold",
+                format!(
+                    "The signature threshold {} must be between 2/3 and 1.",
+                    show(threshold)
+                ),
+            );
+        }
+    }
+}
+
+fn show(ratio: Ratio) -> String { format!("{}/{}", ratio.numerator(), ratio.denominator()) }
+
+/// Validate the genesis data, returning all problems found. The time `now` is
+/// used to check that the genesis time is not too far in the past.
+pub fn validate_genesis(genesis: &GenesisData, now: Timestamp) -> Vec<Problem> {
+    let mut validator = Validator::default();
+    match genesis {
+        GenesisData::P1 {
+            core,
+            initial_state,
+        }
+        | GenesisData::P2 {
+            core,
+            initial_state,
+        }
+        | GenesisData::P3 {
+            core,
+            initial_state,
+        } => {
+            let chain_parameters = &initial_state.chain_parameters;
+            let state = State {
+                accounts:                 &initial_state.accounts,
+                identity_providers:       &initial_state.identity_providers,
+                anonymity_revokers:       &initial_state.anonymity_revokers,
+                foundation_account_index: chain_parameters.foundation_account_index,
+            };
+            validator.genesis_time(core.time, now);
+            validator.accounts(&state);
+            validator.finalization_parameters(&state, &core.finalization_parameters);
+            let rewards = &chain_parameters.reward_parameters;
+            validator.mint_distribution_v0(&rewards.mint_distribution);
+            validator.transaction_fee_distribution(&rewards.transaction_fee_distribution);
+        }
+        GenesisData::P4 {
+            core,
+            initial_state,
+        }
+        | GenesisData::P5 {
+            core,
+            initial_state,
+        } => {
+            let chain_parameters = &initial_state.chain_parameters;
+            let state = State {
+                accounts:                 &initial_state.accounts,
+                identity_providers:       &initial_state.identity_providers,
+                anonymity_revokers:       &initial_state.anonymity_revokers,
+                foundation_account_index: chain_parameters.foundation_account_index,
+            };
+            let pool_parameters = &chain_parameters.pool_parameters;
+            validator.genesis_time(core.time, now);
+            validator.accounts(&state);
+            validator.minimum_equity_capital(&state, pool_parameters.minimum_equity_capital);
+            validator.finalization_parameters(&state, &core.finalization_parameters);
+            let rewards = &chain_parameters.reward_parameters;
+            validator.mint_distribution_v1(&rewards.mint_distribution);
+            validator.transaction_fee_distribution(&rewards.transaction_fee_distribution);
+        }
+        GenesisData::P6 {
+            core,
+            initial_state,
+        }
+        | GenesisData::P7 {
+            core,
+            initial_state,
+        } => {
+            let chain_parameters = &initial_state.chain_parameters;
+            let state = State {
+                accounts:                 &initial_state.accounts,
+                identity_providers:       &initial_state.identity_providers,
+                anonymity_revokers:       &initial_state.anonymity_revokers,
+                foundation_account_index: chain_parameters.foundation_account_index,
+            };
+            let pool_parameters = &chain_parameters.pool_parameters;
+            validator.genesis_time(core.genesis_time, now);
+            validator.accounts(&state);
+            validator.minimum_equity_capital(&state, pool_parameters.minimum_equity_capital);
+            validator.finalization_committee_parameters(
+                &state,
+                &chain_parameters.finalization_committee_parameters,
+            );
+            let rewards = &chain_parameters.reward_parameters;
+            validator.mint_distribution_v1(&rewards.mint_distribution);
+            validator.transaction_fee_distribution(&rewards.transaction_fee_distribution);
+            validator.consensus_parameters(
+                core,
+                &chain_parameters.timeout_parameters,
+                chain_parameters.min_block_time.millis(),
+            );
+        }
+    }
+    validator.problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        assemble::AssembleGenesisConfig,
+        config::parse_config,
+        tests::{generate_example, test_dir},
+    };
+    use concordium_rust_sdk::{
+        common::Deserial,
+        id::secret_sharing::Threshold,
+        smart_contracts::common::Duration,
+        types::{PartsPerHundredThousands, ProtocolVersion},
+    };
+    use std::{path::Path, sync::OnceLock};
+
+    /// The genesis of the `mainnet-assemble.toml` example, with protocol
+    /// version 1.
+    fn mainnet() -> GenesisData {
+        let config_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("examples")
+            .join("mainnet-assemble.toml");
+        let config: AssembleGenesisConfig =
+            parse_config(&std::fs::read(&config_path).unwrap()).unwrap();
+        crate::assemble_genesis(&config_path, config, false).unwrap()
+    }
+
+    /// The genesis of the `genesis6.toml` example, with protocol version 6.
+    /// It is only generated once, since generating it is slow.
+    fn genesis6() -> GenesisData {
+        static GENESIS: OnceLock<Vec<u8>> = OnceLock::new();
+        let bytes = GENESIS.get_or_init(|| {
+            let dir = test_dir("validate");
+            let bytes = std::fs::read(generate_example("genesis6.toml", &dir)).unwrap();
+            std::fs::remove_dir_all(dir).unwrap();
+            bytes
+        });
+        GenesisData::deserial(&mut std::io::Cursor::new(bytes)).unwrap()
+    }
+
+    /// The genesis time of the mainnet genesis, when it is not too old.
+    fn mainnet_time() -> Timestamp { Timestamp::from(1623218400000) }
+
+    fn mainnet_state(
+        genesis: &mut GenesisData,
+    ) -> (&mut CoreGenesisParametersV0, &mut GenesisStateCPV0) {
+        match genesis {
+            GenesisData::P1 {
+                core,
+                initial_state,
+            } => (core, initial_state),
+            _ => panic!("The mainnet genesis has protocol version 1."),
+        }
+    }
+
+    fn genesis6_state(
+        genesis: &mut GenesisData,
+    ) -> (&mut CoreGenesisParametersV1, &mut GenesisStateCPV2) {
+        match genesis {
+            GenesisData::P6 {
+                core,
+                initial_state,
+            } => (core, initial_state),
+            _ => panic!("The example genesis6.toml has protocol version 6."),
+        }
+    }
+
+    /// The locations and severities of the problems found.
+    fn problems(genesis: &GenesisData, now: Timestamp) -> Vec<(Severity, Location)> {
+        validate_genesis(genesis, now)
+            .into_iter()
+            .map(|problem| (problem.severity, problem.location))
+            .collect()
+    }
+
+    fn error(location: impl Into<Location>) -> (Severity, Location) {
+        (Severity::Error, location.into())
+    }
+
+    fn warning(location: impl Into<Location>) -> (Severity, Location) {
+        (Severity::Warning, location.into())
+    }
+
+    #[test]
+    fn examples_are_valid() {
+        let genesis = mainnet();
+        assert_eq!(genesis.protocol_version(), ProtocolVersion::P1);
+        assert_eq!(problems(&genesis, mainnet_time()), []);
+        let genesis = genesis6();
+        assert_eq!(genesis.protocol_version(), ProtocolVersion::P6);
+        assert_eq!(problems(&genesis, Timestamp::now()), []);
+    }
+
+    #[test]
+    fn old_genesis_time_is_a_warning() {
+        assert_eq!(problems(&mainnet(), Timestamp::now()), [warning(
+            "parameters.genesisTime"
+        )]);
+    }
+
+    #[test]
+    fn foundation_account_must_exist() {
+        let mut genesis = mainnet();
+        let (_, state) = mainnet_state(&mut genesis);
+        let index = state.accounts.len() as u64;
+        state.chain_parameters.foundation_account_index = AccountIndex::from(index);
+        assert_eq!(problems(&genesis, mainnet_time()), [error(
+            Location::FoundationAccount(index)
+        )]);
+    }
+
+    #[test]
+    fn bakers_must_have_stake() {
+        let mut genesis = mainnet();
+        let (_, state) = mainnet_state(&mut genesis);
+        for baker in state.accounts.iter_mut().filter_map(|a| a.baker.as_mut()) {
+            baker.stake = Amount::zero();
+        }
+        assert_eq!(problems(&genesis, mainnet_time()), [
+            error(Location::Accounts),
+            error("parameters.finalization.committeeMaxSize"),
+        ]);
+
+        let (_, state) = mainnet_state(&mut genesis);
+        for account in state.accounts.iter_mut() {
+            account.baker = None;
+        }
+        assert_eq!(problems(&genesis, mainnet_time()), [error(
+            Location::Accounts
+        )]);
+    }
+
+    #[test]
+    fn credentials_must_use_genesis_identity_providers_and_anonymity_revokers() {
+        let mut genesis = mainnet();
+        let (_, state) = mainnet_state(&mut genesis);
+        let location = Location::Account {
+            index: 0,
+            field: AccountField::Credential(0),
+        };
+        let AccountCredentialWithoutProofs::Normal { cdv, .. } =
+            state.accounts[0].credentials.values_mut().next().unwrap()
+        else {
+            panic!("The mainnet genesis accounts have normal credentials.");
+        };
+        let ip_identity = cdv.ip_identity;
+        let ar_identity = *cdv.ar_data.keys().next().unwrap();
+        cdv.threshold = Threshold(u8::try_from(cdv.ar_data.len()).unwrap() + 1);
+        assert_eq!(problems(&genesis, mainnet_time()), [error(
+            location.clone()
+        )]);
+
+        let (_, state) = mainnet_state(&mut genesis);
+        state.identity_providers.remove(&ip_identity);
+        state.anonymity_revokers.remove(&ar_identity);
+        let found = problems(&genesis, mainnet_time());
+        // Removing them affects all credentials issued by them.
+        assert!(found
+            .iter()
+            .all(|(severity, location)| *severity == Severity::Error
+                && matches!(location, Location::Account {
+                    field: AccountField::Credential(_),
+                    ..
+                })));
+        assert_eq!(
+            found.iter().filter(|problem| problem.1 == location).count(),
+            3
+        );
+    }
+
+    #[test]
+    fn bakers_must_match_their_account() {
+        let mut genesis = mainnet();
+        let (_, state) = mainnet_state(&mut genesis);
+        let (index, account) = state
+            .accounts
+            .iter_mut()
+            .enumerate()
+            .find(|(_, account)| account.baker.is_some())
+            .unwrap();
+        let index = index as u64;
+        let balance = account.balance;
+        let baker = account.baker.as_mut().unwrap();
+        baker.stake = Amount::from_micro_ccd(balance.micro_ccd + 1);
+        baker.baker_id = AccountIndex::from(index + 1).into();
+        assert_eq!(problems(&genesis, mainnet_time()), [
+            error(Location::Account {
+                index,
+                field: AccountField::Stake,
+            }),
+            error(Location::Account {
+                index,
+                field: AccountField::BakerId,
+            }),
+        ]);
+    }
+
+    #[test]
+    fn bakers_need_the_minimum_equity_capital() {
+        let mut genesis = genesis6();
+        let (_, state) = genesis6_state(&mut genesis);
+        let minimum = state
+            .chain_parameters
+            .pool_parameters
+            .minimum_equity_capital;
+        state.accounts[0].baker.as_mut().unwrap().stake =
+            Amount::from_micro_ccd(minimum.micro_ccd - 1);
+        assert_eq!(problems(&genesis, Timestamp::now()), [error(
+            Location::Account {
+                index: 0,
+                field: AccountField::Stake,
+            }
+        )]);
+    }
+
+    #[test]
+    fn finalization_committee_must_be_reachable() {
+        let mut genesis = mainnet();
+        let (core, _) = mainnet_state(&mut genesis);
+        let set_max_size = |core: &mut CoreGenesisParametersV0, size: u32| {
+            let mut parameters = serde_json::to_value(&core.finalization_parameters).unwrap();
+            parameters["committeeMaxSize"] = size.into();
+            core.finalization_parameters = serde_json::from_value(parameters).unwrap();
+        };
+        set_max_size(core, 0);
+        assert_eq!(problems(&genesis, mainnet_time()), [error(
+            "parameters.finalization.committeeMaxSize"
+        )]);
+        // No baker has a stake of half the total amount of CCD.
+        let (core, _) = mainnet_state(&mut genesis);
+        set_max_size(core, 2);
+        assert_eq!(problems(&genesis, mainnet_time()), [error(
+            "parameters.finalization.committeeMaxSize"
+        )]);
+    }
+
+    #[test]
+    fn finalization_committee_parameters_must_allow_a_committee() {
+        let mut genesis = genesis6();
+        let (_, state) = genesis6_state(&mut genesis);
+        let bakers = state.accounts.iter().filter(|a| a.baker.is_some()).count() as u32;
+        let parameters = &mut state.chain_parameters.finalization_committee_parameters;
+        parameters.min_finalizers = bakers + 1;
+        parameters.max_finalizers = bakers + 2;
+        assert_eq!(problems(&genesis, Timestamp::now()), [warning(
+            "parameters.chain.finalizationCommitteeParameters.minFinalizers"
+        )]);
+
+        let (_, state) = genesis6_state(&mut genesis);
+        let parameters = &mut state.chain_parameters.finalization_committee_parameters;
+        parameters.min_finalizers = 1;
+        parameters.max_finalizers = 0;
+        parameters.finalizers_relative_stake_threshold =
+            PartsPerHundredThousands::new(100_000).unwrap();
+        assert_eq!(problems(&genesis, Timestamp::now()), [
+            error("parameters.chain.finalizationCommitteeParameters.minFinalizers"),
+            error("parameters.chain.finalizationCommitteeParameters"),
+        ]);
+    }
+
+    #[test]
+    fn reward_fractions_must_sum_to_at_most_one() {
+        let half = AmountFraction::new(50_000).unwrap();
+        let more_than_half = AmountFraction::new(50_001).unwrap();
+        let mut genesis = mainnet();
+        let (_, state) = mainnet_state(&mut genesis);
+        let rewards = &mut state.chain_parameters.reward_parameters;
+        rewards.mint_distribution.baking_reward = half;
+        rewards.mint_distribution.finalization_reward = more_than_half;
+        rewards.transaction_fee_distribution.baker = more_than_half;
+        rewards.transaction_fee_distribution.gas_account = half;
+        assert_eq!(problems(&genesis, mainnet_time()), [
+            error("parameters.chain.rewardParameters.mintDistribution"),
+            error("parameters.chain.rewardParameters.transactionFeeDistribution"),
+        ]);
+
+        let mut genesis = genesis6();
+        let (_, state) = genesis6_state(&mut genesis);
+        let rewards = &mut state.chain_parameters.reward_parameters;
+        rewards.mint_distribution.baking_reward = more_than_half;
+        rewards.mint_distribution.finalization_reward = half;
+        assert_eq!(problems(&genesis, Timestamp::now()), [error(
+            "parameters.chain.rewardParameters.mintDistribution"
+        )]);
+    }
+
+    #[test]
+    fn consensus_parameters_must_be_sensible() {
+        let mut genesis = genesis6();
+        let (core, state) = genesis6_state(&mut genesis);
+        let timeout_parameters = &mut state.chain_parameters.timeout_parameters;
+        timeout_parameters.increase = Ratio::new(1, 1).unwrap();
+        timeout_parameters.decrease = Ratio::new(1, 1).unwrap();
+        state.chain_parameters.min_block_time = timeout_parameters.base;
+        core.signature_threshold = Ratio::new(1, 2).unwrap();
+        assert_eq!(problems(&genesis, Timestamp::now()), [
+            warning("parameters.chain.minBlockTime"),
+            error("parameters.chain.timeoutParameters.increase"),
+            error("parameters.chain.timeoutParameters.decrease"),
+            error("parameters.signatureThreshold"),
+        ]);
+
+        let (_, state) = genesis6_state(&mut genesis);
+        state.chain_parameters.timeout_parameters.base = Duration::from_millis(0);
+        assert!(problems(&genesis, Timestamp::now())
+            .contains(&error("parameters.chain.timeoutParameters.base")));
+    }
+
+    #[test]
+    fn accounts_are_located_where_they_are_configured() {
+        let stake = |index| Location::Account {
+            index,
+            field: AccountField::Stake,
+        };
+        let credential = Location::Account {
+            index: 2,
+            field: AccountField::Credential(0),
+        };
+
+        let origin = AccountOrigin::Genesis;
+        assert_eq!(origin.path(&stake(3)), "accounts[3].baker.stake");
+        assert_eq!(origin.path(&credential), "accounts[2].credentials[0]");
+        assert_eq!(
+            origin.path(&Location::FoundationAccount(7)),
+            "chainParameters.foundationAccountIndex"
+        );
+
+        let origin = AccountOrigin::AccountsFile(Path::new("accounts.json"));
+        assert_eq!(origin.path(&stake(3)), "accounts.json[3].baker.stake");
+        assert_eq!(origin.path(&credential), "accounts.json[2].credentials[0]");
+        assert_eq!(
+            origin.path(&Location::FoundationAccount(7)),
+            "foundationAccount"
+        );
+
+        // An entry with 5 bakers, an existing account, and 100 accounts of
+        // which the first is the foundation account.
+        let origin = AccountOrigin::Entries(&[5, 1, 100]);
+        assert_eq!(
+            origin.path(&stake(3)),
+            "accounts[0].stake (genesis account 3)"
+        );
+        assert_eq!(origin.path(&stake(5)), "accounts[1].stake");
+        assert_eq!(origin.path(&credential), "accounts[0] (genesis account 2)");
+        assert_eq!(
+            origin.path(&Location::FoundationAccount(6)),
+            "accounts[2].foundation"
+        );
+        assert_eq!(
+            origin.path(&"parameters.genesisTime".into()),
+            "parameters.genesisTime"
+        );
+    }
+}